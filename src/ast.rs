@@ -9,6 +9,7 @@ pub use self::constant::{Constant, TypedConstant, UntypedConstant};
 
 use crate::typ::{self, ModuleValueConstructor, PatternConstructor, Type, ValueConstructor};
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 pub const CAPTURE_VARIABLE: &str = "gleam@capture_variable";
@@ -133,6 +134,29 @@ pub enum TypeAst {
 }
 
 impl TypeAst {
+    pub fn find_node(&self, byte_index: usize) -> Option<&Self> {
+        let found = match self {
+            TypeAst::Constructor { args, .. } => {
+                args.iter().find_map(|arg| arg.find_node(byte_index))
+            }
+
+            TypeAst::Fn { args, retrn, .. } => args
+                .iter()
+                .find_map(|arg| arg.find_node(byte_index))
+                .or_else(|| retrn.find_node(byte_index)),
+
+            TypeAst::Tuple { elems, .. } => elems.iter().find_map(|e| e.find_node(byte_index)),
+
+            TypeAst::Var { .. } | TypeAst::Hole { .. } => None,
+        };
+
+        match self {
+            _ if found.is_some() => found,
+            _ if self.location().contains(byte_index) => Some(self),
+            _ => None,
+        }
+    }
+
     pub fn location(&self) -> &SrcSpan {
         match self {
             TypeAst::Fn { location, .. }
@@ -327,7 +351,7 @@ impl BinOp {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct CallArg<A> {
     pub label: Option<String>,
     pub location: SrcSpan,
@@ -502,12 +526,129 @@ impl TypedClauseGuard {
     }
 }
 
-#[derive(Debug, PartialEq, Default, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Default, Clone, Serialize, Deserialize)]
 pub struct SrcSpan {
     pub start: usize,
     pub end: usize,
 }
 
+impl SrcSpan {
+    /// Whether a cursor at the byte index is on the span. A cursor just
+    /// after the end is counted, as that is where it is left after typing
+    /// or selecting a name.
+    ///
+    pub fn contains(&self, byte_index: usize) -> bool {
+        byte_index >= self.start && byte_index <= self.end
+    }
+}
+
+/// The innermost node of a typed module found at a position in the source.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Located<'a> {
+    Expression(&'a TypedExpr),
+    Pattern(&'a TypedPattern),
+    Arg(&'a TypedArg),
+    Annotation(&'a TypeAst),
+    UnqualifiedImport {
+        module: &'a [String],
+        import: &'a UnqualifiedImport,
+    },
+    Statement(&'a TypedStatement),
+}
+
+impl TypedModule {
+    pub fn find_node(&self, byte_index: usize) -> Option<Located<'_>> {
+        self.statements
+            .iter()
+            .find_map(|statement| statement.find_node(byte_index))
+    }
+}
+
+impl TypedStatement {
+    pub fn find_node(&self, byte_index: usize) -> Option<Located<'_>> {
+        let found = match self {
+            Statement::Fn {
+                args,
+                return_annotation,
+                body,
+                ..
+            } => args
+                .iter()
+                .find_map(|arg| arg.find_node(byte_index))
+                .or_else(|| {
+                    return_annotation
+                        .as_ref()
+                        .and_then(|annotation| annotation.find_node(byte_index))
+                        .map(Located::Annotation)
+                })
+                .or_else(|| body.find_node(byte_index)),
+
+            Statement::TypeAlias { resolved_type, .. } => {
+                resolved_type.find_node(byte_index).map(Located::Annotation)
+            }
+
+            Statement::CustomType { constructors, .. } => constructors
+                .iter()
+                .flat_map(|constructor| constructor.args.iter())
+                .find_map(|(_, typ, _)| typ.find_node(byte_index))
+                .map(Located::Annotation),
+
+            Statement::ExternalFn { args, retrn, .. } => args
+                .iter()
+                .find_map(|arg| arg.typ.find_node(byte_index))
+                .or_else(|| retrn.find_node(byte_index))
+                .map(Located::Annotation),
+
+            Statement::ModuleConstant { annotation, .. } => annotation
+                .as_ref()
+                .and_then(|annotation| annotation.find_node(byte_index))
+                .map(Located::Annotation),
+
+            Statement::Import {
+                module,
+                unqualified,
+                ..
+            } => unqualified
+                .iter()
+                .find(|import| import.location.contains(byte_index))
+                .map(|import| Located::UnqualifiedImport { module, import }),
+
+            Statement::ExternalType { .. } => None,
+        };
+
+        match self {
+            _ if found.is_some() => found,
+
+            Statement::Fn { location, .. }
+            | Statement::ModuleConstant { location, .. }
+            | Statement::Import { location, .. }
+                if location.contains(byte_index) =>
+            {
+                Some(Located::Statement(self))
+            }
+
+            _ => None,
+        }
+    }
+}
+
+impl TypedArg {
+    pub fn find_node(&self, byte_index: usize) -> Option<Located<'_>> {
+        if !self.location.contains(byte_index) {
+            return None;
+        }
+        let annotation = self
+            .annotation
+            .as_ref()
+            .and_then(|annotation| annotation.find_node(byte_index));
+        match annotation {
+            Some(annotation) => Some(Located::Annotation(annotation)),
+            None => Some(Located::Arg(self)),
+        }
+    }
+}
+
 pub type UntypedPattern = Pattern<(), ()>;
 pub type TypedPattern = Pattern<PatternConstructor, Arc<typ::Type>>;
 
@@ -531,6 +672,7 @@ pub enum Pattern<Constructor, Type> {
     Var {
         location: SrcSpan,
         name: String,
+        typ: Type,
     },
 
     VarCall {
@@ -541,6 +683,8 @@ pub enum Pattern<Constructor, Type> {
 
     Let {
         name: String,
+        /// The location of the `as name` part of the pattern.
+        location: SrcSpan,
         pattern: Box<Self>,
     },
 
@@ -597,6 +741,41 @@ impl<A, B> Pattern<A, B> {
         }
     }
 
+    pub fn find_node(&self, byte_index: usize) -> Option<&Self> {
+        let found = match self {
+            Pattern::Let { pattern, .. } => pattern.find_node(byte_index),
+
+            Pattern::Cons { head, tail, .. } => head
+                .find_node(byte_index)
+                .or_else(|| tail.find_node(byte_index)),
+
+            Pattern::Constructor { args, .. } => {
+                args.iter().find_map(|arg| arg.value.find_node(byte_index))
+            }
+
+            Pattern::Tuple { elems, .. } => elems.iter().find_map(|e| e.find_node(byte_index)),
+
+            Pattern::BitString { segments, .. } => segments
+                .iter()
+                .find_map(|segment| segment.value.find_node(byte_index)),
+
+            Pattern::Int { .. }
+            | Pattern::Float { .. }
+            | Pattern::String { .. }
+            | Pattern::Var { .. }
+            | Pattern::VarCall { .. }
+            | Pattern::Discard { .. }
+            | Pattern::Nil { .. } => None,
+        };
+
+        match self {
+            _ if found.is_some() => found,
+            Pattern::Let { .. } => None,
+            _ if self.location().contains(byte_index) => Some(self),
+            _ => None,
+        }
+    }
+
     pub fn put_list_cons_location_start(self, start: usize) -> Self {
         match self {
             Pattern::Cons {
@@ -632,7 +811,7 @@ pub type UntypedConstantBitStringSegment = BitStringSegment<UntypedConstant, ()>
 pub type UntypedPatternBitStringSegment = BitStringSegment<UntypedPattern, ()>;
 pub type TypedPatternBitStringSegment = BitStringSegment<TypedPattern, Arc<typ::Type>>;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BitStringSegment<Value, Type> {
     pub location: SrcSpan,
     pub value: Box<Value>,
@@ -640,7 +819,7 @@ pub struct BitStringSegment<Value, Type> {
     pub typ: Type,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum BitStringSegmentOption<Value> {
     Binary {
         location: SrcSpan,
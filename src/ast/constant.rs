@@ -4,7 +4,7 @@ use crate::typ::HasType;
 pub type TypedConstant = Constant<Arc<Type>, String>;
 pub type UntypedConstant = Constant<(), ()>;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Constant<T, RecordTag> {
     Int {
         location: SrcSpan,
@@ -137,6 +137,7 @@ pub enum TypedExpr {
     RecordUpdate {
         location: SrcSpan,
         typ: Arc<Type>,
+        constructor: Box<Self>,
         spread: Box<Self>,
         args: Vec<TypedRecordUpdateArg>,
     },
@@ -200,6 +201,110 @@ impl TypedExpr {
     }
 }
 
+impl TypedExpr {
+    pub fn find_node(&self, byte_index: usize) -> Option<Located<'_>> {
+        let found = match self {
+            Self::Seq { first, then, .. } => first
+                .find_node(byte_index)
+                .or_else(|| then.find_node(byte_index)),
+
+            Self::Let {
+                pattern,
+                value,
+                then,
+                ..
+            } => pattern
+                .find_node(byte_index)
+                .map(Located::Pattern)
+                .or_else(|| value.find_node(byte_index))
+                .or_else(|| then.find_node(byte_index)),
+
+            Self::Fn {
+                args,
+                return_annotation,
+                body,
+                ..
+            } => args
+                .iter()
+                .find_map(|arg| arg.find_node(byte_index))
+                .or_else(|| {
+                    return_annotation
+                        .as_ref()
+                        .and_then(|annotation| annotation.find_node(byte_index))
+                        .map(Located::Annotation)
+                })
+                .or_else(|| body.find_node(byte_index)),
+
+            Self::ListCons { head, tail, .. } => head
+                .find_node(byte_index)
+                .or_else(|| tail.find_node(byte_index)),
+
+            Self::Call { fun, args, .. } => fun
+                .find_node(byte_index)
+                .or_else(|| args.iter().find_map(|arg| arg.value.find_node(byte_index))),
+
+            Self::BinOp { left, right, .. } | Self::Pipe { left, right, .. } => left
+                .find_node(byte_index)
+                .or_else(|| right.find_node(byte_index)),
+
+            Self::Case {
+                subjects, clauses, ..
+            } => subjects
+                .iter()
+                .find_map(|subject| subject.find_node(byte_index))
+                .or_else(|| {
+                    clauses.iter().find_map(|clause| {
+                        std::iter::once(&clause.pattern)
+                            .chain(clause.alternative_patterns.iter())
+                            .flatten()
+                            .find_map(|pattern| pattern.find_node(byte_index))
+                            .map(Located::Pattern)
+                            .or_else(|| clause.then.find_node(byte_index))
+                    })
+                }),
+
+            Self::RecordAccess { record, .. } => record.find_node(byte_index),
+
+            Self::Tuple { elems, .. } => elems.iter().find_map(|e| e.find_node(byte_index)),
+
+            Self::TupleIndex { tuple, .. } => tuple.find_node(byte_index),
+
+            Self::BitString { segments, .. } => segments
+                .iter()
+                .find_map(|segment| segment.value.find_node(byte_index)),
+
+            Self::RecordUpdate {
+                constructor,
+                spread,
+                args,
+                ..
+            } => constructor
+                .find_node(byte_index)
+                .or_else(|| spread.find_node(byte_index))
+                .or_else(|| args.iter().find_map(|arg| arg.value.find_node(byte_index))),
+
+            Self::Int { .. }
+            | Self::Float { .. }
+            | Self::String { .. }
+            | Self::Var { .. }
+            | Self::ListNil { .. }
+            | Self::ModuleSelect { .. }
+            | Self::Todo { .. } => None,
+        };
+
+        match self {
+            _ if found.is_some() => found,
+
+            // These have no location of their own, only that of their
+            // final expression
+            Self::Seq { .. } | Self::Let { .. } => None,
+
+            _ if self.location().contains(byte_index) => Some(Located::Expression(self)),
+            _ => None,
+        }
+    }
+}
+
 impl HasLocation for TypedExpr {
     fn location(&self) -> &SrcSpan {
         self.location()
@@ -1,11 +1,6 @@
 #![allow(warnings)]
 
-// TODO: Avoid rebuilding clean modules
-// TODO: Download deps from Hex
-// TODO: Support compilation of rebar3 packages
 // TODO: Track removed files in src and test so they can be removed from _build
-// TODO: Test profile and default profile
-// TODO: Only compile test code in test profile
 // TODO: Full .app generation
 // TODO: Validate config.otp_start_module does not contain '
 // TODO: Validate config.otp_start_module has a start function
@@ -14,26 +9,43 @@
 // - no .app generation
 // - no Erlang generation
 
-mod dep_tree;
+pub mod dep_tree;
+pub mod dependency_solver;
 mod erlang_code_generator;
-mod package_compiler;
+pub mod manifest;
+pub mod module_cache;
+pub mod package_compiler;
+pub mod package_downloader;
 mod project_compiler;
 pub mod project_root;
+pub mod version;
+
+#[cfg(test)]
+mod dependency_solver_tests;
 
 #[cfg(test)]
 mod package_compilation_tests;
 
+#[cfg(test)]
+mod package_downloader_tests;
+
 use crate::{
     ast::TypedModule,
     build::{
-        erlang_code_generator::ErlangCodeGenerator, project_compiler::ProjectCompiler,
+        dependency_solver::PackageIndex,
+        erlang_code_generator::ErlangCodeGenerator,
+        manifest::{Manifest, Unlock},
+        package_downloader::{HexRepository, Repository},
+        project_compiler::ProjectCompiler,
         project_root::ProjectRoot,
     },
     config::{self, PackageConfig},
+    diagnostic::DiagnosticFormat,
     erl,
-    error::{Error, FileIOAction, FileKind, GleamExpect},
+    error::{Error, FileIOAction, FileKind, GleamExpect, ResolutionProblem},
     fs::OutputFile,
     grammar, parser, typ,
+    warning::Warning,
 };
 use itertools::Itertools;
 use std::collections::HashMap;
@@ -42,8 +54,24 @@ use std::fs::DirEntry;
 use std::path::PathBuf;
 use std::process;
 
-pub fn main(root_config: PackageConfig, path: PathBuf) -> Result<HashMap<String, Package>, Error> {
-    let root = ProjectRoot::new(path);
+pub fn main(
+    root_config: PackageConfig,
+    path: PathBuf,
+    profile: Profile,
+    diagnostics_format: DiagnosticFormat,
+) -> Result<HashMap<String, Package>, Error> {
+    let mut root = ProjectRoot::new(path);
+    root.profile = profile;
+    let root_name = root_config.name.clone();
+    let warnings_as_errors = root_config.warnings_as_errors;
+
+    tracing::info!("Downloading dependencies");
+    let _ = download_dependencies(
+        &root,
+        &root_config,
+        &mut HexRepository::new(),
+        Unlock::Nothing,
+    )?;
 
     tracing::info!("Copying root package to _build");
     copy_root_package_to_build(&root, &root_config)?;
@@ -54,17 +82,111 @@ pub fn main(root_config: PackageConfig, path: PathBuf) -> Result<HashMap<String,
     tracing::info!("Compiling packages");
     let packages = ProjectCompiler::new(&root, root_config, configs).compile()?;
 
+    // Warnings in dependencies cannot be fixed by the user so only the
+    // warnings for the top level package are shown
+    tracing::info!("Printing warnings");
+    let warnings: Vec<_> = packages
+        .get(&root_name)
+        .into_iter()
+        .flat_map(|package| package.modules.iter())
+        .flat_map(|module| module.warnings.iter())
+        .collect();
+    for warning in warnings.iter() {
+        warning.print(diagnostics_format);
+    }
+    if warnings_as_errors && !warnings.is_empty() {
+        return Err(Error::WarningsAsErrors {
+            count: warnings.len(),
+        });
+    }
+
     tracing::info!("Writing generated Erlang source code to disc");
     for package in packages.values() {
         crate::fs::write_outputs(package.outputs.as_slice())?;
     }
 
     tracing::info!("Compiling Erlang source code to BEAM bytecode");
-    compile_erlang_to_beam(&root, &packages)?;
+    compile_erlang_to_beam(&root, &root_name, &packages)?;
 
     Ok(packages)
 }
 
+/// Choose a version of each dependency and dev-dependency of the top level
+/// package and download any required by the profile that are not already
+/// in `_build`. The versions in the manifest are used unless the
+/// requirements in `gleam.toml` have changed since it was written or they
+/// are unlocked, in which case versions are chosen again and the manifest
+/// updated.
+///
+pub fn download_dependencies(
+    root: &ProjectRoot,
+    config: &PackageConfig,
+    repository: &mut (impl Repository + PackageIndex),
+    unlock: Unlock,
+) -> Result<Manifest, Error> {
+    let previous = Manifest::read(root)?;
+
+    let resolved = match &previous {
+        Some(manifest)
+            if unlock == Unlock::Nothing
+                && manifest.is_up_to_date(&config.dependencies, &config.dev_dependencies) =>
+        {
+            manifest.resolved()
+        }
+        _ => {
+            if previous.is_some() && unlock == Unlock::Nothing {
+                crate::cli::print_green_prefix(
+                    "  Resolving",
+                    "versions, as the dependencies in gleam.toml have changed",
+                );
+            } else {
+                crate::cli::print_green_prefix("  Resolving", "versions");
+            }
+            let locked = previous
+                .as_ref()
+                .map(|manifest| manifest.locked(&unlock))
+                .unwrap_or_default();
+            // A package in both tables uses the requirement in dependencies
+            let mut requirements = config.dev_dependencies.clone();
+            requirements.extend(config.dependencies.clone());
+            dependency_solver::resolve(repository, &config.name, &requirements, &locked)?
+        }
+    };
+
+    // Each package to update must be one of the dependencies
+    if let Unlock::Packages(names) = &unlock {
+        if let Some(name) = names.iter().find(|name| !resolved.contains_key(*name)) {
+            return Err(Error::DependencyResolution {
+                package: name.clone(),
+                problem: ResolutionProblem::NotADependency,
+            });
+        }
+    }
+
+    let mut manifest = Manifest::new(&config.dependencies, &config.dev_dependencies, resolved);
+    let checksums = manifest.checksums();
+    let packages: Vec<_> = manifest
+        .profile_packages(root.profile)
+        .into_iter()
+        .map(|package| (package.name.clone(), package.version.clone()))
+        .collect();
+    let downloaded = package_downloader::download(root, repository, &packages, &checksums)?;
+    for downloaded in downloaded {
+        if let Some(package) = manifest
+            .packages
+            .iter_mut()
+            .find(|package| package.name == downloaded.name)
+        {
+            package.checksum = Some(downloaded.checksum);
+        }
+    }
+
+    if previous.as_ref() != Some(&manifest) {
+        crate::fs::write_output(&manifest.to_output(root))?;
+    }
+    Ok(manifest)
+}
+
 #[derive(Debug)]
 pub struct Package {
     pub config: PackageConfig,
@@ -78,7 +200,10 @@ pub struct Module {
     pub code: String,
     pub path: PathBuf,
     pub origin: Origin,
-    pub ast: TypedModule,
+    /// None if the module has not changed since the last build, in which
+    /// case its interface and Erlang were loaded from the cache.
+    pub ast: Option<TypedModule>,
+    pub warnings: Vec<Warning>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -87,12 +212,33 @@ pub enum Origin {
     Test,
 }
 
+/// The default profile is used to build the project, while the test
+/// profile also includes the test modules of the top level package and its
+/// dev-dependencies.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Profile {
+    Default,
+    Test,
+}
+
 fn compile_erlang_to_beam(
     root: &ProjectRoot,
+    root_name: &str,
     packages: &HashMap<String, Package>,
 ) -> Result<(), Error> {
     crate::cli::print_compiling("Erlang code");
 
+    // Packages are compiled after their dependencies as they may use their
+    // behaviours, parse transforms, and header files
+    let mut sequence = project_compiler::order_packages(
+        packages
+            .values()
+            .map(|package| &package.config)
+            .filter(|config| config.name != root_name),
+    )?;
+    sequence.push(root_name.to_string());
+
     let escript_path = root.build_path().join("compile_escript.erl");
     let escript_source = std::include_str!("build/compile_escript.erl").to_string();
 
@@ -104,7 +250,12 @@ fn compile_erlang_to_beam(
     // Run escript to compile Erlang to beam files
     let mut command = process::Command::new("escript");
     command.arg(escript_path);
-    command.arg(root.build_path());
+    command.arg(root.profile_path());
+    command.arg(match root.profile {
+        Profile::Default => "default",
+        Profile::Test => "test",
+    });
+    command.args(sequence);
 
     tracing::trace!("Running OS process {:?}", command);
     let status = command.status().map_err(|e| Error::ShellCommand {
@@ -126,17 +277,21 @@ fn copy_root_package_to_build(
     root: &ProjectRoot,
     root_config: &PackageConfig,
 ) -> Result<(), Error> {
-    let target = root.default_build_lib_package_path(&root_config.name);
+    let target = root.build_lib_package_path(&root_config.name);
     let path = &root.root;
 
-    // Reset _build dir
-    crate::fs::delete_dir(&target)?;
+    // Reset _build dir, keeping the cached modules from previous builds
+    crate::fs::delete_dir(&root.build_lib_package_src_path(&root_config.name))?;
+    crate::fs::delete_dir(&root.build_lib_package_test_path(&root_config.name))?;
+    crate::fs::delete_dir(&root.build_lib_package_ebin_path(&root_config.name))?;
     crate::fs::mkdir(&target)?;
 
     // Copy source files across
     crate::fs::copy(path.join("gleam.toml"), target.join("gleam.toml"))?;
     crate::fs::copy_dir(path.join("src"), &target)?;
-    crate::fs::copy_dir(path.join("test"), &target)?;
+    if root.profile == Profile::Test {
+        crate::fs::copy_dir(path.join("test"), &target)?;
+    }
 
     Ok(())
 }
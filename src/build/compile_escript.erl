@@ -1,16 +1,117 @@
 #!/usr/bin/env erlang
 
 % TODO: build in parallel
-main([ProfilePath]) ->
-    ErlangFiles = filelib:wildcard([ProfilePath, "/**/{src,test}/*.erl"]),
-    lists:foreach(fun(F) -> compile(F) end, ErlangFiles).
+%
+% Packages are given in the order they are to be compiled, with the top level
+% package last. Only the src directory of each dependency is compiled, while
+% the test directory of the top level package is also compiled for the test
+% profile.
+main([ProfilePath, Profile | Packages]) ->
+    Lib = filename:join(ProfilePath, "lib"),
+    PackagePaths = [filename:join(Lib, Package) || Package <- Packages],
+    RootDirs = case Profile of
+        "test" -> ["src", "test"];
+        "default" -> ["src"]
+    end,
+    Dirs = [["src"] || _ <- tl(Packages)] ++ [RootDirs],
 
-compile(ErlangFile) ->
-    EBin = ebin_path(ErlangFile),
+    % Each ebin directory is added to the code path so that compiled
+    % behaviours and parse transforms can be loaded, and so include_lib
+    % can find the header files of other packages
+    lists:foreach(fun add_ebin_to_path/1, PackagePaths),
+    lists:foreach(fun compile_package/1, lists:zip3(Packages, PackagePaths, Dirs)).
+
+add_ebin_to_path(PackagePath) ->
+    EBin = filename:join(PackagePath, "ebin"),
     ok = filelib:ensure_dir([EBin, $/]),
-    ErlangName = filename:rootname(ErlangFile),
-    {ok, _} = compile:file(ErlangName, [{outdir, EBin}, return_errors]).
+    true = code:add_patha(EBin).
+
+compile_package({Package, PackagePath, Dirs}) ->
+    EBin = filename:join(PackagePath, "ebin"),
+    Options = [
+        {outdir, EBin},
+        {i, filename:join(PackagePath, "include")},
+        {i, filename:join(PackagePath, "src")},
+        return_errors
+        | erl_opts(PackagePath)
+    ],
+    ErlangFiles = lists:append(
+        [filelib:wildcard(filename:join([PackagePath, Dir, "**", "*.erl"])) || Dir <- Dirs]
+    ),
+    compile_files(ErlangFiles, Options),
+    write_app_file(Package, PackagePath).
+
+% Modules that use a behaviour or parse transform defined in the same
+% package fail to compile until it has been compiled, so any that fail are
+% tried again until no more can be compiled.
+compile_files([], _Options) ->
+    ok;
+compile_files(ErlangFiles, Options) ->
+    case lists:partition(fun(F) -> compile_file(F, Options) end, ErlangFiles) of
+        {[], Failed} ->
+            lists:foreach(
+                fun(F) -> compile:file(F, [report_errors | Options]) end,
+                Failed
+            ),
+            halt(1);
+        {_, Failed} ->
+            compile_files(Failed, Options)
+    end.
+
+compile_file(ErlangFile, Options) ->
+    case compile:file(ErlangFile, Options) of
+        {ok, _} -> true;
+        _ -> false
+    end.
+
+% The compiler options from the erl_opts of the package's rebar.config.
+% Warnings are not made errors as warnings in dependencies cannot be fixed
+% by the user.
+erl_opts(PackagePath) ->
+    case file:consult(filename:join(PackagePath, "rebar.config")) of
+        {ok, Config} ->
+            ErlOpts = proplists:get_value(erl_opts, Config, []),
+            lists:flatmap(fun(Opt) -> erl_opt(PackagePath, Opt) end, ErlOpts);
+        {error, _} ->
+            []
+    end.
+
+erl_opt(_PackagePath, warnings_as_errors) ->
+    [];
+erl_opt(PackagePath, {i, Dir}) ->
+    [{i, filename:join(PackagePath, Dir)}];
+erl_opt(_PackagePath, {platform_define, Regex, Name}) ->
+    platform_define(Regex, {d, Name});
+erl_opt(_PackagePath, {platform_define, Regex, Name, Value}) ->
+    platform_define(Regex, {d, Name, Value});
+erl_opt(_PackagePath, {src_dirs, _}) ->
+    [];
+erl_opt(_PackagePath, Opt) ->
+    [Opt].
+
+platform_define(Regex, Define) ->
+    Platform = lists:flatten([
+        erlang:system_info(otp_release), "-",
+        erlang:system_info(system_architecture), "-",
+        integer_to_list(erlang:system_info(wordsize) * 8)
+    ]),
+    case re:run(Platform, Regex, [{capture, none}]) of
+        match -> [Define];
+        nomatch -> []
+    end.
 
-ebin_path(File) ->
-    PackageRoot = filename:dirname(filename:dirname(File)),
-    filename:join(PackageRoot, "ebin").
+% Gleam packages have their .app file generated by the Gleam compiler, while
+% for others it is created from the .app.src file, listing the compiled
+% modules.
+write_app_file(Package, PackagePath) ->
+    AppFile = filename:join([PackagePath, "ebin", Package ++ ".app"]),
+    AppSrcFile = filename:join([PackagePath, "src", Package ++ ".app.src"]),
+    case filelib:is_file(AppFile) orelse file:consult(AppSrcFile) of
+        {ok, [{application, Name, Properties}]} ->
+            Beams = filelib:wildcard(filename:join([PackagePath, "ebin", "*.beam"])),
+            Modules = [list_to_atom(filename:basename(B, ".beam")) || B <- Beams],
+            App = {application, Name, lists:keystore(modules, 1, Properties, {modules, Modules})},
+            ok = file:write_file(AppFile, io_lib:format("~p.~n", [App]));
+        _ ->
+            ok
+    end.
@@ -0,0 +1,431 @@
+//! The versions of the dependencies of a project are chosen by a
+//! backtracking search over the releases of each required package. The
+//! newest version of a package that meets every requirement on it is tried
+//! first, along with the requirements of that release on other packages.
+//! If the search reaches a package with no suitable version it goes back
+//! to the most recently chosen package responsible for the problem, passing
+//! over any chosen since whose versions have nothing to do with it, and
+//! tries an older version of that one.
+//!
+//! Versions locked by the manifest of the project are tried before any
+//! others so that they only change when they no longer meet the
+//! requirements.
+//!
+//! A release which requires a package that does not exist, or which has an
+//! invalid requirement, cannot be chosen, so the search goes back in the
+//! same way.
+//!
+//! If there is no solution the problem found deepest in the search is
+//! reported, as it is the one closest to being solved. When no version of a
+//! package meets the requirements on it, the requirements from each release
+//! that was tried are given together.
+
+use crate::{
+    build::version::{Requirement, Version},
+    error::{Error, GleamExpect, ResolutionProblem},
+};
+use itertools::Itertools;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// A requirement of a package release on another package.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency {
+    pub name: String,
+    pub requirement: String,
+    /// Optional dependencies are only used if another package requires
+    /// them, in which case the requirement still applies.
+    pub optional: bool,
+}
+
+/// The dependencies of a package release, along with the checksum of its
+/// tarball.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    pub dependencies: Vec<Dependency>,
+    /// The SHA-256 checksum of the release tarball, in uppercase hex.
+    pub checksum: String,
+}
+
+/// A chosen package release and the packages it depends on.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolved {
+    pub version: Version,
+    /// The names of the chosen packages required by this release.
+    pub dependencies: Vec<String>,
+    /// The checksum of the release tarball, or None if it was read from a
+    /// manifest that did not record it.
+    pub checksum: Option<String>,
+}
+
+/// The releases of packages and their dependencies, normally fetched from
+/// the Hex API.
+///
+pub trait PackageIndex {
+    /// The released versions of a package, or None if there is no package
+    /// with that name.
+    fn versions(&mut self, name: &str) -> Result<Option<Vec<Version>>, Error>;
+
+    /// The dependencies and checksum of a package release.
+    fn release(&mut self, name: &str, version: &Version) -> Result<Release, Error>;
+}
+
+/// Choose a version of each package required by the top level package,
+/// either directly or by another required package, preferring the locked
+/// version of each package.
+///
+pub fn resolve(
+    index: &mut impl PackageIndex,
+    root_name: &str,
+    requirements: &HashMap<String, String>,
+    locked: &HashMap<String, Version>,
+) -> Result<BTreeMap<String, Resolved>, Error> {
+    let mut requirements: Vec<_> = requirements
+        .iter()
+        .map(|(name, requirement)| Dependency {
+            name: name.clone(),
+            requirement: requirement.clone(),
+            optional: false,
+        })
+        .collect();
+    requirements.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut solver = Solver {
+        index,
+        root_name,
+        locked,
+        versions: HashMap::new(),
+        dependencies: HashMap::new(),
+        checksums: HashMap::new(),
+        conflict: None,
+    };
+    let mut constraints = solver.constraints(None, root_name, requirements)?;
+    let mut selected = BTreeMap::new();
+    if let Search::Solved = solver.search(&mut selected, &mut constraints)? {
+        Ok(selected
+            .iter()
+            .map(|(name, version)| {
+                // Optional dependencies are only recorded if they were chosen
+                let key = (name.clone(), version.clone());
+                let dependencies = solver.dependencies[&key]
+                    .as_ref()
+                    .gleam_expect("Getting dependencies of chosen release")
+                    .iter()
+                    .filter(|c| selected.contains_key(&c.package))
+                    .map(|c| c.package.clone())
+                    .dedup()
+                    .collect();
+                let checksum = solver.checksums.get(&key).cloned();
+                let version = version.clone();
+                (
+                    name.clone(),
+                    Resolved {
+                        version,
+                        dependencies,
+                        checksum,
+                    },
+                )
+            })
+            .collect())
+    } else {
+        let conflict = solver
+            .conflict
+            .gleam_expect("Dependency resolution failed without a conflict");
+        Err(Error::DependencyResolution {
+            package: conflict.package,
+            problem: conflict.problem,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Constraint {
+    package: String,
+    requirement: Requirement,
+    optional: bool,
+    /// The package, or package release, the requirement comes from.
+    required_by: String,
+    /// The package whose release the requirement comes from, or None for
+    /// the top level package.
+    parent: Option<String>,
+}
+
+/// The outcome of choosing versions for the packages not yet chosen.
+///
+enum Search {
+    Solved,
+    /// There is no way to choose them with the versions chosen for these
+    /// packages.
+    Failed(HashSet<String>),
+}
+
+/// The reason a package could not be chosen at some depth of the search.
+///
+#[derive(Debug)]
+struct Conflict {
+    depth: usize,
+    package: String,
+    problem: ResolutionProblem,
+}
+
+struct Solver<'a, I> {
+    index: &'a mut I,
+    root_name: &'a str,
+    locked: &'a HashMap<String, Version>,
+    /// The versions of each package, newest first, or None if there is no
+    /// package with that name.
+    versions: HashMap<String, Option<Vec<Version>>>,
+    /// The dependencies of each release, or the package and problem of a
+    /// requirement of the release that is not valid.
+    dependencies: HashMap<(String, Version), Result<Vec<Constraint>, (String, ResolutionProblem)>>,
+    checksums: HashMap<(String, Version), String>,
+    conflict: Option<Conflict>,
+}
+
+impl<'a, I: PackageIndex> Solver<'a, I> {
+    /// Choose a version for the next package that is required but has not
+    /// been chosen yet. If there is no way to do so the chosen packages that
+    /// are responsible are returned, so that the packages chosen since can
+    /// be passed over.
+    ///
+    fn search(
+        &mut self,
+        selected: &mut BTreeMap<String, Version>,
+        constraints: &mut Vec<Constraint>,
+    ) -> Result<Search, Error> {
+        let name = match constraints
+            .iter()
+            .filter(|c| !c.optional && !selected.contains_key(&c.package))
+            .map(|c| &c.package)
+            .min()
+        {
+            Some(name) => name.clone(),
+            None => return Ok(Search::Solved),
+        };
+        // The packages that require this one, or limit its versions
+        let mut responsible: HashSet<_> = constraints
+            .iter()
+            .filter(|c| c.package == name)
+            .filter_map(|c| c.parent.clone())
+            .collect();
+
+        let candidates: Option<Vec<_>> = self.versions(&name)?.map(|versions| {
+            versions
+                .iter()
+                .filter(|version| {
+                    constraints
+                        .iter()
+                        .filter(|c| c.package == name)
+                        .all(|c| c.requirement.matches(version))
+                })
+                .cloned()
+                .collect()
+        });
+        let mut candidates = match candidates {
+            Some(candidates) => candidates,
+            None => {
+                let required_by = constraints
+                    .iter()
+                    .find(|c| c.package == name)
+                    .map(|c| c.required_by.clone())
+                    .unwrap_or_default();
+                let required_by_root = required_by == self.root_name;
+                let problem = ResolutionProblem::UnknownPackage { required_by };
+
+                // Other releases of the package that required this one may
+                // not require it, but the top level package always does
+                if required_by_root {
+                    return Err(Error::DependencyResolution {
+                        package: name,
+                        problem,
+                    });
+                }
+                self.record_problem(selected.len(), &name, problem);
+                return Ok(Search::Failed(responsible));
+            }
+        };
+        if candidates.is_empty() {
+            self.record_conflict(selected.len(), &name, constraints);
+            return Ok(Search::Failed(responsible));
+        }
+        if let Some(index) = self
+            .locked
+            .get(&name)
+            .and_then(|locked| candidates.iter().position(|version| version == locked))
+        {
+            let locked = candidates.remove(index);
+            candidates.insert(0, locked);
+        }
+
+        for version in candidates {
+            let dependencies = match self.dependencies(&name, &version)? {
+                Ok(dependencies) => dependencies,
+                Err((package, problem)) => {
+                    self.record_problem(selected.len() + 1, &package, problem);
+                    continue;
+                }
+            };
+
+            // The packages already chosen must meet the requirements of
+            // this release
+            let unmet = dependencies.iter().find(|dependency| {
+                selected
+                    .get(&dependency.package)
+                    .map_or(false, |chosen| !dependency.requirement.matches(chosen))
+            });
+            if let Some(unmet) = unmet {
+                let mut constraints = constraints.clone();
+                constraints.extend(dependencies.iter().cloned());
+                self.record_conflict(selected.len() + 1, &unmet.package, &constraints);
+                let _ = responsible.insert(unmet.package.clone());
+                continue;
+            }
+
+            let count = dependencies.len();
+            constraints.extend(dependencies);
+            let _ = selected.insert(name.clone(), version);
+            let failed = match self.search(selected, constraints)? {
+                Search::Solved => return Ok(Search::Solved),
+                Search::Failed(failed) => failed,
+            };
+            let _ = selected.remove(&name);
+            constraints.truncate(constraints.len() - count);
+
+            // Another version of this package cannot help, so go back to
+            // one of the packages that can
+            if !failed.contains(&name) {
+                return Ok(Search::Failed(failed));
+            }
+            responsible.extend(failed);
+        }
+        let _ = responsible.remove(&name);
+        Ok(Search::Failed(responsible))
+    }
+
+    fn versions(&mut self, name: &str) -> Result<Option<&[Version]>, Error> {
+        if !self.versions.contains_key(name) {
+            let versions = self.index.versions(name)?.map(|mut versions| {
+                versions.sort_by(|a, b| b.cmp(a));
+                versions
+            });
+            let _ = self.versions.insert(name.to_string(), versions);
+        }
+        Ok(self.versions[name].as_deref())
+    }
+
+    fn dependencies(
+        &mut self,
+        name: &str,
+        version: &Version,
+    ) -> Result<Result<Vec<Constraint>, (String, ResolutionProblem)>, Error> {
+        let key = (name.to_string(), version.clone());
+        if !self.dependencies.contains_key(&key) {
+            let Release {
+                mut dependencies,
+                checksum,
+            } = self.index.release(name, version)?;
+            dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+            let _ = self.checksums.insert(key.clone(), checksum);
+            let required_by = format!("{} {}", name, version);
+            let constraints = match self.constraints(Some(name), &required_by, dependencies) {
+                Ok(constraints) => Ok(constraints),
+                Err(Error::DependencyResolution { package, problem }) => Err((package, problem)),
+                Err(error) => return Err(error),
+            };
+            let _ = self.dependencies.insert(key.clone(), constraints);
+        }
+        Ok(self.dependencies[&key].clone())
+    }
+
+    fn constraints(
+        &self,
+        parent: Option<&str>,
+        required_by: &str,
+        dependencies: Vec<Dependency>,
+    ) -> Result<Vec<Constraint>, Error> {
+        dependencies
+            .into_iter()
+            // The top level package is not fetched from Hex
+            .filter(|dependency| dependency.name != self.root_name)
+            .map(|dependency| {
+                let requirement = Requirement::parse(&dependency.requirement).ok_or_else(|| {
+                    Error::DependencyResolution {
+                        package: dependency.name.clone(),
+                        problem: ResolutionProblem::InvalidRequirement {
+                            required_by: required_by.to_string(),
+                            requirement: dependency.requirement.clone(),
+                        },
+                    }
+                })?;
+                Ok(Constraint {
+                    package: dependency.name,
+                    requirement,
+                    optional: dependency.optional,
+                    required_by: required_by.to_string(),
+                    parent: parent.map(str::to_string),
+                })
+            })
+            .collect()
+    }
+
+    /// Record that no version of a package meets the requirements on it. If
+    /// that was already the problem found for the package, the requirements
+    /// are added to those found before, so that each release tried of the
+    /// packages requiring it is given.
+    ///
+    fn record_conflict(&mut self, depth: usize, package: &str, constraints: &[Constraint]) {
+        let mut requirements: Vec<_> = constraints
+            .iter()
+            .filter(|c| c.package == package)
+            .map(|c| (c.required_by.clone(), c.requirement.to_string()))
+            .collect();
+        if let Some(Conflict {
+            depth: conflict_depth,
+            package: conflict_package,
+            problem:
+                ResolutionProblem::Conflict {
+                    requirements: found,
+                    ..
+                },
+        }) = &mut self.conflict
+        {
+            if conflict_package == package {
+                requirements.retain(|requirement| !found.contains(requirement));
+                found.extend(requirements);
+                *conflict_depth = depth.max(*conflict_depth);
+                return;
+            }
+        }
+        let versions = self
+            .versions
+            .get(package)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .rev()
+            .map(|version| version.to_string())
+            .collect();
+        let problem = ResolutionProblem::Conflict {
+            requirements,
+            versions,
+        };
+        self.record_problem(depth, package, problem);
+    }
+
+    fn record_problem(&mut self, depth: usize, package: &str, problem: ResolutionProblem) {
+        if self
+            .conflict
+            .as_ref()
+            .map_or(false, |conflict| conflict.depth >= depth)
+        {
+            return;
+        }
+        self.conflict = Some(Conflict {
+            depth,
+            package: package.to_string(),
+            problem,
+        });
+    }
+}
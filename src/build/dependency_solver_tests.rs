@@ -0,0 +1,519 @@
+use super::{
+    dependency_solver::*,
+    version::{Requirement, Version},
+};
+use crate::error::{Error, ResolutionProblem};
+use std::collections::{BTreeMap, HashMap};
+
+#[test]
+fn version_parse_test() {
+    let version = |major, minor, patch, pre: &[&str], build: Option<&str>| Version {
+        major,
+        minor,
+        patch,
+        pre: pre.iter().map(|s| s.to_string()).collect(),
+        build: build.map(|s| s.to_string()),
+    };
+    assert_eq!(Version::parse("1.2.3"), Some(version(1, 2, 3, &[], None)));
+    assert_eq!(
+        Version::parse("0.10.0-rc.1"),
+        Some(version(0, 10, 0, &["rc", "1"], None))
+    );
+    assert_eq!(
+        Version::parse("1.0.0-alpha-2+build.5"),
+        Some(version(1, 0, 0, &["alpha-2"], Some("build.5")))
+    );
+    assert_eq!(
+        Version::parse("1.0.0+build.5").and_then(|version| version.build),
+        Some("build.5".to_string())
+    );
+    assert_eq!(Version::parse("1.2"), None);
+    assert_eq!(Version::parse("1.2.3.4"), None);
+    assert_eq!(Version::parse("01.2.3"), None);
+    assert_eq!(Version::parse("1.2.x"), None);
+    assert_eq!(Version::parse("1.2.3-"), None);
+    assert_eq!(Version::parse("1.2.3-rc..1"), None);
+    assert_eq!(Version::parse(""), None);
+
+    for text in &["1.2.3", "0.10.0-rc.1", "1.0.0-alpha-2+build.5"] {
+        assert_eq!(Version::parse(text).unwrap().to_string(), *text);
+    }
+}
+
+#[test]
+fn version_order_test() {
+    let versions = [
+        "1.0.0-alpha",
+        "1.0.0-alpha.1",
+        "1.0.0-alpha.beta",
+        "1.0.0-beta",
+        "1.0.0-beta.2",
+        "1.0.0-beta.11",
+        "1.0.0-rc.1",
+        "1.0.0",
+        "1.0.1",
+        "1.2.0",
+        "1.10.0",
+        "2.0.0",
+    ];
+    for pair in versions.windows(2) {
+        let (a, b) = (Version::parse(pair[0]), Version::parse(pair[1]));
+        assert!(a < b, "{} < {}", pair[0], pair[1]);
+    }
+
+    // Build metadata is not taken into account
+    let (a, b) = (Version::parse("1.0.0+a"), Version::parse("1.0.0+b"));
+    assert_eq!(a, b);
+    assert_eq!(a.cmp(&b), std::cmp::Ordering::Equal);
+}
+
+#[test]
+fn requirement_test() {
+    let matches = |requirement: &str, version: &str| {
+        Requirement::parse(requirement)
+            .unwrap()
+            .matches(&Version::parse(version).unwrap())
+    };
+
+    assert!(matches("~> 0.8", "0.8.0"));
+    assert!(matches("~> 0.8", "0.12.3"));
+    assert!(!matches("~> 0.8", "0.7.9"));
+    assert!(!matches("~> 0.8", "1.0.0"));
+
+    assert!(matches("~> 1.2.3", "1.2.3"));
+    assert!(matches("~> 1.2.3", "1.2.10"));
+    assert!(!matches("~> 1.2.3", "1.3.0"));
+
+    assert!(matches(">= 1.0.0 and < 2.0.0", "1.5.0"));
+    assert!(!matches(">= 1.0.0 and < 2.0.0", "2.0.0"));
+    assert!(!matches(">= 1.0.0 and < 2.0.0", "0.9.0"));
+
+    assert!(matches("1.4.0", "1.4.0"));
+    assert!(matches("== 1.4.0", "1.4.0"));
+    assert!(!matches("1.4.0", "1.4.1"));
+    assert!(matches("!= 1.4.0", "1.4.1"));
+    assert!(matches("> 1.4.0", "1.4.1"));
+    assert!(!matches("> 1.4.0", "1.4.0"));
+    assert!(matches("<= 1.4.0", "1.4.0"));
+
+    // `and` binds more tightly than `or`
+    assert!(matches("== 1.0.0 or >= 2.0.0 and < 3.0.0", "1.0.0"));
+    assert!(matches("== 1.0.0 or >= 2.0.0 and < 3.0.0", "2.5.0"));
+    assert!(!matches("== 1.0.0 or >= 2.0.0 and < 3.0.0", "3.0.0"));
+
+    // Pre-releases are only matched by requirements that refer to one
+    assert!(!matches(">= 1.0.0", "2.0.0-rc.1"));
+    assert!(matches(">= 2.0.0-rc.0", "2.0.0-rc.1"));
+    assert!(matches("~> 2.0.0-rc.0", "2.0.0"));
+
+    for invalid in &[
+        "",
+        "~>",
+        "1.0",
+        ">= 1.0",
+        "and 1.0.0",
+        "1.0.0 and",
+        "=> 1.0.0",
+    ] {
+        assert_eq!(Requirement::parse(invalid), None, "{}", invalid);
+    }
+}
+
+/// An index of packages held in memory, where each release has a list of
+/// dependencies with their requirements. Optional dependencies are written
+/// with a `?` after their name.
+///
+#[derive(Debug, Default)]
+struct Index {
+    packages: HashMap<String, Vec<(Version, Vec<Dependency>)>>,
+}
+
+impl Index {
+    fn new(releases: &[(&str, &str, &[(&str, &str)])]) -> Self {
+        let mut index = Index::default();
+        for (name, version, dependencies) in releases {
+            let dependencies = dependencies
+                .iter()
+                .map(|(name, requirement)| Dependency {
+                    name: name.trim_end_matches('?').to_string(),
+                    requirement: requirement.to_string(),
+                    optional: name.ends_with('?'),
+                })
+                .collect();
+            index
+                .packages
+                .entry(name.to_string())
+                .or_default()
+                .push((Version::parse(version).unwrap(), dependencies));
+        }
+        index
+    }
+}
+
+impl PackageIndex for Index {
+    fn versions(&mut self, name: &str) -> Result<Option<Vec<Version>>, Error> {
+        Ok(self.packages.get(name).map(|releases| {
+            releases
+                .iter()
+                .map(|(version, _)| version.clone())
+                .collect()
+        }))
+    }
+
+    fn release(&mut self, name: &str, version: &Version) -> Result<Release, Error> {
+        let dependencies = self.packages[name]
+            .iter()
+            .find(|(release, _)| release == version)
+            .map(|(_, dependencies)| dependencies.clone())
+            .unwrap();
+        Ok(Release {
+            dependencies,
+            checksum: format!("{}-{}", name, version),
+        })
+    }
+}
+
+fn resolve_with(
+    index: &mut Index,
+    requirements: &[(&str, &str)],
+) -> Result<BTreeMap<String, String>, Error> {
+    resolve_locked(index, requirements, &[])
+}
+
+fn resolve_locked(
+    index: &mut Index,
+    requirements: &[(&str, &str)],
+    locked: &[(&str, &str)],
+) -> Result<BTreeMap<String, String>, Error> {
+    let requirements = requirements
+        .iter()
+        .map(|(name, requirement)| (name.to_string(), requirement.to_string()))
+        .collect();
+    let locked = locked
+        .iter()
+        .map(|(name, version)| (name.to_string(), Version::parse(version).unwrap()))
+        .collect();
+    let versions = resolve(index, "app", &requirements, &locked)?;
+    Ok(versions
+        .into_iter()
+        .map(|(name, resolved)| (name, resolved.version.to_string()))
+        .collect())
+}
+
+fn solution(versions: &[(&str, &str)]) -> BTreeMap<String, String> {
+    versions
+        .iter()
+        .map(|(name, version)| (name.to_string(), version.to_string()))
+        .collect()
+}
+
+#[test]
+fn resolve_test() {
+    let mut index = Index::new(&[
+        ("gleam_stdlib", "0.8.0", &[]),
+        ("gleam_stdlib", "0.9.0", &[]),
+        ("gleam_stdlib", "0.10.1", &[]),
+        ("gleam_stdlib", "1.0.0", &[]),
+        ("gleam_stdlib", "1.1.0-rc.1", &[]),
+        ("gleam_otp", "0.1.0", &[("gleam_stdlib", "~> 0.8")]),
+        ("gleam_otp", "0.2.0", &[("gleam_stdlib", "~> 0.9")]),
+        (
+            "gleam_http",
+            "1.0.0",
+            &[("gleam_stdlib", ">= 0.9.0 and < 2.0.0")],
+        ),
+    ]);
+
+    // The newest versions meeting the requirements are chosen
+    assert_eq!(
+        resolve_with(&mut index, &[("gleam_otp", "~> 0.1")]),
+        Ok(solution(&[
+            ("gleam_otp", "0.2.0"),
+            ("gleam_stdlib", "0.10.1")
+        ]))
+    );
+    assert_eq!(
+        resolve_with(&mut index, &[("gleam_http", "~> 1.0")]),
+        Ok(solution(&[
+            ("gleam_http", "1.0.0"),
+            ("gleam_stdlib", "1.0.0")
+        ]))
+    );
+    assert_eq!(
+        resolve_with(
+            &mut index,
+            &[("gleam_otp", "0.1.0"), ("gleam_stdlib", "!= 0.10.1")]
+        ),
+        Ok(solution(&[
+            ("gleam_otp", "0.1.0"),
+            ("gleam_stdlib", "0.9.0")
+        ]))
+    );
+    assert_eq!(resolve_with(&mut index, &[]), Ok(solution(&[])));
+}
+
+#[test]
+fn resolve_backtracking_test() {
+    // The newest version of one is not compatible with the requirement on
+    // two, so an older version of it is chosen
+    let mut index = Index::new(&[
+        ("one", "1.0.0", &[("two", "~> 1.0")]),
+        ("one", "1.1.0", &[("two", "~> 2.0")]),
+        ("two", "1.0.0", &[]),
+        ("two", "1.3.0", &[]),
+        ("two", "2.0.0", &[]),
+    ]);
+    assert_eq!(
+        resolve_with(&mut index, &[("one", "~> 1.0"), ("two", "~> 1.0")]),
+        Ok(solution(&[("one", "1.0.0"), ("two", "1.3.0")]))
+    );
+
+    // The conflict is only found after choosing a version of a package
+    // required by the newest version of one
+    let mut index = Index::new(&[
+        ("one", "1.0.0", &[("three", "~> 1.0")]),
+        ("one", "2.0.0", &[("three", "~> 2.0")]),
+        ("three", "1.0.0", &[("four", "~> 1.0")]),
+        ("three", "2.0.0", &[("four", "~> 2.0")]),
+        ("four", "1.0.0", &[]),
+        ("four", "2.0.0", &[]),
+    ]);
+    assert_eq!(
+        resolve_with(&mut index, &[("one", ">= 1.0.0"), ("four", "< 2.0.0")]),
+        Ok(solution(&[
+            ("four", "1.0.0"),
+            ("one", "1.0.0"),
+            ("three", "1.0.0")
+        ]))
+    );
+}
+
+#[test]
+fn resolve_locked_test() {
+    let mut index = Index::new(&[
+        ("one", "1.0.0", &[("two", "~> 1.0")]),
+        ("one", "1.1.0", &[("two", "~> 1.1")]),
+        ("two", "1.0.0", &[]),
+        ("two", "1.1.0", &[]),
+        ("two", "1.2.0", &[]),
+    ]);
+
+    // Locked versions are chosen over newer ones
+    assert_eq!(
+        resolve_locked(
+            &mut index,
+            &[("one", "~> 1.0")],
+            &[("one", "1.0.0"), ("two", "1.1.0")]
+        ),
+        Ok(solution(&[("one", "1.0.0"), ("two", "1.1.0")]))
+    );
+
+    // Unless they no longer meet the requirements
+    assert_eq!(
+        resolve_locked(
+            &mut index,
+            &[("one", "~> 1.1")],
+            &[("one", "1.0.0"), ("two", "1.0.0")]
+        ),
+        Ok(solution(&[("one", "1.1.0"), ("two", "1.2.0")]))
+    );
+}
+
+#[test]
+fn resolve_optional_test() {
+    let mut index = Index::new(&[
+        ("one", "1.0.0", &[("two?", "~> 1.0")]),
+        ("two", "1.0.0", &[]),
+        ("two", "2.0.0", &[]),
+    ]);
+
+    // Optional dependencies are not included unless required elsewhere
+    assert_eq!(
+        resolve_with(&mut index, &[("one", "~> 1.0")]),
+        Ok(solution(&[("one", "1.0.0")]))
+    );
+
+    // But their requirements still apply when they are
+    assert_eq!(
+        resolve_with(&mut index, &[("one", "~> 1.0"), ("two", ">= 1.0.0")]),
+        Ok(solution(&[("one", "1.0.0"), ("two", "1.0.0")]))
+    );
+}
+
+#[test]
+fn resolve_dependencies_test() {
+    let mut index = Index::new(&[
+        ("one", "1.0.0", &[("two", "~> 1.0"), ("three?", "~> 1.0")]),
+        ("two", "1.0.0", &[("three?", "~> 1.0")]),
+        ("three", "1.0.0", &[]),
+    ]);
+    let mut dependencies = |requirements: &[(&str, &str)]| {
+        let requirements = requirements
+            .iter()
+            .map(|(name, requirement)| (name.to_string(), requirement.to_string()))
+            .collect();
+        resolve(&mut index, "app", &requirements, &HashMap::new())
+            .unwrap()
+            .into_iter()
+            .map(|(name, resolved)| (name, resolved.dependencies))
+            .collect::<Vec<_>>()
+    };
+
+    // The chosen packages each release depends on are recorded, including
+    // optional dependencies only if they were chosen
+    assert_eq!(
+        dependencies(&[("one", "~> 1.0")]),
+        vec![
+            ("one".to_string(), vec!["two".to_string()]),
+            ("two".to_string(), vec![]),
+        ]
+    );
+    assert_eq!(
+        dependencies(&[("one", "~> 1.0"), ("three", "~> 1.0")]),
+        vec![
+            (
+                "one".to_string(),
+                vec!["three".to_string(), "two".to_string()]
+            ),
+            ("three".to_string(), vec![]),
+            ("two".to_string(), vec!["three".to_string()]),
+        ]
+    );
+
+    // Along with the checksum of each chosen release
+    let requirements = vec![("two".to_string(), "~> 1.0".to_string())]
+        .into_iter()
+        .collect();
+    let resolved = resolve(&mut index, "app", &requirements, &HashMap::new()).unwrap();
+    assert_eq!(resolved["two"].checksum, Some("two-1.0.0".to_string()));
+}
+
+#[test]
+fn resolve_invalid_release_test() {
+    let mut index = Index::new(&[
+        ("one", "1.0.0", &[("two", "~> 1.0")]),
+        ("one", "1.1.0", &[("missing", "~> 1.0")]),
+        ("one", "1.2.0", &[("two", "~> wibble")]),
+        ("two", "1.0.0", &[]),
+    ]);
+
+    // Releases which require unknown packages or have invalid requirements
+    // are passed over for older releases
+    assert_eq!(
+        resolve_with(&mut index, &[("one", "~> 1.0")]),
+        Ok(solution(&[("one", "1.0.0"), ("two", "1.0.0")]))
+    );
+}
+
+#[test]
+fn resolve_error_test() {
+    let mut index = Index::new(&[
+        ("one", "1.0.0", &[("two", "~> 2.0")]),
+        ("one", "1.1.0", &[("two", "~> 2.1")]),
+        ("two", "1.0.0", &[]),
+        ("two", "2.0.0", &[]),
+        ("three", "1.0.0", &[("missing", "~> 1.0")]),
+        ("four", "1.0.0", &[("two", "~> wibble")]),
+    ]);
+
+    // The requirements from each release of one are given, as none of them
+    // can be chosen
+    assert_eq!(
+        resolve_with(&mut index, &[("one", "~> 1.0"), ("two", "~> 1.0")]),
+        Err(Error::DependencyResolution {
+            package: "two".to_string(),
+            problem: ResolutionProblem::Conflict {
+                requirements: vec![
+                    ("app".to_string(), "~> 1.0".to_string()),
+                    ("one 1.1.0".to_string(), "~> 2.1".to_string()),
+                    ("one 1.0.0".to_string(), "~> 2.0".to_string()),
+                ],
+                versions: vec!["1.0.0".to_string(), "2.0.0".to_string()],
+            }
+        })
+    );
+
+    assert_eq!(
+        resolve_with(&mut index, &[("one", "~> 3.0")]),
+        Err(Error::DependencyResolution {
+            package: "one".to_string(),
+            problem: ResolutionProblem::Conflict {
+                requirements: vec![("app".to_string(), "~> 3.0".to_string())],
+                versions: vec!["1.0.0".to_string(), "1.1.0".to_string()],
+            }
+        })
+    );
+
+    assert_eq!(
+        resolve_with(&mut index, &[("three", "~> 1.0")]),
+        Err(Error::DependencyResolution {
+            package: "missing".to_string(),
+            problem: ResolutionProblem::UnknownPackage {
+                required_by: "three 1.0.0".to_string(),
+            }
+        })
+    );
+
+    assert_eq!(
+        resolve_with(&mut index, &[("four", "1.0")]),
+        Err(Error::DependencyResolution {
+            package: "four".to_string(),
+            problem: ResolutionProblem::InvalidRequirement {
+                required_by: "app".to_string(),
+                requirement: "1.0".to_string(),
+            }
+        })
+    );
+
+    assert_eq!(
+        resolve_with(&mut index, &[("four", "1.0.0")]),
+        Err(Error::DependencyResolution {
+            package: "two".to_string(),
+            problem: ResolutionProblem::InvalidRequirement {
+                required_by: "four 1.0.0".to_string(),
+                requirement: "~> wibble".to_string(),
+            }
+        })
+    );
+}
+
+#[test]
+fn resolve_backjumping_test() {
+    // Every release of one requires a version of two that does not exist,
+    // and the packages chosen in between have many releases. Trying each
+    // combination of their versions would not finish, so the search goes
+    // straight back to one
+    let names = ["one", "three", "four", "five", "six", "seven"];
+    let versions: Vec<_> = (0..30).map(|minor| format!("1.{}.0", minor)).collect();
+    let two: &[(&str, &str)] = &[("two", "~> 2.0")];
+    let mut releases: Vec<(&str, &str, &[(&str, &str)])> = vec![("two", "1.0.0", &[])];
+    for name in names.iter() {
+        for version in versions.iter() {
+            let dependencies = if *name == "one" { two } else { &[] };
+            releases.push((name, version, dependencies));
+        }
+    }
+    let mut index = Index::new(&releases);
+
+    let requirements: Vec<_> = names.iter().map(|name| (*name, "~> 1.0")).collect();
+    let requirements: Vec<_> = requirements
+        .into_iter()
+        .chain(vec![("two", "~> 1.0")])
+        .collect();
+    let (package, requirements) = match resolve_with(&mut index, &requirements) {
+        Err(Error::DependencyResolution {
+            package,
+            problem: ResolutionProblem::Conflict { requirements, .. },
+        }) => (package, requirements),
+        result => panic!("Unexpected result {:?}", result),
+    };
+    assert_eq!(package, "two");
+    assert_eq!(requirements.len(), 31);
+    assert_eq!(
+        requirements[..2].to_vec(),
+        vec![
+            ("app".to_string(), "~> 1.0".to_string()),
+            ("one 1.29.0".to_string(), "~> 2.0".to_string()),
+        ]
+    );
+}
@@ -1,4 +1,5 @@
 use crate::{
+    ast::TypedModule,
     build::{project_root::ProjectRoot, Module},
     config::PackageConfig,
     erl,
@@ -25,29 +26,15 @@ impl<'a> ErlangCodeGenerator<'a> {
         }
     }
 
-    pub fn render(&self) -> Vec<OutputFile> {
-        let num_modules = self.modules.len();
-        let mut outputs = Vec::with_capacity(num_modules);
-
-        // Render Erlang modules and header files
-        for module in self.modules {
-            self.render_module(module, &mut outputs);
-        }
-
-        // Render ebin/package.app
-        outputs.push(self.package_app_file());
-
-        outputs
-    }
-
-    pub fn render_module(&self, module: &Module, outputs: &mut Vec<OutputFile>) {
+    pub fn render_module(&self, module: &Module, ast: &TypedModule) -> Vec<OutputFile> {
         let erl_name = module.name.replace("/", "@");
         let dir = self
             .root
-            .default_build_lib_package_source_path(&self.config.name, module.origin);
+            .build_lib_package_source_path(&self.config.name, module.origin);
+        let mut outputs = vec![];
 
         // Render record header files
-        for (name, text) in erl::records(&module.ast).into_iter() {
+        for (name, text) in erl::records(ast).into_iter() {
             let name = format!("{}_{}.hrl", erl_name, name);
             tracing::trace!(name = ?name, "Generated Erlang header");
             outputs.push(OutputFile {
@@ -57,17 +44,18 @@ impl<'a> ErlangCodeGenerator<'a> {
         }
 
         // Render Erlang module file
-        let text = erl::module(&module.ast);
+        let text = erl::module(ast);
         let name = format!("{}.erl", erl_name);
         tracing::trace!(name = ?name, "Generated Erlang module");
         let path = dir.join(name);
         outputs.push(OutputFile { path, text });
+        outputs
     }
 
     pub fn package_app_file(&self) -> OutputFile {
         let path = self
             .root
-            .default_build_lib_package_ebin_path(&self.config.name)
+            .build_lib_package_ebin_path(&self.config.name)
             .join(format!("{}.app", &self.config.name));
 
         let start_module = match &self.config.otp_start_module {
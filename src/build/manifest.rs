@@ -0,0 +1,195 @@
+//! The manifest records the version of each package chosen for the
+//! dependencies of a project, so that every build of the project uses the
+//! same versions until they are updated with `gleam deps update`. It is
+//! written to `manifest.toml` in the project root, and is intended to be
+//! committed to version control.
+//!
+//! The versions are chosen for the dependencies and dev-dependencies
+//! together, so that both profiles use the same version of a package, but
+//! only the packages required by the profile being built are downloaded.
+
+use crate::{
+    build::{dependency_solver::Resolved, project_root::ProjectRoot, version::Version, Profile},
+    error::{Error, FileIOAction, FileKind, GleamExpect},
+    fs::OutputFile,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+const HEADER: &str = "# This file was generated by Gleam
+# You typically do not need to edit this file
+
+";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// The requirements in `gleam.toml` when the versions were chosen, used
+    /// to tell when they have changed.
+    pub requirements: BTreeMap<String, String>,
+    #[serde(
+        default,
+        rename = "dev-requirements",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub dev_requirements: BTreeMap<String, String>,
+    #[serde(default)]
+    pub packages: Vec<ManifestPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestPackage {
+    pub name: String,
+    pub version: Version,
+    pub source: Source,
+    /// The SHA-256 checksum of the package tarball, in uppercase hex, as
+    /// given by Hex when the version was chosen.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    /// The packages this release depends on.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Hex,
+}
+
+/// Which of the versions locked by the manifest may change when choosing
+/// versions again.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Unlock {
+    Nothing,
+    All,
+    Packages(Vec<String>),
+}
+
+impl Manifest {
+    /// A manifest for the chosen versions of packages.
+    ///
+    pub fn new(
+        requirements: &HashMap<String, String>,
+        dev_requirements: &HashMap<String, String>,
+        resolved: BTreeMap<String, Resolved>,
+    ) -> Self {
+        let packages = resolved
+            .into_iter()
+            .map(|(name, resolved)| ManifestPackage {
+                checksum: resolved.checksum,
+                name,
+                version: resolved.version,
+                source: Source::Hex,
+                dependencies: resolved.dependencies,
+            })
+            .collect();
+        Self {
+            requirements: sorted(requirements),
+            dev_requirements: sorted(dev_requirements),
+            packages,
+        }
+    }
+
+    /// Read the manifest of a project, returning None if it has none.
+    ///
+    pub fn read(root: &ProjectRoot) -> Result<Option<Self>, Error> {
+        let path = root.manifest_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let toml = crate::fs::read(&path)?;
+        toml::from_str(&toml).map(Some).map_err(|e| Error::FileIO {
+            action: FileIOAction::Parse,
+            kind: FileKind::File,
+            path,
+            err: Some(e.to_string()),
+        })
+    }
+
+    pub fn to_output(&self, root: &ProjectRoot) -> OutputFile {
+        let toml = toml::to_string(self).gleam_expect("Serialising manifest");
+        OutputFile {
+            path: root.manifest_path(),
+            text: format!("{}{}", HEADER, toml),
+        }
+    }
+
+    /// Whether the versions were chosen for the given requirements.
+    ///
+    pub fn is_up_to_date(
+        &self,
+        requirements: &HashMap<String, String>,
+        dev_requirements: &HashMap<String, String>,
+    ) -> bool {
+        self.requirements == sorted(requirements)
+            && self.dev_requirements == sorted(dev_requirements)
+    }
+
+    pub fn resolved(&self) -> BTreeMap<String, Resolved> {
+        self.packages
+            .iter()
+            .map(|package| {
+                let resolved = Resolved {
+                    version: package.version.clone(),
+                    dependencies: package.dependencies.clone(),
+                    checksum: package.checksum.clone(),
+                };
+                (package.name.clone(), resolved)
+            })
+            .collect()
+    }
+
+    /// The packages required by a profile. Those only required by the
+    /// dev-dependencies are left out of the default profile.
+    ///
+    pub fn profile_packages(&self, profile: Profile) -> Vec<&ManifestPackage> {
+        let mut required: HashSet<&str> = self.requirements.keys().map(String::as_str).collect();
+        if profile == Profile::Test {
+            required.extend(self.dev_requirements.keys().map(String::as_str));
+        }
+        let mut stack: Vec<_> = required.iter().copied().collect();
+        while let Some(name) = stack.pop() {
+            let package = self.packages.iter().find(|package| package.name == name);
+            for dependency in package.into_iter().flat_map(|p| p.dependencies.iter()) {
+                if required.insert(dependency) {
+                    stack.push(dependency);
+                }
+            }
+        }
+        self.packages
+            .iter()
+            .filter(|package| required.contains(package.name.as_str()))
+            .collect()
+    }
+
+    /// The locked version of each package that is not being unlocked.
+    ///
+    pub fn locked(&self, unlock: &Unlock) -> HashMap<String, Version> {
+        self.packages
+            .iter()
+            .filter(|package| match unlock {
+                Unlock::Nothing => true,
+                Unlock::All => false,
+                Unlock::Packages(names) => !names.contains(&package.name),
+            })
+            .map(|package| (package.name.clone(), package.version.clone()))
+            .collect()
+    }
+
+    /// The known checksum of each package.
+    ///
+    pub fn checksums(&self) -> HashMap<String, String> {
+        self.packages
+            .iter()
+            .filter_map(|package| Some((package.name.clone(), package.checksum.clone()?)))
+            .collect()
+    }
+}
+
+fn sorted(requirements: &HashMap<String, String>) -> BTreeMap<String, String> {
+    requirements
+        .iter()
+        .map(|(name, requirement)| (name.clone(), requirement.clone()))
+        .collect()
+}
@@ -0,0 +1,176 @@
+//! The interface and generated Erlang of each compiled module are cached in
+//! `_build` so that later builds can skip parsing, type checking, and code
+//! generation for modules that have not changed.
+//!
+//! A cached module is only used if its source code is the same as when it
+//! was compiled and the interface of each module it imports is also the
+//! same, as otherwise the types and code of the module may be different.
+
+use crate::{error::GleamExpect, fs::OutputFile, typ};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// Fields of an interface that are not hashed.
+const IGNORED_FIELDS: &[&str] = &["origin", "location"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedModule {
+    /// Caches written by other versions of the compiler are not used, as
+    /// the types and generated code may differ between versions.
+    pub compiler_version: String,
+    pub source_hash: String,
+    /// The name and interface hash of each module imported when this
+    /// module was compiled.
+    pub dependencies: Vec<(String, String)>,
+    pub interface: typ::Module,
+    /// The file name and contents of the Erlang module and header files
+    /// generated for the module.
+    pub erlang: Vec<(String, String)>,
+}
+
+impl CachedModule {
+    pub fn new(
+        code: &str,
+        dependencies: Vec<(String, String)>,
+        interface: typ::Module,
+        erlang: &[OutputFile],
+    ) -> Self {
+        let erlang = erlang
+            .iter()
+            .map(|file| {
+                let name = file
+                    .path
+                    .file_name()
+                    .gleam_expect("Generated Erlang file name")
+                    .to_string_lossy()
+                    .to_string();
+                (name, file.text.clone())
+            })
+            .collect();
+        Self {
+            compiler_version: COMPILER_VERSION.to_string(),
+            source_hash: source_hash(code),
+            dependencies,
+            interface,
+            erlang,
+        }
+    }
+
+    /// Read a cached module, returning None if there is none or it was not
+    /// written by this version of the compiler.
+    ///
+    pub fn read(path: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(path).ok()?;
+        let cached: Self = serde_json::from_str(&text).ok()?;
+        Some(cached).filter(|cached| cached.compiler_version == COMPILER_VERSION)
+    }
+
+    pub fn to_output(&self, path: PathBuf) -> OutputFile {
+        let text = serde_json::to_string(self).gleam_expect("Serialising cached module");
+        OutputFile { path, text }
+    }
+
+    /// The generated Erlang files, written to the given directory.
+    ///
+    pub fn erlang_outputs(&self, dir: &Path) -> Vec<OutputFile> {
+        self.erlang
+            .iter()
+            .map(|(name, text)| OutputFile {
+                path: dir.join(name),
+                text: text.clone(),
+            })
+            .collect()
+    }
+}
+
+pub fn source_hash(code: &str) -> String {
+    hash(code)
+}
+
+/// The hash of a module interface, ignoring the parts of it that do not
+/// affect the modules that import it. These are the locations of
+/// definitions, which move as code is edited, and the ids of type variables,
+/// which depend on how many modules were type checked before it.
+///
+/// The maps of the interface are converted to JSON objects, which have
+/// sorted keys, so that the hash does not depend on the iteration order of
+/// the HashMaps.
+///
+pub fn interface_hash(interface: &typ::Module) -> String {
+    let json = serde_json::to_value(interface).gleam_expect("Serialising module interface");
+    hash(&renumber(json, IGNORED_FIELDS, 0, &mut HashMap::new()).to_string())
+}
+
+/// A copy of an interface with its type variables numbered from the given
+/// id, in the order they appear in the interface. The ids are given in the
+/// same way however the interface was type checked or loaded, so the
+/// interfaces of different modules can be kept apart by giving each a
+/// different start.
+///
+pub fn renumber_type_ids(interface: &typ::Module, start: usize) -> typ::Module {
+    let json = serde_json::to_value(interface).gleam_expect("Serialising module interface");
+    let json = renumber(json, &[], start, &mut HashMap::new());
+    serde_json::from_value(json).gleam_expect("Deserialising module interface")
+}
+
+/// The largest id of a type variable in an interface.
+///
+pub fn max_type_id(interface: &typ::Module) -> Option<usize> {
+    fn max_id(value: &Value) -> Option<u64> {
+        match value {
+            Value::Object(fields) => fields
+                .iter()
+                .filter_map(|(key, value)| match (key.as_str(), value.as_u64()) {
+                    ("id", Some(id)) => Some(id),
+                    _ => max_id(value),
+                })
+                .max(),
+            Value::Array(values) => values.iter().filter_map(max_id).max(),
+            _ => None,
+        }
+    }
+
+    let json = serde_json::to_value(interface).gleam_expect("Serialising module interface");
+    max_id(&json).map(|id| id as usize)
+}
+
+fn renumber(value: Value, ignored: &[&str], start: usize, ids: &mut HashMap<u64, usize>) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .filter(|(key, _)| !ignored.contains(&key.as_str()))
+                .map(|(key, value)| match (key.as_str(), value.as_u64()) {
+                    ("id", Some(id)) => {
+                        let next = start + ids.len();
+                        let id = *ids.entry(id).or_insert(next);
+                        (key, Value::from(id))
+                    }
+                    _ => (key, renumber(value, ignored, start, ids)),
+                })
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|value| renumber(value, ignored, start, ids))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+/// The SHA-256 hash of a string, which unlike the hashers of the standard
+/// library is the same for every version of Rust.
+///
+fn hash(value: &str) -> String {
+    ring::digest::digest(&ring::digest::SHA256, value.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
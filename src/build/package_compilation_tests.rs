@@ -17,12 +17,14 @@ fn package_compiler_test() {
             let mut modules = HashMap::new();
             let config = PackageConfig {
                 dependencies: HashMap::new(),
+                dev_dependencies: HashMap::new(),
                 description: "the description".to_string(),
                 version: Some("1.1.0".to_string()),
                 name: "the_package".to_string(),
                 docs: Default::default(),
                 otp_start_module: None,
                 tool: BuildTool::Gleam,
+                warnings_as_errors: false,
             };
             let root = ProjectRoot::new(PathBuf::new());
             let mut compiler = PackageCompiler::new(&root, config);
@@ -473,8 +475,8 @@ call_thing() ->\n    thing:new().\n"
                 path: PathBuf::from("/src/two.gleam"),
                 name: "two".to_string(),
                 code: "import one
-                        fn make() { one.Point(1, 4) }
-                        fn x(p) { let one.Point(x, _) = p x }"
+                        pub fn make() { one.Point(1, 4) }
+                        pub fn x(p) { let one.Point(x, _) = p x }"
                     .to_string(),
             },
         ],
@@ -490,7 +492,7 @@ call_thing() ->\n    thing:new().\n"
             },
             OutputFile {
                 path: PathBuf::from("_build/default/lib/the_package/src/two.erl"),
-                text: "-module(two).\n-compile(no_auto_import).\n
+                text: "-module(two).\n-compile(no_auto_import).\n\n-export([make/0, x/1]).\n
 make() ->\n    {point, 1, 4}.\n
 x(P) ->\n    {point, X, _} = P,\n    X.\n"
                     .to_string(),
@@ -512,7 +514,7 @@ x(P) ->\n    {point, X, _} = P,\n    X.\n"
                 path: PathBuf::from("/src/two.gleam"),
                 name: "two".to_string(),
                 code: "import one.{div}
-                    fn run() { 2 |> div(top: _, bottom: 4) |> div(2, bottom: _) }"
+                    pub fn run() { 2 |> div(top: _, bottom: 4) |> div(2, bottom: _) }"
                     .to_string(),
             },
         ],
@@ -526,7 +528,7 @@ x(P) ->\n    {point, X, _} = P,\n    X.\n"
             },
             OutputFile {
                 path: PathBuf::from("_build/default/lib/the_package/src/two.erl"),
-                text: "-module(two).\n-compile(no_auto_import).\n
+                text: "-module(two).\n-compile(no_auto_import).\n\n-export([run/0]).\n
 run() ->\n    one:'div'(2, one:'div'(2, 4)).\n"
                     .to_string(),
             },
@@ -546,7 +548,7 @@ run() ->\n    one:'div'(2, one:'div'(2, 4)).\n"
                 path: PathBuf::from("/src/two.gleam"),
                 name: "two".to_string(),
                 code: "import one
-                        fn make() { one.Empty }"
+                        pub fn make() { one.Empty }"
                     .to_string(),
             },
         ],
@@ -558,7 +560,7 @@ run() ->\n    one:'div'(2, one:'div'(2, 4)).\n"
             },
             OutputFile {
                 path: PathBuf::from("_build/default/lib/the_package/src/two.erl"),
-                text: "-module(two).\n-compile(no_auto_import).\n
+                text: "-module(two).\n-compile(no_auto_import).\n\n-export([make/0]).\n
 make() ->\n    empty.\n"
                     .to_string(),
             },
@@ -577,7 +579,7 @@ make() ->\n    empty.\n"
                 origin: Origin::Src,
                 path: PathBuf::from("/src/two.gleam"),
                 name: "two".to_string(),
-                code: "import one.{Empty, id} fn make() { id(Empty) }".to_string(),
+                code: "import one.{Empty, id} pub fn make() { id(Empty) }".to_string(),
             },
         ],
         Ok(vec![
@@ -590,7 +592,7 @@ id(X) ->\n    X.\n"
             },
             OutputFile {
                 path: PathBuf::from("_build/default/lib/the_package/src/two.erl"),
-                text: "-module(two).\n-compile(no_auto_import).\n
+                text: "-module(two).\n-compile(no_auto_import).\n\n-export([make/0]).\n
 make() ->\n    one:id(empty).\n"
                     .to_string(),
             },
@@ -610,7 +612,7 @@ make() ->\n    one:id(empty).\n"
                 origin: Origin::Src,
                 path: PathBuf::from("/src/two.gleam"),
                 name: "two".to_string(),
-                code: "import one.{Empty as e, id as i} fn make() { i(e) }".to_string(),
+                code: "import one.{Empty as e, id as i} pub fn make() { i(e) }".to_string(),
             },
         ],
         Ok(vec![
@@ -624,7 +626,7 @@ id(X) ->\n    X.\n"
             },
             OutputFile {
                 path: PathBuf::from("_build/default/lib/the_package/src/two.erl"),
-                text: "-module(two).\n-compile(no_auto_import).\n
+                text: "-module(two).\n-compile(no_auto_import).\n\n-export([make/0]).\n
 make() ->\n    one:id(empty).\n"
                     .to_string(),
             },
@@ -643,7 +645,7 @@ make() ->\n    one:id(empty).\n"
                 origin: Origin::Src,
                 path: PathBuf::from("/src/two.gleam"),
                 name: "two".to_string(),
-                code: "import one fn funky() { one.receive }".to_string(),
+                code: "import one pub fn funky() { one.receive }".to_string(),
             },
         ],
         Ok(vec![
@@ -657,7 +659,7 @@ make() ->\n    one:id(empty).\n"
             },
             OutputFile {
                 path: PathBuf::from("_build/default/lib/the_package/src/two.erl"),
-                text: "-module(two).\n-compile(no_auto_import).\n
+                text: "-module(two).\n-compile(no_auto_import).\n\n-export([funky/0]).\n
 funky() ->\n    fun one:'receive'/0.\n"
                     .to_string(),
             },
@@ -676,7 +678,7 @@ funky() ->\n    fun one:'receive'/0.\n"
                 origin: Origin::Src,
                 path: PathBuf::from("/src/two.gleam"),
                 name: "two".to_string(),
-                code: "import one.{receive} fn funky() { receive }".to_string(),
+                code: "import one.{receive} pub fn funky() { receive }".to_string(),
             },
         ],
         Ok(vec![
@@ -690,7 +692,7 @@ funky() ->\n    fun one:'receive'/0.\n"
             },
             OutputFile {
                 path: PathBuf::from("_build/default/lib/the_package/src/two.erl"),
-                text: "-module(two).\n-compile(no_auto_import).\n
+                text: "-module(two).\n-compile(no_auto_import).\n\n-export([funky/0]).\n
 funky() ->\n    fun one:'receive'/0.\n"
                     .to_string(),
             },
@@ -710,7 +712,7 @@ funky() ->\n    fun one:'receive'/0.\n"
                 origin: Origin::Src,
                 path: PathBuf::from("/src/two.gleam"),
                 name: "two".to_string(),
-                code: "import one fn funky() { one.receive(1) }".to_string(),
+                code: "import one pub fn funky() { one.receive(1) }".to_string(),
             },
         ],
         Ok(vec![
@@ -724,7 +726,7 @@ funky() ->\n    fun one:'receive'/0.\n"
             },
             OutputFile {
                 path: PathBuf::from("_build/default/lib/the_package/src/two.erl"),
-                text: "-module(two).\n-compile(no_auto_import).\n
+                text: "-module(two).\n-compile(no_auto_import).\n\n-export([funky/0]).\n
 funky() ->\n    one:'receive'(1).\n"
                     .to_string(),
             },
@@ -851,7 +853,7 @@ get_name(Person) ->\n    erlang:element(2, Person).\n"
                 origin: Origin::Src,
                 path: PathBuf::from("/src/two.gleam"),
                 name: "two".to_string(),
-                code: "import one.{C} fn main() { C }".to_string(),
+                code: "import one.{C} pub fn main() { C }".to_string(),
             },
         ],
         Ok(vec![
@@ -866,7 +868,7 @@ get_name(Person) ->\n    erlang:element(2, Person).\n"
             },
             OutputFile {
                 path: PathBuf::from("_build/default/lib/the_package/src/two.erl"),
-                text: "-module(two).\n-compile(no_auto_import).\n
+                text: "-module(two).\n-compile(no_auto_import).\n\n-export([main/0]).\n
 main() ->\n    fun(A, B) -> {c, A, B} end.\n"
                     .to_string(),
             },
@@ -886,7 +888,7 @@ main() ->\n    fun(A, B) -> {c, A, B} end.\n"
                 origin: Origin::Src,
                 path: PathBuf::from("/src/two.gleam"),
                 name: "two".to_string(),
-                code: "import one.{X as e, id as i} fn make() { i(e) }".to_string(),
+                code: "import one.{X as e, id as i} pub fn make() { i(e) }".to_string(),
             },
         ],
         Ok(vec![
@@ -904,7 +906,7 @@ id(X) ->\n    X.\n"
             },
             OutputFile {
                 path: PathBuf::from("_build/default/lib/the_package/src/two.erl"),
-                text: "-module(two).\n-compile(no_auto_import).\n
+                text: "-module(two).\n-compile(no_auto_import).\n\n-export([make/0]).\n
 make() ->\n    one:id(fun(A) -> {x, A} end).\n"
                     .to_string(),
             },
@@ -924,7 +926,7 @@ make() ->\n    one:id(fun(A) -> {x, A} end).\n"
                 origin: Origin::Src,
                 path: PathBuf::from("/src/two.gleam"),
                 name: "two".to_string(),
-                code: "import one fn main() { one.C }".to_string(),
+                code: "import one pub fn main() { one.C }".to_string(),
             },
         ],
         Ok(vec![
@@ -939,7 +941,7 @@ make() ->\n    one:id(fun(A) -> {x, A} end).\n"
             },
             OutputFile {
                 path: PathBuf::from("_build/default/lib/the_package/src/two.erl"),
-                text: "-module(two).\n-compile(no_auto_import).\n
+                text: "-module(two).\n-compile(no_auto_import).\n\n-export([main/0]).\n
 main() ->\n    fun(A, B) -> {c, A, B} end.\n"
                     .to_string(),
             },
@@ -966,12 +968,12 @@ main() ->\n    fun(A, B) -> {c, A, B} end.\n"
         Err(Error::Type {
             path: PathBuf::from("/src/two.gleam"),
             src: "import one fn main() { one.C }".to_string(),
-            error: crate::typ::Error::UnknownModuleValue {
+            errors: vec![crate::typ::Error::UnknownModuleValue {
                 location: crate::ast::SrcSpan { start: 26, end: 28 },
                 name: "C".to_string(),
                 module_name: vec!["one".to_string(),],
                 value_constructors: vec![],
-            }
+            }]
         }),
     );
 
@@ -995,7 +997,7 @@ main() ->\n    fun(A, B) -> {c, A, B} end.\n"
         Err(Error::Type {
             path: PathBuf::from("/src/two.gleam"),
             src: "import one fn test(t: one.T) { t.a }".to_string(),
-            error: crate::typ::Error::UnknownField {
+            errors: vec![crate::typ::Error::UnknownField {
                 location: crate::ast::SrcSpan { start: 32, end: 34 },
                 typ: Arc::new(crate::typ::Type::App {
                     public: true,
@@ -1005,7 +1007,54 @@ main() ->\n    fun(A, B) -> {c, A, B} end.\n"
                 }),
                 label: "a".to_string(),
                 fields: vec![],
-            }
+            }]
+        }),
+    );
+
+    // Errors in independent modules are all reported, and modules that
+    // import a module with errors are not type checked
+    assert_erlang_compile!(
+        vec![
+            Source {
+                origin: Origin::Src,
+                path: PathBuf::from("/src/one.gleam"),
+                name: "one".to_string(),
+                code: "pub fn one() { 1 + 1.0 }".to_string(),
+            },
+            Source {
+                origin: Origin::Src,
+                path: PathBuf::from("/src/two.gleam"),
+                name: "two".to_string(),
+                code: "import one pub fn two() { one.one() + x }".to_string(),
+            },
+            Source {
+                origin: Origin::Src,
+                path: PathBuf::from("/src/three.gleam"),
+                name: "three".to_string(),
+                code: "pub fn three() { 1.0 + 2 }".to_string(),
+            },
+        ],
+        Err(Error::Multiple {
+            errors: vec![
+                Error::Type {
+                    path: PathBuf::from("/src/one.gleam"),
+                    src: "pub fn one() { 1 + 1.0 }".to_string(),
+                    errors: vec![crate::typ::Error::CouldNotUnify {
+                        location: crate::ast::SrcSpan { start: 19, end: 22 },
+                        expected: crate::typ::int(),
+                        given: crate::typ::float(),
+                    }],
+                },
+                Error::Type {
+                    path: PathBuf::from("/src/three.gleam"),
+                    src: "pub fn three() { 1.0 + 2 }".to_string(),
+                    errors: vec![crate::typ::Error::CouldNotUnify {
+                        location: crate::ast::SrcSpan { start: 17, end: 20 },
+                        expected: crate::typ::int(),
+                        given: crate::typ::float(),
+                    }],
+                },
+            ],
         }),
     );
 
@@ -1090,12 +1139,14 @@ fn config_compilation_test() {
     fn make_config() -> PackageConfig {
         PackageConfig {
             dependencies: HashMap::new(),
+            dev_dependencies: HashMap::new(),
             description: "".to_string(),
             version: None,
             name: "the_package".to_string(),
             docs: Default::default(),
             otp_start_module: None,
             tool: BuildTool::Gleam,
+            warnings_as_errors: false,
         }
     }
 
@@ -1185,12 +1236,552 @@ fn config_compilation_test() {
     );
 }
 
+#[test]
+fn package_compiler_warnings_test() {
+    let config = PackageConfig {
+        dependencies: HashMap::new(),
+        dev_dependencies: HashMap::new(),
+        description: "".to_string(),
+        version: None,
+        name: "the_package".to_string(),
+        docs: Default::default(),
+        otp_start_module: None,
+        tool: BuildTool::Gleam,
+        warnings_as_errors: false,
+    };
+    let root = ProjectRoot::new(PathBuf::new());
+    let mut compiler = PackageCompiler::new(&root, config);
+    compiler.sources = vec![Source {
+        origin: Origin::Src,
+        path: PathBuf::from("/src/one.gleam"),
+        name: "one".to_string(),
+        code: "pub fn main() { let x = 1 2 }".to_string(),
+    }];
+    compiler.print_progress = false;
+    let package = compiler
+        .compile(&mut HashMap::new(), &mut HashMap::with_capacity(4))
+        .expect("Compilation failed");
+
+    // Warnings are kept alongside the module they were emitted for
+    assert_eq!(
+        package.modules[0].warnings,
+        vec![crate::warning::Warning::Type {
+            path: PathBuf::from("/src/one.gleam"),
+            src: "pub fn main() { let x = 1 2 }".to_string(),
+            warning: crate::typ::Warning::UnusedVariable {
+                location: crate::ast::SrcSpan { start: 20, end: 21 },
+                name: "x".to_string(),
+            },
+        }]
+    );
+}
+
+#[test]
+fn package_compiler_cache_test() {
+    let root = ProjectRoot::new(
+        std::env::temp_dir().join(format!("gleam_cache_test_{}", std::process::id())),
+    );
+    let compile = |one: &str, three: &str| {
+        let config = PackageConfig {
+            dependencies: HashMap::new(),
+            dev_dependencies: HashMap::new(),
+            description: "".to_string(),
+            version: None,
+            name: "the_package".to_string(),
+            docs: Default::default(),
+            otp_start_module: None,
+            tool: BuildTool::Gleam,
+            warnings_as_errors: false,
+        };
+        let source = |name: &str, code: &str| Source {
+            origin: Origin::Src,
+            path: PathBuf::from(format!("/src/{}.gleam", name)),
+            name: name.to_string(),
+            code: code.to_string(),
+        };
+        let mut compiler = PackageCompiler::new(&root, config);
+        compiler.sources = vec![
+            source("one", one),
+            source("two", "import one\npub fn two() { one.id(2) }"),
+            source("three", three),
+        ];
+        compiler.print_progress = false;
+        compiler.use_cache = true;
+        let package = compiler
+            .compile(&mut HashMap::new(), &mut HashMap::with_capacity(4))
+            .expect("Compilation failed");
+        crate::fs::write_outputs(&package.outputs).expect("Writing outputs");
+        let mut compiled: Vec<_> = package
+            .modules
+            .iter()
+            .filter(|module| module.ast.is_some())
+            .map(|module| module.name.clone())
+            .collect();
+        compiled.sort();
+        let erlang: Vec<_> = get_sorted_outputs(package)
+            .into_iter()
+            .filter(|output| output.path.extension() != Some("cache".as_ref()))
+            .collect();
+        (compiled, erlang)
+    };
+
+    let (compiled, erlang) = compile("pub fn id(x) { x }", "pub fn three() { 3 }");
+    assert_eq!(compiled, vec!["one", "three", "two"]);
+
+    // Unchanged modules are loaded from the cache, with the same Erlang
+    let (compiled, cached_erlang) = compile("pub fn id(x) { x }", "pub fn three() { 3 }");
+    assert!(compiled.is_empty());
+    assert_eq!(cached_erlang, erlang);
+
+    // Modules importing a changed module are only compiled again if its
+    // interface has changed
+    let (compiled, _) = compile("pub fn id(y) {\n  y\n}", "pub fn three() { 3 }");
+    assert_eq!(compiled, vec!["one"]);
+    let (compiled, _) = compile("pub fn id(_) { 1 }", "pub fn three() { 3 }");
+    assert_eq!(compiled, vec!["one", "two"]);
+    let (compiled, _) = compile("pub fn id(_) { 1 }", "pub fn three() { 4 }");
+    assert_eq!(compiled, vec!["three"]);
+
+    crate::fs::delete_dir(&root.root).expect("Deleting build directory");
+}
+
+#[test]
+fn package_compiler_cached_type_ids_test() {
+    let root = ProjectRoot::new(
+        std::env::temp_dir().join(format!("gleam_type_ids_test_{}", std::process::id())),
+    );
+    let compile = |two: &str| {
+        let config = PackageConfig {
+            dependencies: HashMap::new(),
+            dev_dependencies: HashMap::new(),
+            description: "".to_string(),
+            version: None,
+            name: "the_package".to_string(),
+            docs: Default::default(),
+            otp_start_module: None,
+            tool: BuildTool::Gleam,
+            warnings_as_errors: false,
+        };
+        let source = |name: &str, code: &str| Source {
+            origin: Origin::Src,
+            path: PathBuf::from(format!("/src/{}.gleam", name)),
+            name: name.to_string(),
+            code: code.to_string(),
+        };
+        let mut compiler = PackageCompiler::new(&root, config);
+        compiler.sources = vec![source("one", "pub fn id(x) { x }"), source("two", two)];
+        compiler.print_progress = false;
+        compiler.use_cache = true;
+        let mut modules = HashMap::new();
+        let package = compiler
+            .compile(&mut modules, &mut HashMap::with_capacity(4))
+            .expect("Compilation failed");
+        crate::fs::write_outputs(&package.outputs).expect("Writing outputs");
+        modules
+    };
+    let ids = |value: serde_json::Value| {
+        fn collect(value: &serde_json::Value, ids: &mut Vec<u64>) {
+            match value {
+                serde_json::Value::Object(fields) => {
+                    for (key, value) in fields {
+                        match (key.as_str(), value.as_u64()) {
+                            ("id", Some(id)) => ids.push(id),
+                            _ => collect(value, ids),
+                        }
+                    }
+                }
+                serde_json::Value::Array(values) => {
+                    values.iter().for_each(|value| collect(value, ids))
+                }
+                _ => (),
+            }
+        }
+        let mut ids = vec![];
+        collect(&value, &mut ids);
+        ids
+    };
+
+    let _ = compile("pub fn pair(a, b) { tuple(a, b) }");
+
+    // The type variables of a module loaded from the cache do not share ids
+    // with those of the modules type checked alongside it
+    let modules = compile("pub fn swap(a, b) { tuple(b, a) }");
+    let one = ids(serde_json::to_value(&modules["one"].1).expect("Serialising one"));
+    let two = ids(serde_json::to_value(&modules["two"].1).expect("Serialising two"));
+    assert!(!one.is_empty());
+    assert!(!two.is_empty());
+    assert!(one.iter().all(|id| !two.contains(id)));
+
+    crate::fs::delete_dir(&root.root).expect("Deleting build directory");
+}
+
+#[test]
+fn erlang_package_test() {
+    let root = ProjectRoot::new(
+        std::env::temp_dir().join(format!("gleam_erlang_package_test_{}", std::process::id())),
+    );
+    let write = |path: PathBuf, text: &str| {
+        crate::fs::write_output(&OutputFile {
+            path,
+            text: text.to_string(),
+        })
+        .expect("Writing file")
+    };
+    write(
+        root.root.join("manifest.toml"),
+        r#"[requirements]
+app_lib = "~> 1.0"
+
+[[packages]]
+name = "app_lib"
+version = "1.0.0"
+source = "hex"
+dependencies = ["cowboy"]
+
+[[packages]]
+name = "cowboy"
+version = "2.8.0"
+source = "hex"
+dependencies = ["cowlib"]
+
+[[packages]]
+name = "cowlib"
+version = "2.9.1"
+source = "hex"
+"#,
+    );
+    write(
+        root.build_lib_package_path("app_lib").join("gleam.toml"),
+        "name = \"app_lib\"\n[dependencies]\ncowboy = \"~> 2.8\"",
+    );
+    write(
+        root.build_lib_package_path("cowboy")
+            .join("hex_metadata.config"),
+        "{<<\"name\">>,<<\"cowboy\">>}.\n{<<\"version\">>,<<\"2.8.0\">>}.",
+    );
+    write(
+        root.build_lib_package_path("cowboy").join("rebar.config"),
+        "{erl_opts, [debug_info]}.",
+    );
+    write(
+        root.build_lib_package_src_path("cowlib")
+            .join("cowlib.app.src"),
+        "{application, cowlib, []}.",
+    );
+    write(
+        root.build_lib_package_path("notes").join("README.md"),
+        "Not a package",
+    );
+
+    // Packages with a rebar.config or .app.src file instead of a gleam.toml
+    // are compiled as Erlang packages, with their dependencies taken from
+    // the manifest
+    let configs = root.package_configs("app").expect("Reading configs");
+    let cowboy = &configs["cowboy"];
+    assert_eq!(cowboy.tool, BuildTool::Rebar3);
+    assert_eq!(cowboy.version, Some("2.8.0".to_string()));
+    assert_eq!(
+        cowboy.dependencies.keys().collect::<Vec<_>>(),
+        vec!["cowlib"]
+    );
+    assert_eq!(configs["cowlib"].tool, BuildTool::Rebar3);
+    assert_eq!(configs["cowlib"].version, None);
+    assert_eq!(configs["app_lib"].tool, BuildTool::Other);
+
+    // Other directories are not packages
+    assert!(!configs.contains_key("notes"));
+
+    // And are ordered after their dependencies
+    assert_eq!(
+        project_compiler::order_packages(configs.values()),
+        Ok(vec![
+            "cowlib".to_string(),
+            "cowboy".to_string(),
+            "app_lib".to_string()
+        ])
+    );
+
+    crate::fs::delete_dir(&root.root).expect("Deleting build directory");
+}
+
+#[test]
+fn compile_erlang_to_beam_test() {
+    // The Erlang modules are compiled by an escript, so this test can only be
+    // run where Erlang is installed
+    let escript_installed = std::env::var_os("PATH").map_or(false, |paths| {
+        std::env::split_paths(&paths).any(|dir| dir.join("escript").is_file())
+    });
+    if !escript_installed {
+        println!("escript not found, skipping");
+        return;
+    }
+
+    let mut root = ProjectRoot::new(
+        std::env::temp_dir().join(format!("gleam_compile_erlang_test_{}", std::process::id())),
+    );
+    let write = |path: PathBuf, text: &str| {
+        crate::fs::write_output(&OutputFile {
+            path,
+            text: text.to_string(),
+        })
+        .expect("Writing file")
+    };
+    let package = |name: &str, tool: BuildTool, dependencies: &[&str]| {
+        let config = PackageConfig {
+            name: name.to_string(),
+            tool,
+            dependencies: dependencies
+                .iter()
+                .map(|name| (name.to_string(), String::new()))
+                .collect(),
+            ..Default::default()
+        };
+        let package = Package {
+            config,
+            modules: vec![],
+            outputs: vec![],
+        };
+        (name.to_string(), package)
+    };
+    let packages: HashMap<_, _> = vec![
+        package("app", BuildTool::Gleam, &["dep"]),
+        package("dep", BuildTool::Rebar3, &[]),
+    ]
+    .into_iter()
+    .collect();
+    let write_packages = |root: &ProjectRoot| {
+        let dep = root.build_lib_package_path("dep");
+        write(
+            dep.join("rebar.config"),
+            r#"{erl_opts, [
+    warnings_as_errors,
+    {i, "headers"},
+    {d, 'FROM_OPTS', 42},
+    {platform_define, "^[0-9]+-", 'ANY_PLATFORM'},
+    {platform_define, "^no-such-platform$", 'NO_PLATFORM'}
+]}."#,
+        );
+        write(
+            dep.join("src/dep.app.src"),
+            r#"{application, dep, [{vsn, "1.0.0"}, {applications, [kernel]}]}."#,
+        );
+        write(dep.join("headers/dep.hrl"), "-define(FROM_HEADER, 1).");
+        // Compiled before the parse transform it uses, so it is retried
+        // once the parse transform has been compiled
+        write(
+            dep.join("src/a_user.erl"),
+            "-module(a_user).\n-compile({parse_transform, z_transform}).",
+        );
+        write(
+            dep.join("src/z_transform.erl"),
+            "-module(z_transform).\n-export([parse_transform/2]).\n\
+             parse_transform(Forms, _Options) -> Forms.",
+        );
+        write(
+            dep.join("src/dep.erl"),
+            r#"-module(dep).
+-export([value/0]).
+-include("dep.hrl").
+-ifdef('ANY_PLATFORM').
+-ifndef('NO_PLATFORM').
+% An unused variable would fail with warnings_as_errors
+value() -> Unused = 1, ?FROM_OPTS + ?FROM_HEADER.
+-endif.
+-endif."#,
+        );
+        write(dep.join("test/dep_tests.erl"), "Not valid Erlang");
+
+        let app = root.build_lib_package_path("app");
+        write(app.join("src/app.erl"), "-module(app).");
+        write(app.join("test/app_tests.erl"), "-module(app_tests).");
+    };
+    let beams = |root: &ProjectRoot, name: &str| {
+        let mut beams: Vec<_> = crate::fs::read_dir(root.build_lib_package_ebin_path(name))
+            .expect("Reading ebin")
+            .filter_map(Result::ok)
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        beams.sort();
+        beams
+    };
+    let erl = |root: &ProjectRoot, expression: &str| {
+        let output = process::Command::new("erl")
+            .arg("-noshell")
+            .arg("-pa")
+            .arg(root.build_lib_package_ebin_path("dep"))
+            .arg("-eval")
+            .arg(format!("io:format(\"~p\", [{}]), halt().", expression))
+            .output()
+            .expect("Running erl");
+        String::from_utf8(output.stdout).expect("erl output")
+    };
+
+    // Only the src directory of dependencies is compiled, and test modules
+    // of the top level package only for the test profile
+    write_packages(&root);
+    compile_erlang_to_beam(&root, "app", &packages).expect("Compiling Erlang");
+    assert_eq!(
+        beams(&root, "dep"),
+        vec!["a_user.beam", "dep.app", "dep.beam", "z_transform.beam"]
+    );
+    assert_eq!(beams(&root, "app"), vec!["app.beam"]);
+
+    root.profile = Profile::Test;
+    write_packages(&root);
+    compile_erlang_to_beam(&root, "app", &packages).expect("Compiling Erlang");
+    assert_eq!(beams(&root, "app"), vec!["app.beam", "app_tests.beam"]);
+
+    // The erl_opts of the rebar.config are used
+    assert_eq!(erl(&root, "dep:value()"), "43");
+
+    // And the .app file lists the compiled modules
+    assert_eq!(
+        erl(
+            &root,
+            "begin {ok, [{application, dep, Props}]} = \
+             file:consult(code:where_is_file(\"dep.app\")), \
+             {proplists:get_value(vsn, Props), proplists:get_value(modules, Props)} end"
+        ),
+        "{\"1.0.0\",[a_user,dep,z_transform]}"
+    );
+
+    crate::fs::delete_dir(&root.root).expect("Deleting build directory");
+}
+
+#[test]
+fn package_compiler_parallel_test() {
+    let compile = |threads: usize| {
+        let config = PackageConfig {
+            dependencies: HashMap::new(),
+            dev_dependencies: HashMap::new(),
+            description: "".to_string(),
+            version: None,
+            name: "the_package".to_string(),
+            docs: Default::default(),
+            otp_start_module: None,
+            tool: BuildTool::Gleam,
+            warnings_as_errors: false,
+        };
+        let root = ProjectRoot::new(PathBuf::new());
+        let mut compiler = PackageCompiler::new(&root, config);
+        compiler.sources = synthetic_sources(4, 5);
+        compiler.sources.push(Source {
+            origin: Origin::Src,
+            path: PathBuf::from("/src/broken_one.gleam"),
+            name: "broken_one".to_string(),
+            code: "pub fn main() { 1 + 1.0 }".to_string(),
+        });
+        compiler.sources.push(Source {
+            origin: Origin::Src,
+            path: PathBuf::from("/src/broken_two.gleam"),
+            name: "broken_two".to_string(),
+            code: "import layer_0_0\npub fn main() { layer_0_0.value_0(1.0) }".to_string(),
+        });
+        compiler.print_progress = false;
+        compiler.threads = threads;
+        compiler
+            .compile(&mut HashMap::new(), &mut HashMap::with_capacity(4))
+            .map(get_sorted_outputs)
+    };
+
+    // Errors are reported in the same order however many threads are used
+    let sequential = compile(1);
+    assert!(matches!(&sequential, Err(Error::Multiple { errors }) if errors.len() == 2));
+    for _ in 0..5 {
+        assert_eq!(compile(8), sequential);
+    }
+}
+
+// Run with `cargo test --release package_compiler_benchmark -- --ignored --nocapture`
+#[test]
+#[ignore]
+fn package_compiler_benchmark() {
+    let compile = |threads: usize| {
+        let config = PackageConfig {
+            dependencies: HashMap::new(),
+            dev_dependencies: HashMap::new(),
+            description: "".to_string(),
+            version: None,
+            name: "the_package".to_string(),
+            docs: Default::default(),
+            otp_start_module: None,
+            tool: BuildTool::Gleam,
+            warnings_as_errors: false,
+        };
+        let root = ProjectRoot::new(PathBuf::new());
+        let mut compiler = PackageCompiler::new(&root, config);
+        compiler.sources = synthetic_sources(20, 50);
+        compiler.print_progress = false;
+        compiler.threads = threads;
+        let start = std::time::Instant::now();
+        let _ = compiler
+            .compile(&mut HashMap::new(), &mut HashMap::with_capacity(1000))
+            .expect("Compilation failed");
+        start.elapsed()
+    };
+
+    let threads = num_cpus::get();
+    println!("1000 modules sequentially: {:?}", compile(1));
+    println!(
+        "1000 modules with {} threads: {:?}",
+        threads,
+        compile(threads)
+    );
+}
+
+/// Layers of modules, each importing several modules from the layer before.
+///
+fn synthetic_sources(layers: usize, width: usize) -> Vec<Source> {
+    let mut sources = vec![];
+    for layer in 0..layers {
+        for index in 0..width {
+            let name = format!("layer_{}_{}", layer, index);
+            let imports: Vec<_> = match layer {
+                0 => vec![],
+                _ => (0..3)
+                    .map(|offset| format!("layer_{}_{}", layer - 1, (index + offset) % width))
+                    .collect(),
+            };
+            let mut code = String::new();
+            for import in imports.iter() {
+                code.push_str(&format!("import {}\n", import));
+            }
+            code.push_str("\npub type Box(a) {\n  Box(inner: a, count: Int)\n}\n");
+            for function in 0..10 {
+                let body = match imports.first() {
+                    Some(import) => format!("{}.value_{}(x) + 1", import, function),
+                    None => "x + 1".to_string(),
+                };
+                code.push_str(&format!(
+                    "\npub fn value_{}(x) {{\n  let Box(inner: inner, count: _) = Box(inner: [x], count: 1)\n  case inner {{\n    [] -> 0\n    [y, ..] -> {}\n  }}\n}}\n",
+                    function,
+                    body.replace("(x)", "(y)"),
+                ));
+            }
+            sources.push(Source {
+                origin: Origin::Src,
+                path: PathBuf::from(format!("/src/{}.gleam", name)),
+                name,
+                code,
+            });
+        }
+    }
+    sources
+}
+
 fn normalise_error(e: Error) -> Error {
     match e {
         Error::ImportCycle { mut modules } => {
             modules.sort();
             Error::ImportCycle { modules }
         }
+        Error::Multiple { mut errors } => {
+            errors.sort_by_key(|error| match error {
+                Error::Type { path, .. } => path.clone(),
+                _ => PathBuf::new(),
+            });
+            Error::Multiple { errors }
+        }
         e => e,
     }
 }
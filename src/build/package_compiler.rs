@@ -1,16 +1,21 @@
 use crate::{
     ast::{SrcSpan, TypedModule, UntypedModule},
     build::{
-        dep_tree, erlang_code_generator::ErlangCodeGenerator, project_root::ProjectRoot, Module,
-        Origin, Package,
+        dep_tree,
+        erlang_code_generator::ErlangCodeGenerator,
+        module_cache::{self, CachedModule},
+        project_root::ProjectRoot,
+        Module, Origin, Package,
     },
     config::PackageConfig,
     error::{self, Error, GleamExpect},
     grammar, parser, typ,
     warning::Warning,
 };
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
 
 #[derive(Debug)]
 pub struct PackageCompiler<'a> {
@@ -18,6 +23,11 @@ pub struct PackageCompiler<'a> {
     pub config: PackageConfig,
     pub sources: Vec<Source>,
     pub print_progress: bool,
+    /// Whether modules that have not changed since the last build are loaded
+    /// from the cache in `_build`, and compiled modules written to it.
+    pub use_cache: bool,
+    /// The number of threads used to type check modules in parallel.
+    pub threads: usize,
 }
 
 // TODO: ensure this is not a duplicate module
@@ -31,6 +41,8 @@ impl<'a> PackageCompiler<'a> {
             config,
             sources: vec![],
             print_progress: true,
+            use_cache: false,
+            threads: num_cpus::get(),
         }
     }
 
@@ -46,20 +58,65 @@ impl<'a> PackageCompiler<'a> {
         let span = tracing::info_span!("compile", package = self.config.name.as_str());
         let _enter = span.enter();
 
+        let cache_dir = self
+            .root
+            .build_lib_package_artefacts_path(&self.config.name);
+
         tracing::info!("Parsing source code");
-        let parsed_modules = parse_sources(self.sources, already_defined_modules)?;
+        let inputs = parse_sources(
+            self.sources,
+            already_defined_modules,
+            if self.use_cache {
+                Some(cache_dir.as_path())
+            } else {
+                None
+            },
+        )?;
 
         // Determine order in which modules are to be processed
         let sequence =
-            dep_tree::toposort_deps(parsed_modules.values().map(module_deps_for_graph).collect())
+            dep_tree::toposort_deps(inputs.values().map(module_deps_for_graph).collect())
                 .map_err(convert_deps_tree_error)?;
 
         tracing::info!("Type checking modules");
-        let modules = type_check(sequence, parsed_modules, existing_modules)?;
+        let (modules, mut cached) = type_check(sequence, inputs, existing_modules, self.threads)?;
 
         tracing::info!("Generating Erlang source code");
-        let outputs =
-            ErlangCodeGenerator::new(&self.root, &self.config, modules.as_slice()).render();
+        let generator = ErlangCodeGenerator::new(&self.root, &self.config, modules.as_slice());
+        let mut outputs = vec![generator.package_app_file()];
+        for module in modules.iter() {
+            let ast = match &module.ast {
+                Some(ast) => ast,
+                None => {
+                    let cached = cached
+                        .remove(&module.name)
+                        .gleam_expect("Getting cached module for name");
+                    let dir = self
+                        .root
+                        .build_lib_package_source_path(&self.config.name, module.origin);
+                    outputs.extend(cached.erlang_outputs(&dir));
+                    continue;
+                }
+            };
+            let erlang = generator.render_module(module, ast);
+
+            // Modules with warnings are compiled again each time so that the
+            // warnings are not lost
+            if self.use_cache && module.warnings.is_empty() {
+                let dependencies = ast
+                    .dependencies()
+                    .into_iter()
+                    .filter_map(|(dep, _)| {
+                        let (_, interface) = existing_modules.get(&dep)?;
+                        Some((dep, module_cache::interface_hash(interface)))
+                    })
+                    .collect();
+                let cached =
+                    CachedModule::new(&module.code, dependencies, ast.type_info.clone(), &erlang);
+                outputs.push(cached.to_output(cache_path(&cache_dir, &module.name)));
+            }
+            outputs.extend(erlang);
+        }
 
         Ok(Package {
             config: self.config,
@@ -76,7 +133,7 @@ impl<'a> PackageCompiler<'a> {
         tracing::info!("Reading source code");
         let package_path = self
             .root
-            .default_build_lib_package_source_path(&self.config.name, origin);
+            .build_lib_package_source_path(&self.config.name, origin);
         for path in crate::fs::gleam_files(&package_path) {
             let name = module_name(&package_path, &path);
             let code = crate::fs::read(&path)?;
@@ -93,48 +150,346 @@ impl<'a> PackageCompiler<'a> {
 
 fn type_check(
     sequence: Vec<String>,
-    mut parsed_modules: HashMap<String, Parsed>,
+    mut inputs: HashMap<String, Input>,
     module_types: &mut HashMap<String, (Origin, typ::Module)>,
-) -> Result<Vec<Module>, Error> {
-    let mut warnings = vec![];
-    let mut modules = Vec::with_capacity(parsed_modules.len());
-    let mut uid = 0;
+    threads: usize,
+) -> Result<(Vec<Module>, HashMap<String, CachedModule>), Error> {
+    let mut modules = Vec::with_capacity(inputs.len());
+    let mut cached_modules = HashMap::new();
+    let mut interface_hashes = HashMap::new();
+    let mut errors = vec![];
+
+    // Modules that could not be type checked, either because they contain
+    // errors or because they import a module that could not be type checked
+    let mut failed = HashSet::new();
+
+    // The type variables of each interface are renumbered into a range of
+    // ids of their own, above those of the modules of earlier packages, so
+    // that the interfaces of modules loaded from the cache or checked in
+    // parallel never share an id. Modules are checked with ids above all of
+    // these ranges.
+    let first_id = module_types
+        .values()
+        .filter_map(|(_, interface)| module_cache::max_type_id(interface))
+        .max()
+        .map_or(0, |id| id + 1);
+    let mut names = sequence.clone();
+    names.sort();
+    let uid = first_id + names.len() * TYPE_IDS_PER_MODULE;
+    let id_starts: HashMap<_, _> = names
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| (name, first_id + i * TYPE_IDS_PER_MODULE))
+        .collect();
 
-    for name in sequence {
-        let Parsed {
-            name,
-            code,
-            ast,
-            path,
-            origin,
-        } = parsed_modules
-            .remove(&name)
-            .gleam_expect("Getting parsed module for name");
+    // A module is checked as soon as each module of the package that it
+    // imports has been, so the number of imports still to be checked is
+    // kept for each module, along with the modules importing it.
+    let mut waiting = HashMap::with_capacity(sequence.len());
+    let mut importers: HashMap<_, Vec<_>> = HashMap::new();
+    for name in sequence.iter() {
+        let (_, deps) = module_deps_for_graph(&inputs[name]);
+        let deps: HashSet<_> = deps
+            .into_iter()
+            .filter(|dep| inputs.contains_key(dep))
+            .collect();
+        let _ = waiting.insert(name.clone(), deps.len());
+        for dep in deps {
+            importers.entry(dep).or_default().push(name.clone());
+        }
+    }
+    let mut ready: VecDeque<_> = sequence
+        .into_iter()
+        .filter(|name| waiting[name] == 0)
+        .collect();
+
+    let workers = if threads > 1 {
+        Some(Workers::spawn(threads, module_types))
+    } else {
+        None
+    };
+    let mut queued = VecDeque::new();
+    let mut checking = HashMap::new();
+
+    loop {
+        while let Some(name) = ready.pop_front() {
+            let input = inputs
+                .remove(&name)
+                .gleam_expect("Getting input module for name");
+
+            let parsed = match input {
+                Input::Parsed(parsed) => parsed,
+
+                // A cached module can be used if the modules it imports have
+                // the same interfaces as when it was compiled
+                Input::Cached(cached) => {
+                    let unchanged = cached.cache.dependencies.iter().all(|(dep, hash)| {
+                        let current = interface_hashes.entry(dep.clone()).or_insert_with(|| {
+                            module_types
+                                .get(dep)
+                                .map(|(_, interface)| module_cache::interface_hash(interface))
+                        });
+                        current.as_ref() == Some(hash)
+                    });
+                    if unchanged {
+                        tracing::trace!(module = ?cached.name, "Loading from cache");
+                        let interface = module_cache::renumber_type_ids(
+                            &cached.cache.interface,
+                            id_starts[&cached.name],
+                        );
+                        if let Some(workers) = &workers {
+                            workers.publish(&cached.name, cached.origin, &interface);
+                        }
+                        let _ =
+                            module_types.insert(cached.name.clone(), (cached.origin, interface));
+                        modules.push(Module {
+                            origin: cached.origin,
+                            name: cached.name.clone(),
+                            code: cached.code,
+                            ast: None,
+                            path: cached.path,
+                            warnings: vec![],
+                        });
+                        release(&cached.name, &importers, &mut waiting, &mut ready);
+                        let _ = cached_modules.insert(cached.name, cached.cache);
+                        continue;
+                    }
+                    match parse_source(&cached.code, &cached.name, &cached.path) {
+                        Ok(ast) => Parsed {
+                            name: cached.name,
+                            code: cached.code,
+                            ast,
+                            path: cached.path,
+                            origin: cached.origin,
+                        },
+                        Err(error) => {
+                            errors.push((cached.name.clone(), error));
+                            release(&cached.name, &importers, &mut waiting, &mut ready);
+                            let _ = failed.insert(cached.name);
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            // The imports of a module that depends on a failed module cannot
+            // be resolved, so it is skipped rather than reporting further
+            // errors
+            if parsed
+                .ast
+                .dependencies()
+                .iter()
+                .any(|(dep, _)| failed.contains(dep))
+            {
+                tracing::trace!(module = ?parsed.name, "Skipping type checking");
+                release(&parsed.name, &importers, &mut waiting, &mut ready);
+                let _ = failed.insert(parsed.name);
+                continue;
+            }
+
+            let Parsed {
+                name,
+                code,
+                ast,
+                path,
+                origin,
+            } = parsed;
+            match &workers {
+                Some(workers) => workers.check(name.clone(), ast, uid),
+                None => queued.push_back((name.clone(), ast)),
+            }
+            let _ = checking.insert(name, (code, path, origin));
+        }
 
-        tracing::trace!(module = ?name, "Type checking");
-        let ast =
-            typ::infer_module(&mut uid, ast, module_types, &mut warnings).map_err(|error| {
-                Error::Type {
+        if checking.is_empty() {
+            break;
+        }
+        let (name, (warnings, result)) = match &workers {
+            Some(workers) => workers.next_result(),
+            None => {
+                let (name, ast) = queued.pop_front().gleam_expect("Getting queued module");
+                let result = infer(ast, uid, module_types);
+                (name, result)
+            }
+        };
+        let (code, path, origin) = checking
+            .remove(&name)
+            .gleam_expect("Getting checked module for name");
+        release(&name, &importers, &mut waiting, &mut ready);
+
+        let warnings = warnings
+            .into_iter()
+            .map(|warning| Warning::Type {
+                path: path.clone(),
+                src: code.clone(),
+                warning,
+            })
+            .collect();
+
+        let mut ast = match result {
+            Ok(ast) => ast,
+            Err(type_errors) => {
+                let error = Error::Type {
                     path: path.clone(),
                     src: code.clone(),
-                    error,
-                }
-            })?;
+                    errors: type_errors,
+                };
+                errors.push((name.clone(), error));
+                let _ = failed.insert(name);
+                continue;
+            }
+        };
 
+        ast.type_info = module_cache::renumber_type_ids(&ast.type_info, id_starts[&name]);
+        if let Some(workers) = &workers {
+            workers.publish(&name, origin, &ast.type_info);
+        }
         module_types.insert(name.clone(), (origin, ast.type_info.clone()));
 
         modules.push(Module {
             origin,
             name,
             code,
-            ast,
+            ast: Some(ast),
             path,
+            warnings,
         });
     }
 
-    // TODO: do something with warnings
+    // Modules finish in an order that depends on how long each takes to
+    // check, so they and their errors are sorted to be deterministic
+    errors.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let errors = errors.into_iter().map(|(_, error)| error).collect();
+    if let Some(error) = Error::from_errors(errors) {
+        return Err(error);
+    }
+
+    modules.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok((modules, cached_modules))
+}
+
+/// Mark a module as done, making each module importing it ready to be
+/// checked once all of its other imports are done too.
+///
+fn release(
+    name: &str,
+    importers: &HashMap<String, Vec<String>>,
+    waiting: &mut HashMap<String, usize>,
+    ready: &mut VecDeque<String>,
+) {
+    for importer in importers.get(name).into_iter().flatten() {
+        let count = waiting
+            .get_mut(importer)
+            .gleam_expect("Getting import count for module");
+        *count -= 1;
+        if *count == 0 {
+            ready.push_back(importer.clone());
+        }
+    }
+}
+
+// The number of type variable ids set aside for the interface of each module
+const TYPE_IDS_PER_MODULE: usize = 1 << 20;
+
+// Worker threads are given the same size stack as the main thread, as type
+// checking deeply nested expressions recurses deeply
+const THREAD_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+type InferResult = (Vec<typ::Warning>, Result<TypedModule, Vec<typ::Error>>);
+
+type Interfaces = HashMap<String, (Origin, typ::Module)>;
+
+fn infer(ast: UntypedModule, uid: usize, module_types: &Interfaces) -> InferResult {
+    let mut uid = uid;
+    tracing::trace!(module = ?ast.name.join("/"), "Type checking");
+    let mut warnings = vec![];
+    let result = typ::infer_module(&mut uid, ast, module_types, &mut warnings);
+    (warnings, result)
+}
+
+/// Threads type checking modules in parallel. Each keeps its own copy of the
+/// interfaces of the modules checked so far, which is given the interface of
+/// each module as it is checked, so that the threads do not wait on each
+/// other to read them.
+///
+/// A thread that panics while type checking sends the panic back in place of
+/// the result so that it is raised again rather than waited on forever, and
+/// carries on so that the interfaces sent to it are still received.
+///
+struct Workers {
+    jobs: mpsc::Sender<(String, UntypedModule, usize)>,
+    interfaces: Vec<mpsc::Sender<(String, Origin, typ::Module)>>,
+    results: mpsc::Receiver<(String, std::thread::Result<InferResult>)>,
+}
+
+impl Workers {
+    fn spawn(threads: usize, module_types: &Interfaces) -> Self {
+        let (jobs, job_receiver) = mpsc::channel::<(String, UntypedModule, usize)>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let (result_sender, results) = mpsc::channel();
+        let mut interfaces = Vec::with_capacity(threads);
+        for _ in 0..threads {
+            let (interface_sender, interface_receiver) = mpsc::channel();
+            interfaces.push(interface_sender);
+            let job_receiver = job_receiver.clone();
+            let result_sender = result_sender.clone();
+            let mut module_types = module_types.clone();
+            let _ = std::thread::Builder::new()
+                .stack_size(THREAD_STACK_SIZE)
+                .spawn(move || loop {
+                    let job = job_receiver
+                        .lock()
+                        .gleam_expect("Locking modules to type check")
+                        .recv();
+                    let (name, ast, uid) = match job {
+                        Ok(job) => job,
+                        Err(_) => break,
+                    };
+                    // The interfaces of the modules imported by this one were
+                    // sent before it was
+                    for (name, origin, interface) in interface_receiver.try_iter() {
+                        let _ = module_types.insert(name, (origin, interface));
+                    }
+                    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                        infer(ast, uid, &module_types)
+                    }));
+                    if result_sender.send((name, result)).is_err() {
+                        break;
+                    }
+                })
+                .gleam_expect("Spawning type checking thread");
+        }
+        Self {
+            jobs,
+            interfaces,
+            results,
+        }
+    }
+
+    fn check(&self, name: String, ast: UntypedModule, uid: usize) {
+        self.jobs
+            .send((name, ast, uid))
+            .gleam_expect("Sending module to type check");
+    }
 
-    Ok(modules)
+    fn publish(&self, name: &str, origin: Origin, interface: &typ::Module) {
+        for sender in self.interfaces.iter() {
+            sender
+                .send((name.to_string(), origin, interface.clone()))
+                .gleam_expect("Sending module interface");
+        }
+    }
+
+    fn next_result(&self) -> (String, InferResult) {
+        let (name, result) = self
+            .results
+            .recv()
+            .gleam_expect("Receiving type checking result");
+        match result {
+            Ok(result) => (name, result),
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
 }
 
 fn convert_deps_tree_error(e: dep_tree::Error) -> Error {
@@ -143,22 +498,33 @@ fn convert_deps_tree_error(e: dep_tree::Error) -> Error {
     }
 }
 
-fn module_deps_for_graph(module: &Parsed) -> (String, Vec<String>) {
-    let name = module.name.clone();
-    let deps: Vec<_> = module
-        .ast
-        .dependencies()
-        .into_iter()
-        .map(|(dep, _span)| dep)
-        .collect();
-    (name, deps)
+fn module_deps_for_graph(module: &Input) -> (String, Vec<String>) {
+    match module {
+        Input::Parsed(parsed) => {
+            let deps: Vec<_> = parsed
+                .ast
+                .dependencies()
+                .into_iter()
+                .map(|(dep, _span)| dep)
+                .collect();
+            (parsed.name.clone(), deps)
+        }
+        Input::Cached(cached) => {
+            let deps = cached.cache.dependencies.iter();
+            (
+                cached.name.clone(),
+                deps.map(|(dep, _)| dep.clone()).collect(),
+            )
+        }
+    }
 }
 
 fn parse_sources(
     sources: Vec<Source>,
     already_defined_modules: &mut HashMap<String, PathBuf>,
-) -> Result<HashMap<String, Parsed>, Error> {
-    let mut parsed_modules = HashMap::with_capacity(sources.len());
+    cache_dir: Option<&Path>,
+) -> Result<HashMap<String, Input>, Error> {
+    let mut inputs = HashMap::with_capacity(sources.len());
     for source in sources.into_iter() {
         let Source {
             name,
@@ -166,33 +532,52 @@ fn parse_sources(
             path,
             origin,
         } = source;
-        let ast = parse_source(code.as_str(), name.as_str(), &path)?;
-        let module = Parsed {
-            origin,
-            path,
-            name,
-            code,
-            ast,
-        };
 
         // Ensure there are no modules defined that already have this name
-        if let Some(first) =
-            already_defined_modules.insert(module.name.clone(), module.path.clone())
-        {
+        if let Some(first) = already_defined_modules.insert(name.clone(), path.clone()) {
             return Err(Error::DuplicateModule {
-                module: module.name.clone(),
+                module: name,
                 first,
-                second: module.path.clone(),
+                second: path,
             });
         }
 
+        // Modules that have not changed since they were cached do not need
+        // to be parsed
+        let cache = cache_dir
+            .and_then(|dir| CachedModule::read(&cache_path(dir, &name)))
+            .filter(|cache| cache.source_hash == module_cache::source_hash(&code));
+        let input = match cache {
+            Some(cache) => Input::Cached(Cached {
+                path,
+                name: name.clone(),
+                code,
+                origin,
+                cache,
+            }),
+            None => {
+                let ast = parse_source(code.as_str(), name.as_str(), &path)?;
+                Input::Parsed(Parsed {
+                    origin,
+                    path,
+                    name: name.clone(),
+                    code,
+                    ast,
+                })
+            }
+        };
+
         // Register the parsed module
-        parsed_modules.insert(module.name.clone(), module);
+        inputs.insert(name, input);
     }
-    Ok(parsed_modules)
+    Ok(inputs)
+}
+
+fn cache_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.cache", name.replace("/", "@")))
 }
 
-fn parse_source(src: &str, name: &str, path: &PathBuf) -> Result<UntypedModule, Error> {
+pub fn parse_source(src: &str, name: &str, path: &PathBuf) -> Result<UntypedModule, Error> {
     // Strip comments, etc
     let (cleaned, comments) = parser::strip_extra(src);
 
@@ -219,7 +604,7 @@ fn parse_source(src: &str, name: &str, path: &PathBuf) -> Result<UntypedModule,
     Ok(module)
 }
 
-fn module_name(package_path: &PathBuf, full_module_path: &PathBuf) -> String {
+pub fn module_name(package_path: &PathBuf, full_module_path: &PathBuf) -> String {
     // /path/to/project/_build/default/lib/the_package/src/my/module.gleam
 
     // my/module.gleam
@@ -257,3 +642,20 @@ struct Parsed {
     origin: Origin,
     ast: UntypedModule,
 }
+
+#[derive(Debug)]
+struct Cached {
+    path: PathBuf,
+    name: String,
+    code: String,
+    origin: Origin,
+    cache: CachedModule,
+}
+
+/// A module to be compiled, or one that may be loaded from the cache if the
+/// modules it imports are unchanged.
+#[derive(Debug)]
+enum Input {
+    Parsed(Parsed),
+    Cached(Cached),
+}
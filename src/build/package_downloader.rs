@@ -0,0 +1,318 @@
+//! Dependencies are downloaded from the Hex repository as tarballs, which
+//! contain the metadata of the release and a gzipped tarball of its files.
+//! The files are unpacked into the lib directory of `_build`, alongside a
+//! copy of the metadata so that later downloads can tell which version is
+//! already there.
+//!
+//! https://github.com/hexpm/specifications/blob/master/package_tarball.md
+
+use crate::{
+    build::{
+        dependency_solver::{Dependency, PackageIndex, Release},
+        project_root::ProjectRoot,
+        version::Version,
+    },
+    error::{DownloadProblem, Error, FileIOAction, FileKind, GleamExpect},
+    fs::OutputFile,
+};
+use flate2::read::GzDecoder;
+use regex::Regex;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::collections::HashMap;
+use std::io::Read;
+
+const TARBALL_VERSION: &str = "3";
+const METADATA_FILE_NAME: &str = "hex_metadata.config";
+
+/// A package release fetched from Hex and unpacked into `_build`.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Downloaded {
+    pub name: String,
+    pub version: Version,
+    /// The SHA-256 checksum of the whole tarball, in uppercase hex.
+    pub checksum: String,
+}
+
+/// A source of package releases, normally the Hex repository.
+///
+pub trait Repository {
+    /// The tarball of a package release, or None if there is no release of
+    /// the package with that version.
+    fn get_tarball(&mut self, name: &str, version: &Version) -> Result<Option<Vec<u8>>, Error>;
+}
+
+/// The Hex repository and API, accessed with the Hex API client.
+///
+pub struct HexRepository {
+    /// The client used to make requests, which has the base URLs of the API
+    /// and the repository.
+    pub client: hexpm::UnauthenticatedClient,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl HexRepository {
+    pub fn new() -> Self {
+        Self {
+            client: hexpm::UnauthenticatedClient::new(),
+            // Start event loop so we can run async functions to call the Hex API
+            runtime: tokio::runtime::Runtime::new()
+                .gleam_expect("Unable to start Tokio async runtime"),
+        }
+    }
+}
+
+impl Repository for HexRepository {
+    fn get_tarball(&mut self, name: &str, version: &Version) -> Result<Option<Vec<u8>>, Error> {
+        let url = hexpm::Client::repository_base_url(&self.client)
+            .join(&format!("tarballs/{}-{}.tar", name, version))
+            .map_err(|e| Error::Hex(e.to_string()))?
+            .to_string();
+        self.runtime.block_on(get(&self.client, url))
+    }
+}
+
+impl PackageIndex for HexRepository {
+    fn versions(&mut self, name: &str) -> Result<Option<Vec<Version>>, Error> {
+        let package: Option<ApiPackage> = self.get_json(&format!("packages/{}", name))?;
+        Ok(package.map(|package| {
+            package
+                .releases
+                .iter()
+                .filter_map(|release| Version::parse(&release.version))
+                .collect()
+        }))
+    }
+
+    fn release(&mut self, name: &str, version: &Version) -> Result<Release, Error> {
+        let path = format!("packages/{}/releases/{}", name, version);
+        let release: ApiRelease = self.get_json(&path)?.ok_or_else(|| {
+            Error::Hex(format!("the release {} of {} was not found", version, name))
+        })?;
+        let dependencies = release
+            .requirements
+            .into_iter()
+            .map(|(name, requirement)| Dependency {
+                name,
+                requirement: requirement.requirement,
+                optional: requirement.optional,
+            })
+            .collect();
+        let checksum = release.outer_checksum.ok_or_else(|| {
+            Error::Hex(format!(
+                "the release {} of {} has no checksum",
+                version, name
+            ))
+        })?;
+        Ok(Release {
+            dependencies,
+            checksum: checksum.to_uppercase(),
+        })
+    }
+}
+
+impl HexRepository {
+    fn get_json<T: DeserializeOwned>(&mut self, path: &str) -> Result<Option<T>, Error> {
+        let url = hexpm::Client::api_base_url(&self.client)
+            .join(path)
+            .map_err(|e| Error::Hex(e.to_string()))?
+            .to_string();
+        match self.runtime.block_on(get(&self.client, url))? {
+            Some(body) => serde_json::from_slice(&body)
+                .map(Some)
+                .map_err(|e| Error::Hex(e.to_string())),
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiPackage {
+    releases: Vec<ApiRelease>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiRelease {
+    version: String,
+    #[serde(default)]
+    requirements: HashMap<String, ApiRequirement>,
+    /// The SHA-256 checksum of the release tarball, in lowercase hex. Only
+    /// given for a single release, not for those listed with a package.
+    #[serde(default)]
+    outer_checksum: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiRequirement {
+    requirement: String,
+    #[serde(default)]
+    optional: bool,
+}
+
+/// Download each of the given package releases that is not already in the
+/// lib directory of `_build`, returning those that were downloaded.
+///
+/// The checksums are those recorded in the manifest for the releases. A
+/// release already in `_build` is only skipped if its checksum is known,
+/// and a downloaded tarball must match it.
+///
+pub fn download(
+    root: &ProjectRoot,
+    repository: &mut impl Repository,
+    packages: &[(String, Version)],
+    checksums: &HashMap<String, String>,
+) -> Result<Vec<Downloaded>, Error> {
+    let mut downloaded = Vec::with_capacity(packages.len());
+    for (name, version) in packages {
+        let checksum = checksums.get(name);
+        if checksum.is_some() && installed_version(root, name).as_ref() == Some(version) {
+            tracing::debug!(package = name.as_str(), "Package already downloaded");
+            continue;
+        }
+        crate::cli::print_downloading(&format!("{} v{}", name, version));
+        let tarball = repository
+            .get_tarball(name, version)?
+            .ok_or_else(|| problem(name, version, DownloadProblem::NotFound))?;
+        let actual = sha256(&[&tarball]);
+        if let Some(expected) = checksum.filter(|expected| **expected != actual) {
+            let problem = DownloadProblem::ManifestChecksumMismatch {
+                expected: expected.clone(),
+                actual,
+            };
+            return Err(self::problem(name, version, problem));
+        }
+        unpack(root, name, version, &tarball)?;
+        downloaded.push(Downloaded {
+            name: name.clone(),
+            version: version.clone(),
+            checksum: actual,
+        });
+    }
+    Ok(downloaded)
+}
+
+/// The version of a package in `_build`, if it was downloaded from Hex.
+///
+pub fn installed_version(root: &ProjectRoot, name: &str) -> Option<Version> {
+    lazy_static! {
+        static ref VERSION: Regex =
+            Regex::new(r#"\{<<"version">>,\s*<<"([^"]*)">>\}"#).gleam_expect("Version regex");
+    }
+    let path = root.build_lib_package_path(name).join(METADATA_FILE_NAME);
+    let metadata = std::fs::read_to_string(path).ok()?;
+    let captures = VERSION.captures(&metadata)?;
+    Version::parse(&captures[1])
+}
+
+/// The body of a response from Hex, or None if the file does not exist.
+///
+async fn get(client: &impl hexpm::Client, url: String) -> Result<Option<Vec<u8>>, Error> {
+    let response = client
+        .http_client()
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| Error::Hex(e.to_string()))?;
+
+    match response.status().as_u16() {
+        200 => {
+            let body = response
+                .bytes()
+                .await
+                .map_err(|e| Error::Hex(e.to_string()))?;
+            Ok(Some(body.to_vec()))
+        }
+
+        // The Hex repository is served from a bucket which responds with
+        // forbidden rather than not found for files that do not exist
+        403 | 404 => Ok(None),
+
+        status => Err(Error::Hex(format!(
+            "an unexpected response was sent by Hex: {}",
+            status
+        ))),
+    }
+}
+
+/// Check the tarball's checksum and unpack its contents into `_build`,
+/// replacing any other version of the package already there.
+///
+fn unpack(root: &ProjectRoot, name: &str, version: &Version, tarball: &[u8]) -> Result<(), Error> {
+    let invalid = |detail: String| problem(name, version, DownloadProblem::InvalidTarball(detail));
+
+    let mut files = HashMap::with_capacity(4);
+    let mut archive = tar::Archive::new(tarball);
+    for entry in archive.entries().map_err(|e| invalid(e.to_string()))? {
+        let mut entry = entry.map_err(|e| invalid(e.to_string()))?;
+        let path = entry.path().map_err(|e| invalid(e.to_string()))?;
+        let path = path.to_string_lossy().to_string();
+        let mut contents = vec![];
+        let _ = entry
+            .read_to_end(&mut contents)
+            .map_err(|e| invalid(e.to_string()))?;
+        let _ = files.insert(path, contents);
+    }
+    let file = |file_name: &str| {
+        files
+            .get(file_name)
+            .ok_or_else(|| invalid(format!("The {} file is missing", file_name)))
+    };
+
+    let tarball_version = file("VERSION")?;
+    if tarball_version.as_slice() != TARBALL_VERSION.as_bytes() {
+        return Err(invalid(format!(
+            "Version {} of the tarball format is not supported",
+            String::from_utf8_lossy(tarball_version)
+        )));
+    }
+    let metadata = file("metadata.config")?;
+    let contents = file("contents.tar.gz")?;
+    let expected = String::from_utf8_lossy(file("CHECKSUM")?)
+        .trim()
+        .to_uppercase();
+    let actual = sha256(&[tarball_version, metadata, contents]);
+    if expected != actual {
+        return Err(problem(
+            name,
+            version,
+            DownloadProblem::ChecksumMismatch { expected, actual },
+        ));
+    }
+
+    let path = root.build_lib_package_path(name);
+    crate::fs::delete_dir(&path)?;
+    crate::fs::mkdir(&path)?;
+    tar::Archive::new(GzDecoder::new(contents.as_slice()))
+        .unpack(&path)
+        .map_err(|e| Error::FileIO {
+            action: FileIOAction::WriteTo,
+            kind: FileKind::Directory,
+            path: path.clone(),
+            err: Some(e.to_string()),
+        })?;
+    crate::fs::write_output(&OutputFile {
+        path: path.join(METADATA_FILE_NAME),
+        text: String::from_utf8_lossy(metadata).to_string(),
+    })
+}
+
+fn sha256(parts: &[&[u8]]) -> String {
+    let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+    for part in parts {
+        context.update(part);
+    }
+    context
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect()
+}
+
+fn problem(name: &str, version: &Version, problem: DownloadProblem) -> Error {
+    Error::DownloadPackage {
+        package: name.to_string(),
+        version: version.to_string(),
+        problem,
+    }
+}
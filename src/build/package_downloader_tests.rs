@@ -0,0 +1,571 @@
+use super::{
+    dependency_solver::{Dependency, PackageIndex},
+    download_dependencies,
+    manifest::{Manifest, ManifestPackage, Source, Unlock},
+    package_downloader::*,
+    project_root::ProjectRoot,
+    version::Version,
+    Profile,
+};
+use crate::config::PackageConfig;
+use crate::error::{DownloadProblem, Error, ResolutionProblem};
+use flate2::{write::GzEncoder, Compression};
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+/// A stand-in for Hex, serving the API and the tarballs of its releases over
+/// HTTP on a local port. The releases have no dependencies unless another
+/// response is given for the path of a release.
+///
+#[derive(Debug, Default)]
+struct Hex {
+    releases: Vec<(String, Version, Vec<u8>)>,
+    /// The status and body of the responses to other paths.
+    responses: HashMap<String, (u16, String)>,
+    /// The path of each request made.
+    requests: Vec<String>,
+}
+
+impl Hex {
+    fn respond(&mut self, path: &str) -> (u16, Vec<u8>) {
+        self.requests.push(path.to_string());
+        if let Some((status, body)) = self.responses.get(path) {
+            return (*status, body.clone().into_bytes());
+        }
+        let json = |value: serde_json::Value| (200, value.to_string().into_bytes());
+        let release = |name: &str, version: &str| {
+            self.releases
+                .iter()
+                .find(|release| release.0 == name && release.1.to_string() == version)
+        };
+
+        let segments: Vec<_> = path.split('/').skip(1).collect();
+        match segments.as_slice() {
+            ["api", "packages", name] => {
+                let versions: Vec<_> = self
+                    .releases
+                    .iter()
+                    .filter(|release| release.0 == *name)
+                    .map(|release| json!({ "version": release.1.to_string() }))
+                    .collect();
+                if versions.is_empty() {
+                    (404, vec![])
+                } else {
+                    json(json!({ "releases": versions }))
+                }
+            }
+
+            ["api", "packages", name, "releases", version] => match release(name, version) {
+                Some(release) => json(json!({
+                    "version": version,
+                    "requirements": {},
+                    "outer_checksum": checksum(&[&release.2]).to_lowercase(),
+                })),
+                None => (404, vec![]),
+            },
+
+            // Like Hex, the repository responds with forbidden rather than
+            // not found
+            ["repo", "tarballs", file] => self
+                .releases
+                .iter()
+                .find(|release| format!("{}-{}.tar", release.0, release.1) == *file)
+                .map_or((403, vec![]), |release| (200, release.2.clone())),
+
+            _ => (404, vec![]),
+        }
+    }
+}
+
+/// A Hex repository client for a stand-in for Hex, along with the releases
+/// and responses it serves, which can be changed while it is running.
+///
+struct Registry {
+    hex: Arc<Mutex<Hex>>,
+    repository: HexRepository,
+}
+
+impl Registry {
+    fn new(releases: Vec<(&str, &str, Vec<u8>)>) -> Self {
+        let hex = Arc::new(Mutex::new(Hex::default()));
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Binding local port");
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let serving = hex.clone();
+        let _ = std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.expect("Accepting connection");
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                let _ = reader.read_line(&mut request).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+                let path = request.split(' ').nth(1).unwrap_or("/");
+                let (status, body) = serving.lock().unwrap().respond(path);
+                let head = format!(
+                    "HTTP/1.1 {} Status\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                );
+                let _ = stream.write_all(head.as_bytes());
+                let _ = stream.write_all(&body);
+            }
+        });
+
+        let mut repository = HexRepository::new();
+        repository.client.api_base = format!("{}api/", url).parse().unwrap();
+        repository.client.repository_base = format!("{}repo/", url).parse().unwrap();
+        let registry = Self { hex, repository };
+        for (name, version, tarball) in releases {
+            registry.add(name, version, tarball);
+        }
+        registry
+    }
+
+    /// Add a release, replacing the tarball of any release with the same
+    /// name and version.
+    ///
+    fn add(&self, name: &str, version: &str, tarball: Vec<u8>) {
+        let version = Version::parse(version).unwrap();
+        let mut hex = self.hex.lock().unwrap();
+        hex.releases
+            .retain(|release| release.0 != name || release.1 != version);
+        hex.releases.push((name.to_string(), version, tarball));
+    }
+
+    fn respond(&self, path: &str, status: u16, body: &str) {
+        let _ = self
+            .hex
+            .lock()
+            .unwrap()
+            .responses
+            .insert(path.to_string(), (status, body.to_string()));
+    }
+
+    /// The number of tarballs requested.
+    ///
+    fn downloads(&self) -> usize {
+        let hex = self.hex.lock().unwrap();
+        hex.requests
+            .iter()
+            .filter(|path| path.starts_with("/repo/"))
+            .count()
+    }
+}
+
+fn tar(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(vec![]);
+    for (path, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, *contents)
+            .expect("Adding file to tarball");
+    }
+    builder.into_inner().expect("Building tarball")
+}
+
+fn checksum(parts: &[&[u8]]) -> String {
+    let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+    for part in parts {
+        context.update(part);
+    }
+    let digest = context.finish();
+    digest
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect()
+}
+
+/// A package tarball in the format used by Hex, optionally with an
+/// incorrect checksum.
+///
+fn hex_tarball(name: &str, version: &str, files: &[(&str, &str)], valid: bool) -> Vec<u8> {
+    let files: Vec<_> = files
+        .iter()
+        .map(|(path, code)| (*path, code.as_bytes()))
+        .collect();
+    let mut contents = GzEncoder::new(vec![], Compression::default());
+    contents
+        .write_all(&tar(&files))
+        .expect("Compressing contents");
+    let contents = contents.finish().expect("Compressing contents");
+    let metadata = format!(
+        "{{<<\"name\">>,<<\"{}\">>}}.\n{{<<\"version\">>,<<\"{}\">>}}.\n",
+        name, version
+    );
+    let mut checksum = checksum(&[b"3", metadata.as_bytes(), &contents]);
+    if !valid {
+        checksum = checksum.replace('A', "B");
+    }
+    tar(&[
+        ("VERSION", b"3"),
+        ("CHECKSUM", checksum.as_bytes()),
+        ("metadata.config", metadata.as_bytes()),
+        ("contents.tar.gz", &contents),
+    ])
+}
+
+fn temp_root(name: &str) -> ProjectRoot {
+    let path = std::env::temp_dir().join(format!("gleam_{}_{}", name, std::process::id()));
+    crate::fs::delete_dir(&path).expect("Deleting temp dir");
+    ProjectRoot::new(path)
+}
+
+fn packages(packages: &[(&str, &str)]) -> Vec<(String, Version)> {
+    packages
+        .iter()
+        .map(|(name, version)| (name.to_string(), Version::parse(version).unwrap()))
+        .collect()
+}
+
+#[test]
+fn download_test() {
+    let root = temp_root("download_test");
+    let first = hex_tarball(
+        "one",
+        "1.0.0",
+        &[
+            ("gleam.toml", "name = \"one\""),
+            ("src/one.gleam", "pub fn one() { 1 }"),
+        ],
+        true,
+    );
+    let second = hex_tarball(
+        "one",
+        "1.1.0",
+        &[
+            ("gleam.toml", "name = \"one\""),
+            ("src/one_point_one.gleam", "pub fn one() { 1.1 }"),
+        ],
+        true,
+    );
+    let mut registry = Registry::new(vec![
+        ("one", "1.0.0", first.clone()),
+        ("one", "1.1.0", second),
+    ]);
+    let lib = root.build_lib_package_path("one");
+
+    // The tarball is unpacked into _build
+    let no_checksums = HashMap::new();
+    let downloaded = download(
+        &root,
+        &mut registry.repository,
+        &packages(&[("one", "1.0.0")]),
+        &no_checksums,
+    )
+    .unwrap();
+    assert_eq!(
+        downloaded,
+        vec![Downloaded {
+            name: "one".to_string(),
+            version: Version::new(1, 0, 0),
+            checksum: checksum(&[&first]),
+        }]
+    );
+    assert_eq!(
+        std::fs::read_to_string(lib.join("src/one.gleam")).unwrap(),
+        "pub fn one() { 1 }"
+    );
+    assert_eq!(installed_version(&root, "one"), Some(Version::new(1, 0, 0)));
+    assert_eq!(registry.downloads(), 1);
+
+    // Packages already in _build are not downloaded again if their
+    // checksum is known
+    let mut checksums = HashMap::new();
+    let _ = checksums.insert("one".to_string(), checksum(&[&first]));
+    let one = packages(&[("one", "1.0.0")]);
+    assert_eq!(
+        download(&root, &mut registry.repository, &one, &checksums),
+        Ok(vec![])
+    );
+    assert_eq!(registry.downloads(), 1);
+    assert_eq!(
+        download(&root, &mut registry.repository, &one, &no_checksums).map(|d| d.len()),
+        Ok(1)
+    );
+    assert_eq!(registry.downloads(), 2);
+
+    // Other versions replace the version in _build
+    let one = packages(&[("one", "1.1.0")]);
+    let downloaded = download(&root, &mut registry.repository, &one, &no_checksums).unwrap();
+    assert_eq!(downloaded.len(), 1);
+    assert!(lib.join("src/one_point_one.gleam").exists());
+    assert!(!lib.join("src/one.gleam").exists());
+    assert_eq!(installed_version(&root, "one"), Some(Version::new(1, 1, 0)));
+
+    crate::fs::delete_dir(&root.root).unwrap();
+}
+
+#[test]
+fn download_error_test() {
+    let root = temp_root("download_error_test");
+    let tampered = hex_tarball("two", "2.0.0", &[("gleam.toml", "")], false);
+    let mut registry = Registry::new(vec![
+        ("two", "2.0.0", tampered),
+        ("three", "3.0.0", b"nonsense".to_vec()),
+    ]);
+
+    let mut problem = |name: &str, version: &str| {
+        let packages = packages(&[(name, version)]);
+        match download(&root, &mut registry.repository, &packages, &HashMap::new()) {
+            Err(Error::DownloadPackage { problem, .. }) => problem,
+            result => panic!("Unexpected download result {:?}", result),
+        }
+    };
+
+    assert_eq!(problem("one", "1.0.0"), DownloadProblem::NotFound);
+
+    assert!(matches!(
+        problem("two", "2.0.0"),
+        DownloadProblem::ChecksumMismatch { .. }
+    ));
+    assert!(!root.build_lib_package_path("two").exists());
+
+    assert!(matches!(
+        problem("three", "3.0.0"),
+        DownloadProblem::InvalidTarball(_)
+    ));
+
+    crate::fs::delete_dir(&root.root).unwrap();
+}
+
+#[test]
+fn download_dependencies_test() {
+    let root = temp_root("download_dependencies_test");
+    let tarball = |version| hex_tarball("one", version, &[("gleam.toml", "")], true);
+    let mut registry = Registry::new(vec![
+        ("one", "1.0.0", tarball("1.0.0")),
+        ("one", "1.1.0", tarball("1.1.0")),
+    ]);
+    let config = |requirement: &str| {
+        let mut config = PackageConfig {
+            name: "app".to_string(),
+            ..Default::default()
+        };
+        let _ = config
+            .dependencies
+            .insert("one".to_string(), requirement.to_string());
+        config
+    };
+    let mut download = |registry: &mut Registry, requirement: &str, unlock: Unlock| {
+        download_dependencies(
+            &root,
+            &config(requirement),
+            &mut registry.repository,
+            unlock,
+        )
+    };
+    let locked = |manifest: Result<Manifest, Error>| {
+        let manifest = manifest.expect("Downloading dependencies");
+        assert_eq!(Manifest::read(&root), Ok(Some(manifest.clone())));
+        manifest
+            .packages
+            .iter()
+            .map(|package| format!("{} {}", package.name, package.version))
+            .collect::<Vec<_>>()
+    };
+
+    // The newest version is chosen and recorded in the manifest
+    let manifest = download(&mut registry, "~> 1.0", Unlock::Nothing).unwrap();
+    assert_eq!(
+        manifest.packages,
+        vec![ManifestPackage {
+            name: "one".to_string(),
+            version: Version::new(1, 1, 0),
+            source: Source::Hex,
+            checksum: Some(checksum(&[&tarball("1.1.0")])),
+            dependencies: vec![],
+        }]
+    );
+    assert_eq!(locked(Ok(manifest)), vec!["one 1.1.0"]);
+    assert_eq!(registry.downloads(), 1);
+
+    // The locked version is used even once a newer one is released
+    registry.add("one", "1.2.0", tarball("1.2.0"));
+    assert_eq!(
+        locked(download(&mut registry, "~> 1.0", Unlock::Nothing)),
+        vec!["one 1.1.0"]
+    );
+    assert_eq!(registry.downloads(), 1);
+
+    // Until the requirements in gleam.toml no longer match the manifest
+    assert_eq!(
+        locked(download(&mut registry, "< 1.1.0", Unlock::Nothing)),
+        vec!["one 1.0.0"]
+    );
+
+    // Or the package is updated
+    let unlock = Unlock::Packages(vec!["one".to_string()]);
+    assert_eq!(
+        locked(download(&mut registry, "~> 1.0", unlock)),
+        vec!["one 1.2.0"]
+    );
+
+    // Which must be one of the dependencies
+    let unlock = Unlock::Packages(vec!["nope".to_string()]);
+    assert_eq!(
+        download(&mut registry, "~> 1.0", unlock),
+        Err(Error::DependencyResolution {
+            package: "nope".to_string(),
+            problem: ResolutionProblem::NotADependency,
+        })
+    );
+
+    // A tarball that does not match the checksum in the manifest is not used
+    registry.add(
+        "one",
+        "1.2.0",
+        hex_tarball("one", "1.2.0", &[("src/one.gleam", "")], true),
+    );
+    crate::fs::delete_dir(&root.build_lib_package_path("one")).unwrap();
+    assert!(matches!(
+        download(&mut registry, "~> 1.0", Unlock::Nothing),
+        Err(Error::DownloadPackage {
+            problem: DownloadProblem::ManifestChecksumMismatch { .. },
+            ..
+        })
+    ));
+
+    crate::fs::delete_dir(&root.root).unwrap();
+}
+
+#[test]
+fn download_dependencies_profile_test() {
+    let mut root = temp_root("download_dependencies_profile_test");
+    let tarball = |name| hex_tarball(name, "1.0.0", &[("gleam.toml", "")], true);
+    let mut registry = Registry::new(vec![
+        ("one", "1.0.0", tarball("one")),
+        ("two", "1.0.0", tarball("two")),
+    ]);
+    let mut config = PackageConfig {
+        name: "app".to_string(),
+        ..Default::default()
+    };
+    let _ = config
+        .dependencies
+        .insert("one".to_string(), "~> 1.0".to_string());
+    let _ = config
+        .dev_dependencies
+        .insert("two".to_string(), "~> 1.0".to_string());
+    let checksums = |manifest: &Manifest| {
+        manifest
+            .packages
+            .iter()
+            .map(|package| (package.name.clone(), package.checksum.is_some()))
+            .collect::<Vec<_>>()
+    };
+
+    // Versions and checksums are chosen for the dev-dependencies too, but
+    // they are not downloaded for the default profile
+    let manifest =
+        download_dependencies(&root, &config, &mut registry.repository, Unlock::Nothing).unwrap();
+    assert_eq!(
+        checksums(&manifest),
+        vec![("one".to_string(), true), ("two".to_string(), true)]
+    );
+    assert!(root.build_lib_package_path("one").exists());
+    assert!(!root.build_lib_package_path("two").exists());
+
+    // The test profile has its own directory in _build
+    root.profile = Profile::Test;
+    let manifest =
+        download_dependencies(&root, &config, &mut registry.repository, Unlock::Nothing).unwrap();
+    assert_eq!(
+        checksums(&manifest),
+        vec![("one".to_string(), true), ("two".to_string(), true)]
+    );
+    assert_eq!(Manifest::read(&root), Ok(Some(manifest)));
+    assert!(root.build_lib_path().ends_with("_build/test/lib"));
+    assert!(root.build_lib_package_path("one").exists());
+    assert!(root.build_lib_package_path("two").exists());
+    assert_eq!(registry.downloads(), 3);
+
+    crate::fs::delete_dir(&root.root).unwrap();
+}
+
+#[test]
+fn hex_repository_test() {
+    let mut registry = Registry::new(vec![
+        ("one", "1.0.0", b"first".to_vec()),
+        ("one", "1.1.0-rc1", b"second".to_vec()),
+    ]);
+    registry.respond(
+        "/api/packages/two/releases/2.0.0",
+        200,
+        r#"{"version": "2.0.0", "outer_checksum": "abc123", "requirements": {
+            "one": {"requirement": "~> 1.0"},
+            "three": {"requirement": ">= 3.0.0", "optional": true}
+        }}"#,
+    );
+    registry.respond("/api/packages/broken", 200, "{\"releases\": 1}");
+    registry.respond("/api/packages/down", 500, "");
+    let repository = &mut registry.repository;
+
+    // Versions, dependencies, and checksums are read from the API
+    assert_eq!(
+        repository.versions("one"),
+        Ok(Some(vec![
+            Version::new(1, 0, 0),
+            Version::parse("1.1.0-rc1").unwrap()
+        ]))
+    );
+    let mut release = repository.release("two", &Version::new(2, 0, 0)).unwrap();
+    assert_eq!(release.checksum, "ABC123");
+    release.dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(
+        release.dependencies,
+        vec![
+            Dependency {
+                name: "one".to_string(),
+                requirement: "~> 1.0".to_string(),
+                optional: false,
+            },
+            Dependency {
+                name: "three".to_string(),
+                requirement: ">= 3.0.0".to_string(),
+                optional: true,
+            },
+        ]
+    );
+
+    // Tarballs are read from the repository
+    let version = Version::parse("1.1.0-rc1").unwrap();
+    assert_eq!(
+        repository.get_tarball("one", &version),
+        Ok(Some(b"second".to_vec()))
+    );
+
+    // Packages and releases that do not exist are not found
+    assert_eq!(repository.versions("nope"), Ok(None));
+    assert_eq!(repository.get_tarball("nope", &version), Ok(None));
+    assert!(matches!(
+        repository.release("nope", &version),
+        Err(Error::Hex(_))
+    ));
+
+    // Other responses and invalid JSON are errors
+    assert!(matches!(repository.versions("down"), Err(Error::Hex(_))));
+    assert!(matches!(repository.versions("broken"), Err(Error::Hex(_))));
+
+    assert_eq!(
+        registry.hex.lock().unwrap().requests,
+        vec![
+            "/api/packages/one",
+            "/api/packages/two/releases/2.0.0",
+            "/repo/tarballs/one-1.1.0-rc1.tar",
+            "/api/packages/nope",
+            "/repo/tarballs/nope-1.1.0-rc1.tar",
+            "/api/packages/nope/releases/1.1.0-rc1",
+            "/api/packages/down",
+            "/api/packages/broken",
+        ]
+    );
+}
@@ -1,8 +1,9 @@
 use crate::{
     build::{
         dep_tree, package_compiler::PackageCompiler, project_root::ProjectRoot, Origin, Package,
+        Profile,
     },
-    config::PackageConfig,
+    config::{BuildTool, PackageConfig},
     error::{Error, GleamExpect},
     typ,
 };
@@ -41,7 +42,7 @@ impl<'a> ProjectCompiler<'a> {
 
     pub fn compile(mut self) -> Result<HashMap<String, Package>, Error> {
         // Determine package processing order
-        let sequence = order_packges(&self.configs)?;
+        let sequence = order_packages(self.configs.values())?;
 
         // Read and type check deps packages
         for name in sequence.into_iter() {
@@ -52,10 +53,15 @@ impl<'a> ProjectCompiler<'a> {
             self.compile_package(name, config, SourceLocations::Src)?;
         }
 
-        // Read and type check top level package
+        // Read and type check top level package, including its tests if
+        // building the test profile
         let root_config = std::mem::replace(&mut self.root_config, Default::default());
         let name = root_config.name.clone();
-        self.compile_package(name, root_config, SourceLocations::SrcAndTest)?;
+        let locations = match self.root.profile {
+            Profile::Default => SourceLocations::Src,
+            Profile::Test => SourceLocations::SrcAndTest,
+        };
+        self.compile_package(name, root_config, locations)?;
 
         Ok(self.packages)
     }
@@ -66,7 +72,20 @@ impl<'a> ProjectCompiler<'a> {
         config: PackageConfig,
         locations: SourceLocations,
     ) -> Result<(), Error> {
+        // Erlang packages have no Gleam modules to compile, their Erlang
+        // modules are compiled along with those generated for the others
+        if config.tool == BuildTool::Rebar3 {
+            let package = Package {
+                config,
+                modules: vec![],
+                outputs: vec![],
+            };
+            self.packages.insert(name, package);
+            return Ok(());
+        }
+
         let mut compiler = PackageCompiler::new(self.root, config);
+        compiler.use_cache = true;
 
         // Read source files
         compiler.read_package_source_files(Origin::Src)?;
@@ -87,8 +106,13 @@ enum SourceLocations {
     SrcAndTest,
 }
 
-fn order_packges(configs: &HashMap<String, PackageConfig>) -> Result<Vec<String>, Error> {
-    dep_tree::toposort_deps(configs.values().map(package_deps_for_graph).collect())
+/// The names of the packages in order so that each comes after its
+/// dependencies.
+///
+pub fn order_packages<'b>(
+    configs: impl Iterator<Item = &'b PackageConfig>,
+) -> Result<Vec<String>, Error> {
+    dep_tree::toposort_deps(configs.map(package_deps_for_graph).collect())
         .map_err(convert_deps_tree_error)
 }
 
@@ -1,27 +1,39 @@
 use crate::{
-    build::Origin,
-    config::{self, PackageConfig},
+    build::{manifest::Manifest, package_downloader, Origin, Profile},
+    config::{self, BuildTool, PackageConfig},
     error::Error,
 };
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 // Directory names
 const DIR_NAME_BUILD: &str = "_build";
 const DIR_NAME_PROFILE_DEFAULT: &str = "default";
+const DIR_NAME_PROFILE_TEST: &str = "test";
 const DIR_NAME_LIB: &str = "lib";
 const DIR_NAME_PACKAGE_SRC: &str = "src";
 const DIR_NAME_PACKAGE_TEST: &str = "test";
 const DIR_NAME_PACKAGE_EBIN: &str = "ebin";
+const DIR_NAME_PACKAGE_ARTEFACTS: &str = "_gleam_artefacts";
+
+// File names
+const FILE_NAME_CONFIG: &str = "gleam.toml";
+const FILE_NAME_REBAR_CONFIG: &str = "rebar.config";
+const FILE_NAME_MANIFEST: &str = "manifest.toml";
 
 #[derive(Debug)]
 pub struct ProjectRoot {
     pub root: PathBuf,
+    /// The profile being built, which has its own directory in `_build`.
+    pub profile: Profile,
 }
 
 impl ProjectRoot {
     pub fn new(root: PathBuf) -> Self {
-        Self { root }
+        Self {
+            root,
+            profile: Profile::Default,
+        }
     }
 
     pub fn root_config(&self) -> Result<PackageConfig, Error> {
@@ -29,16 +41,26 @@ impl ProjectRoot {
     }
 
     /// Load the gleam.toml config files for all packages except the
-    /// top level package.
+    /// top level package. Packages with a rebar.config or a .app.src file
+    /// instead are Erlang packages, the dependencies of which are taken from
+    /// the manifest. Any other directories are ignored.
     ///
     pub fn package_configs(
         &self,
         root_name: &str,
     ) -> Result<HashMap<String, PackageConfig>, Error> {
         let mut configs = HashMap::with_capacity(25);
-        for dir_entry in crate::fs::read_dir(self.default_build_lib_path())?.filter_map(Result::ok)
-        {
-            let config = config::read_project_config(dir_entry.path())?;
+        let manifest = Manifest::read(self)?.unwrap_or_default();
+        for dir_entry in crate::fs::read_dir(self.build_lib_path())?.filter_map(Result::ok) {
+            let path = dir_entry.path();
+            let config = if path.join(FILE_NAME_CONFIG).exists() {
+                config::read_project_config(&path)?
+            } else if is_erlang_package(&path) {
+                let name = dir_entry.file_name().to_string_lossy().to_string();
+                self.erlang_package_config(name, &manifest)
+            } else {
+                continue;
+            };
             if config.name != root_name {
                 configs.insert(config.name.clone(), config);
             }
@@ -46,43 +68,90 @@ impl ProjectRoot {
         Ok(configs)
     }
 
+    fn erlang_package_config(&self, name: String, manifest: &Manifest) -> PackageConfig {
+        let dependencies = manifest
+            .packages
+            .iter()
+            .find(|package| package.name == name)
+            .into_iter()
+            .flat_map(|package| package.dependencies.iter())
+            .map(|dependency| (dependency.clone(), String::new()))
+            .collect();
+        PackageConfig {
+            version: package_downloader::installed_version(self, &name)
+                .map(|version| version.to_string()),
+            tool: BuildTool::Rebar3,
+            dependencies,
+            name,
+            ..Default::default()
+        }
+    }
+
     pub fn src_path(&self) -> PathBuf {
         self.root.join(DIR_NAME_PACKAGE_SRC)
     }
 
+    pub fn test_path(&self) -> PathBuf {
+        self.root.join(DIR_NAME_PACKAGE_TEST)
+    }
+
+    pub fn manifest_path(&self) -> PathBuf {
+        self.root.join(FILE_NAME_MANIFEST)
+    }
+
     pub fn build_path(&self) -> PathBuf {
         self.root.join(DIR_NAME_BUILD)
     }
 
-    pub fn default_build_lib_path(&self) -> PathBuf {
-        self.build_path()
-            .join(DIR_NAME_PROFILE_DEFAULT)
-            .join(DIR_NAME_LIB)
+    pub fn profile_path(&self) -> PathBuf {
+        self.build_path().join(match self.profile {
+            Profile::Default => DIR_NAME_PROFILE_DEFAULT,
+            Profile::Test => DIR_NAME_PROFILE_TEST,
+        })
+    }
+
+    pub fn build_lib_path(&self) -> PathBuf {
+        self.profile_path().join(DIR_NAME_LIB)
     }
 
-    pub fn default_build_lib_package_path(&self, name: &str) -> PathBuf {
-        self.default_build_lib_path().join(name)
+    pub fn build_lib_package_path(&self, name: &str) -> PathBuf {
+        self.build_lib_path().join(name)
     }
 
-    pub fn default_build_lib_package_source_path(&self, name: &str, origin: Origin) -> PathBuf {
+    pub fn build_lib_package_source_path(&self, name: &str, origin: Origin) -> PathBuf {
         match origin {
-            Origin::Src => self.default_build_lib_package_src_path(name),
-            Origin::Test => self.default_build_lib_package_test_path(name),
+            Origin::Src => self.build_lib_package_src_path(name),
+            Origin::Test => self.build_lib_package_test_path(name),
         }
     }
 
-    pub fn default_build_lib_package_src_path(&self, name: &str) -> PathBuf {
-        self.default_build_lib_package_path(name)
-            .join(DIR_NAME_PACKAGE_SRC)
+    pub fn build_lib_package_src_path(&self, name: &str) -> PathBuf {
+        self.build_lib_package_path(name).join(DIR_NAME_PACKAGE_SRC)
     }
 
-    pub fn default_build_lib_package_test_path(&self, name: &str) -> PathBuf {
-        self.default_build_lib_package_path(name)
+    pub fn build_lib_package_test_path(&self, name: &str) -> PathBuf {
+        self.build_lib_package_path(name)
             .join(DIR_NAME_PACKAGE_TEST)
     }
 
-    pub fn default_build_lib_package_ebin_path(&self, name: &str) -> PathBuf {
-        self.default_build_lib_package_path(name)
+    pub fn build_lib_package_ebin_path(&self, name: &str) -> PathBuf {
+        self.build_lib_package_path(name)
             .join(DIR_NAME_PACKAGE_EBIN)
     }
+
+    pub fn build_lib_package_artefacts_path(&self, name: &str) -> PathBuf {
+        self.build_lib_package_path(name)
+            .join(DIR_NAME_PACKAGE_ARTEFACTS)
+    }
+}
+
+fn is_erlang_package(path: &Path) -> bool {
+    path.join(FILE_NAME_REBAR_CONFIG).is_file()
+        || std::fs::read_dir(path.join(DIR_NAME_PACKAGE_SRC))
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .any(|entry| entry.file_name().to_string_lossy().ends_with(".app.src"))
+            })
+            .unwrap_or(false)
 }
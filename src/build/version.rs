@@ -0,0 +1,320 @@
+//! Versions and version requirements in the format used by Hex, which
+//! follows Semantic Versioning and the requirement syntax of Elixir.
+//!
+//! https://hexdocs.pm/elixir/Version.html
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// A version of a package. As in Semantic Versioning, versions that differ
+/// only in their build metadata are equal.
+///
+#[derive(Debug, Clone)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Vec<String>,
+    /// Build metadata, which is ignored when comparing versions.
+    pub build: Option<String>,
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            pre: vec![],
+            build: None,
+        }
+    }
+
+    /// Parse a version such as `1.2.3`, `1.2.3-rc.1`, or `1.2.3+build.5`.
+    ///
+    pub fn parse(text: &str) -> Option<Self> {
+        match parse_version(text)? {
+            (version, true) => Some(version),
+            (_, false) => None,
+        }
+    }
+
+    pub fn is_pre_release(&self) -> bool {
+        !self.pre.is_empty()
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => compare_pre_release(&self.pre, &other.pre),
+            })
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl Hash for Version {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.major, self.minor, self.patch, &self.pre).hash(state)
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        if let Some(build) = &self.build {
+            write!(f, "+{}", build)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Version {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Version::parse(&text)
+            .ok_or_else(|| de::Error::custom(format!("invalid version `{}`", text)))
+    }
+}
+
+// Numeric identifiers are compared numerically and come before
+// alphanumeric identifiers, and a shorter list of identifiers comes first
+// if all the preceding identifiers are equal.
+fn compare_pre_release(one: &[String], other: &[String]) -> Ordering {
+    for (a, b) in one.iter().zip(other.iter()) {
+        let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    one.len().cmp(&other.len())
+}
+
+/// Parse a version, allowing the patch number to be omitted as it may be
+/// in a `~>` requirement. The boolean is whether the patch was given.
+///
+fn parse_version(text: &str) -> Option<(Version, bool)> {
+    let (text, build) = match text.find('+') {
+        Some(i) if is_identifiers(&text[i + 1..]) => (&text[..i], Some(text[i + 1..].to_string())),
+        Some(_) => return None,
+        None => (text, None),
+    };
+    let (text, pre) = match text.find('-') {
+        Some(i) if is_identifiers(&text[i + 1..]) => (
+            &text[..i],
+            text[i + 1..].split('.').map(|s| s.to_string()).collect(),
+        ),
+        Some(_) => return None,
+        None => (text, vec![]),
+    };
+    let numbers = text
+        .split('.')
+        .map(parse_number)
+        .collect::<Option<Vec<_>>>()?;
+    let (major, minor, patch, has_patch) = match numbers.as_slice() {
+        [major, minor] if pre.is_empty() && build.is_none() => (*major, *minor, 0, false),
+        [major, minor, patch] => (*major, *minor, *patch, true),
+        _ => return None,
+    };
+    let version = Version {
+        major,
+        minor,
+        patch,
+        pre,
+        build,
+    };
+    Some((version, has_patch))
+}
+
+fn parse_number(text: &str) -> Option<u64> {
+    let is_digits = !text.is_empty() && text.chars().all(|c| c.is_ascii_digit());
+    let has_leading_zero = text.len() > 1 && text.starts_with('0');
+    if is_digits && !has_leading_zero {
+        text.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn is_identifiers(text: &str) -> bool {
+    text.split('.').all(|identifier| {
+        !identifier.is_empty()
+            && identifier
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Eq,
+    NotEq,
+    Gt,
+    GtEq,
+    Lt,
+    LtEq,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparison {
+    operator: Operator,
+    version: Version,
+}
+
+impl Comparison {
+    fn matches(&self, version: &Version) -> bool {
+        let ordering = version.cmp(&self.version);
+        match self.operator {
+            Operator::Eq => ordering == Ordering::Equal,
+            Operator::NotEq => ordering != Ordering::Equal,
+            Operator::Gt => ordering == Ordering::Greater,
+            Operator::GtEq => ordering != Ordering::Less,
+            Operator::Lt => ordering == Ordering::Less,
+            Operator::LtEq => ordering != Ordering::Greater,
+        }
+    }
+}
+
+/// A version requirement such as `~> 1.2`, `>= 1.0.0 and < 2.0.0`, or
+/// `1.4.0`. Clauses joined with `and` bind more tightly than those joined
+/// with `or`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    text: String,
+    /// Any one of these lists of comparisons must all match.
+    alternatives: Vec<Vec<Comparison>>,
+}
+
+impl Requirement {
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut alternatives = vec![];
+        for alternative in split_words(text, "or") {
+            let mut comparisons = vec![];
+            for clause in split_words(&alternative, "and") {
+                comparisons.extend(parse_clause(&clause)?);
+            }
+            alternatives.push(comparisons);
+        }
+        Some(Self {
+            text: text.trim().to_string(),
+            alternatives,
+        })
+    }
+
+    /// Whether a version meets the requirement. Pre-release versions only
+    /// match if the requirement refers to a pre-release version, so that
+    /// they are not chosen unless asked for.
+    ///
+    pub fn matches(&self, version: &Version) -> bool {
+        if version.is_pre_release() && !self.allows_pre_release() {
+            return false;
+        }
+        self.alternatives.iter().any(|comparisons| {
+            comparisons
+                .iter()
+                .all(|comparison| comparison.matches(version))
+        })
+    }
+
+    fn allows_pre_release(&self) -> bool {
+        self.alternatives
+            .iter()
+            .flatten()
+            .any(|comparison| comparison.version.is_pre_release())
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// Split on a word surrounded by whitespace.
+///
+fn split_words(text: &str, word: &str) -> Vec<String> {
+    let mut parts = vec![vec![]];
+    for token in text.split_whitespace() {
+        if token == word {
+            parts.push(vec![]);
+        } else if let Some(part) = parts.last_mut() {
+            part.push(token);
+        }
+    }
+    parts.into_iter().map(|part| part.join(" ")).collect()
+}
+
+fn parse_clause(clause: &str) -> Option<Vec<Comparison>> {
+    let operators = [
+        ("==", Operator::Eq),
+        ("!=", Operator::NotEq),
+        (">=", Operator::GtEq),
+        ("<=", Operator::LtEq),
+        (">", Operator::Gt),
+        ("<", Operator::Lt),
+    ];
+
+    // `~> 1.2` allows any later 1.x version, and `~> 1.2.3` allows any
+    // later 1.2.x version
+    if clause.starts_with("~>") {
+        let (version, has_patch) = parse_version(clause[2..].trim())?;
+        let upper = if has_patch {
+            Version::new(version.major, version.minor + 1, 0)
+        } else {
+            Version::new(version.major + 1, 0, 0)
+        };
+        return Some(vec![
+            Comparison {
+                operator: Operator::GtEq,
+                version,
+            },
+            Comparison {
+                operator: Operator::Lt,
+                version: upper,
+            },
+        ]);
+    }
+
+    let (operator, version) = operators
+        .iter()
+        .find(|(prefix, _)| clause.starts_with(prefix))
+        .map(|(prefix, operator)| (*operator, &clause[prefix.len()..]))
+        .unwrap_or((Operator::Eq, clause));
+    let version = Version::parse(version.trim())?;
+    Some(vec![Comparison { operator, version }])
+}
@@ -29,6 +29,10 @@ pub fn print_compiling(text: &str) {
     print_green_prefix("Compiling", text)
 }
 
+pub fn print_downloading(text: &str) {
+    print_green_prefix("Downloading", text)
+}
+
 pub fn print_running(text: &str) {
     print_green_prefix("  Running", text)
 }
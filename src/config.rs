@@ -16,8 +16,12 @@ pub struct PackageConfig {
     pub docs: Docs,
     #[serde(default)]
     pub dependencies: HashMap<String, String>,
+    #[serde(default, rename = "dev-dependencies")]
+    pub dev_dependencies: HashMap<String, String>,
     #[serde(default)]
     pub otp_start_module: Option<String>,
+    #[serde(default)]
+    pub warnings_as_errors: bool,
 }
 
 #[derive(Deserialize, Debug, PartialEq)]
@@ -25,6 +29,9 @@ pub struct PackageConfig {
 pub enum BuildTool {
     Gleam,
     Other,
+    /// A package without a `gleam.toml`, typically built with rebar3, of
+    /// which only the Erlang modules are compiled.
+    Rebar3,
 }
 
 impl Default for BuildTool {
@@ -0,0 +1,57 @@
+use crate::{
+    build::{
+        self, manifest::Unlock, package_downloader::HexRepository, project_root::ProjectRoot,
+        Profile,
+    },
+    error::Error,
+};
+use std::path::PathBuf;
+
+/// Download the dependencies of the project from Hex into the lib directory
+/// of each profile in `_build`, skipping any already there. The
+/// dev-dependencies are only downloaded for the test profile. The versions locked by
+/// `manifest.toml` are used, unless the requirements in `gleam.toml` have
+/// changed since it was written.
+///
+pub fn download(project_root: String) -> Result<(), Error> {
+    run(project_root, Unlock::Nothing)
+}
+
+/// Choose the newest versions of the given packages, or of every package if
+/// none are given, that meet the requirements of the project, and download
+/// them.
+///
+pub fn update(project_root: String, packages: Vec<String>) -> Result<(), Error> {
+    let unlock = if packages.is_empty() {
+        Unlock::All
+    } else {
+        Unlock::Packages(packages)
+    };
+    run(project_root, unlock)
+}
+
+fn run(project_root: String, unlock: Unlock) -> Result<(), Error> {
+    let mut root = ProjectRoot::new(PathBuf::from(project_root));
+    let config = root.root_config()?;
+    let mut repository = HexRepository::new();
+    let _ = build::download_dependencies(&root, &config, &mut repository, unlock)?;
+
+    // The versions were chosen for both profiles above so they are not
+    // unlocked again
+    root.profile = Profile::Test;
+    let manifest = build::download_dependencies(&root, &config, &mut repository, Unlock::Nothing)?;
+
+    crate::cli::print_green_prefix(
+        "      Ready",
+        &format!(
+            "{} {}",
+            manifest.packages.len(),
+            if manifest.packages.len() == 1 {
+                "package"
+            } else {
+                "packages"
+            }
+        ),
+    );
+    Ok(())
+}
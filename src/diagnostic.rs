@@ -1,7 +1,262 @@
 use codespan::{FileId, Files};
 pub use codespan_reporting::diagnostic::{LabelStyle, Severity};
 use codespan_reporting::{diagnostic::Label, term::emit};
-use termcolor::Buffer;
+use serde::Serialize;
+use std::io::Write;
+use strum_macros::{Display, EnumIter, EnumString, EnumVariantNames, IntoStaticStr};
+use termcolor::{Buffer, BufferWriter, ColorSpec, WriteColor};
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "kebab_case")]
+pub enum DiagnosticFormat {
+    Human,
+    Json,
+}
+
+/// The code of each kind of error and warning, shown in diagnostic titles and
+/// explained by the `gleam explain` command.
+///
+/// Codes are stable: once a code has been published it must not be reused
+/// for a different problem, even if the original error is removed.
+///
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Display, EnumString, EnumIter, IntoStaticStr,
+)]
+pub enum Code {
+    E0001,
+    E0002,
+    E0003,
+    E0004,
+    E0005,
+    E0006,
+    E0007,
+    E0008,
+    E0009,
+    E0010,
+    E0011,
+    E0012,
+    E0013,
+    E0014,
+    E0015,
+    E0016,
+    E0017,
+    E0018,
+    E0019,
+    E0020,
+    E0021,
+    E0022,
+    E0023,
+    E0024,
+    E0025,
+    E0026,
+    E0027,
+    E0028,
+    E0029,
+    E0030,
+    E0031,
+    E0032,
+    E0033,
+    E0034,
+    E0035,
+    E0036,
+    E0037,
+    E0038,
+    E0039,
+    E0040,
+    E0041,
+    E0042,
+    E0043,
+    E0044,
+    E0045,
+    E0046,
+    E0047,
+    E0048,
+    E0049,
+    E0050,
+    E0051,
+    E0052,
+    E0053,
+    E0054,
+    E0055,
+    E0056,
+    E0057,
+    E0058,
+    E0059,
+    E0060,
+    E0061,
+    E0062,
+    E0063,
+    W0001,
+    W0002,
+    W0003,
+    W0004,
+    W0005,
+    W0006,
+    W0007,
+    W0008,
+    W0009,
+    W0010,
+    W0011,
+    W0012,
+}
+
+impl Code {
+    pub fn as_str(self) -> &'static str {
+        self.into()
+    }
+}
+
+/// Errors and warnings are written to a DiagnosticBuffer. Human readable
+/// diagnostics are rendered into a terminal buffer as they are written, while
+/// JSON diagnostics are collected so they can be printed one object per line
+/// for editors and other tools to consume.
+///
+pub enum DiagnosticBuffer {
+    Human(Buffer),
+    Json(Vec<JsonDiagnostic>),
+}
+
+impl DiagnosticBuffer {
+    pub fn new(format: DiagnosticFormat, writer: &BufferWriter) -> Self {
+        match format {
+            DiagnosticFormat::Human => DiagnosticBuffer::Human(writer.buffer()),
+            DiagnosticFormat::Json => DiagnosticBuffer::Json(vec![]),
+        }
+    }
+
+    pub fn print(self, writer: &BufferWriter) {
+        match self {
+            DiagnosticBuffer::Human(buffer) => writer.print(&buffer).unwrap(),
+            DiagnosticBuffer::Json(diagnostics) => {
+                let mut buffer = writer.buffer();
+                for line in json_lines(diagnostics) {
+                    writeln!(buffer, "{}", line).unwrap();
+                }
+                writer.print(&buffer).unwrap();
+            }
+        }
+    }
+
+    /// The diagnostics collected by a JSON buffer, with surrounding
+    /// whitespace removed from their messages. Human buffers have none.
+    ///
+    pub fn into_json_diagnostics(self) -> Vec<JsonDiagnostic> {
+        match self {
+            DiagnosticBuffer::Human(_) => vec![],
+            DiagnosticBuffer::Json(diagnostics) => diagnostics
+                .into_iter()
+                .map(|mut diagnostic| {
+                    diagnostic.message = diagnostic.message.trim().to_string();
+                    diagnostic
+                })
+                .collect(),
+        }
+    }
+}
+
+fn json_lines(diagnostics: Vec<JsonDiagnostic>) -> Vec<String> {
+    diagnostics
+        .into_iter()
+        .map(|mut diagnostic| {
+            diagnostic.message = diagnostic.message.trim().to_string();
+            serde_json::to_string(&diagnostic).expect("JSON diagnostic serialization")
+        })
+        .collect()
+}
+
+// Any text written to a JSON buffer forms part of the message of the most
+// recently started diagnostic.
+impl Write for DiagnosticBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            DiagnosticBuffer::Human(buffer) => buffer.write(buf),
+            DiagnosticBuffer::Json(diagnostics) => {
+                if let Some(diagnostic) = diagnostics.last_mut() {
+                    diagnostic.message.push_str(&String::from_utf8_lossy(buf));
+                }
+                Ok(buf.len())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            DiagnosticBuffer::Human(buffer) => buffer.flush(),
+            DiagnosticBuffer::Json(_) => Ok(()),
+        }
+    }
+}
+
+impl WriteColor for DiagnosticBuffer {
+    fn supports_color(&self) -> bool {
+        match self {
+            DiagnosticBuffer::Human(buffer) => buffer.supports_color(),
+            DiagnosticBuffer::Json(_) => false,
+        }
+    }
+
+    fn set_color(&mut self, spec: &ColorSpec) -> std::io::Result<()> {
+        match self {
+            DiagnosticBuffer::Human(buffer) => buffer.set_color(spec),
+            DiagnosticBuffer::Json(_) => Ok(()),
+        }
+    }
+
+    fn reset(&mut self) -> std::io::Result<()> {
+        match self {
+            DiagnosticBuffer::Human(buffer) => buffer.reset(),
+            DiagnosticBuffer::Json(_) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct JsonDiagnostic {
+    pub severity: &'static str,
+    pub code: Code,
+    pub title: String,
+    pub file: Option<String>,
+    pub span: Option<JsonSpan>,
+    pub label: Option<String>,
+    pub message: String,
+}
+
+impl JsonDiagnostic {
+    pub fn new(severity: Severity, code: Code, title: String) -> Self {
+        Self {
+            severity: severity_name(severity),
+            code,
+            title,
+            file: None,
+            span: None,
+            label: None,
+            message: String::new(),
+        }
+    }
+}
+
+/// Byte offsets are zero based and the end is exclusive. Lines and columns
+/// start at 1, as they do in the human readable output.
+///
+#[derive(Debug, PartialEq, Serialize)]
+pub struct JsonSpan {
+    pub start: usize,
+    pub end: usize,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Bug => "bug",
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Note => "note",
+        Severity::Help => "help",
+    }
+}
 
 pub struct DiagnosticLabel {
     pub style: LabelStyle,
@@ -9,6 +264,7 @@ pub struct DiagnosticLabel {
     pub label: String,
 }
 pub struct Diagnostic {
+    pub code: Code,
     pub file: String,
     pub location: crate::ast::SrcSpan,
     pub src: String,
@@ -17,14 +273,16 @@ pub struct Diagnostic {
 }
 
 pub struct MultiLineDiagnostic {
+    pub code: Code,
     pub file: String,
     pub src: String,
     pub title: String,
     pub labels: Vec<DiagnosticLabel>,
 }
 
-pub fn write(buffer: &mut Buffer, d: Diagnostic, severity: Severity) {
+pub fn write(buffer: &mut DiagnosticBuffer, d: Diagnostic, severity: Severity) {
     let diagnostic = MultiLineDiagnostic {
+        code: d.code,
         file: d.file,
         src: d.src,
         title: d.title,
@@ -38,7 +296,15 @@ pub fn write(buffer: &mut Buffer, d: Diagnostic, severity: Severity) {
     write_diagnostic(buffer, diagnostic, severity)
 }
 
-pub fn write_diagnostic(mut buffer: &mut Buffer, d: MultiLineDiagnostic, severity: Severity) {
+pub fn write_diagnostic(buffer: &mut DiagnosticBuffer, d: MultiLineDiagnostic, severity: Severity) {
+    let mut buffer = match buffer {
+        DiagnosticBuffer::Human(buffer) => buffer,
+        DiagnosticBuffer::Json(diagnostics) => {
+            diagnostics.push(json_diagnostic(d, severity));
+            return;
+        }
+    };
+
     let mut files = Files::new();
     let file_id: FileId = files.add(d.file, d.src);
 
@@ -56,6 +322,7 @@ pub fn write_diagnostic(mut buffer: &mut Buffer, d: MultiLineDiagnostic, severit
         .collect();
 
     let diagnostic = codespan_reporting::diagnostic::Diagnostic::new(severity)
+        .with_code(d.code.as_str())
         .with_message(d.title)
         .with_labels(labels);
 
@@ -63,30 +330,116 @@ pub fn write_diagnostic(mut buffer: &mut Buffer, d: MultiLineDiagnostic, severit
     emit(&mut buffer, &config, &files, &diagnostic).unwrap();
 }
 
+fn json_diagnostic(d: MultiLineDiagnostic, severity: Severity) -> JsonDiagnostic {
+    let MultiLineDiagnostic {
+        code,
+        file,
+        src,
+        title,
+        labels,
+    } = d;
+    let mut files = Files::new();
+    let file_id: FileId = files.add(file.clone(), src);
+    let line_and_column = |index: usize| {
+        files
+            .location(file_id, index as u32)
+            .map(|location| (location.line.to_usize() + 1, location.column.to_usize() + 1))
+            .unwrap_or((0, 0))
+    };
+
+    let primary = labels
+        .iter()
+        .find(|label| label.style == LabelStyle::Primary)
+        .or_else(|| labels.first());
+
+    let mut diagnostic = JsonDiagnostic::new(severity, code, title);
+    diagnostic.file = Some(file);
+    if let Some(primary) = primary {
+        let (start_line, start_column) = line_and_column(primary.location.start);
+        let (end_line, end_column) = line_and_column(primary.location.end);
+        diagnostic.span = Some(JsonSpan {
+            start: primary.location.start,
+            end: primary.location.end,
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        });
+        if !primary.label.is_empty() {
+            diagnostic.label = Some(primary.label.clone());
+        }
+    }
+    diagnostic
+}
+
 /// Describes an error encountered while compiling the project (eg. a name collision
 /// between files).
 ///
 pub struct ProjectErrorDiagnostic {
+    pub code: Code,
     pub title: String,
     pub label: String,
 }
 
-pub fn write_title(buffer: &mut Buffer, title: &str) {
-    use std::io::Write;
-    use termcolor::{Color, ColorSpec, WriteColor};
+pub fn write_title(buffer: &mut DiagnosticBuffer, code: Code, title: &str) {
+    use termcolor::Color;
+    if let DiagnosticBuffer::Json(diagnostics) = buffer {
+        diagnostics.push(JsonDiagnostic::new(
+            Severity::Error,
+            code,
+            title.to_string(),
+        ));
+        return;
+    }
     buffer
         .set_color(ColorSpec::new().set_bold(true).set_fg(Some(Color::Red)))
         .unwrap();
-    write!(buffer, "error").unwrap();
+    write!(buffer, "error[{}]", code).unwrap();
     buffer.set_color(ColorSpec::new().set_bold(true)).unwrap();
     write!(buffer, ": {}\n\n", title).unwrap();
     buffer.set_color(&ColorSpec::new()).unwrap();
 }
 
-pub fn write_project(buffer: &mut Buffer, d: ProjectErrorDiagnostic) {
-    use std::io::Write;
-    use termcolor::{ColorSpec, WriteColor};
-    write_title(buffer, d.title.as_ref());
+pub fn write_project(buffer: &mut DiagnosticBuffer, d: ProjectErrorDiagnostic) {
+    write_title(buffer, d.code, d.title.as_ref());
     buffer.set_color(&ColorSpec::new()).unwrap();
     writeln!(buffer, "{}", d.label).unwrap();
 }
+
+#[test]
+fn json_diagnostic_test() {
+    let mut buffer = DiagnosticBuffer::Json(vec![]);
+    write(
+        &mut buffer,
+        Diagnostic {
+            code: Code::W0008,
+            file: "src/one.gleam".to_string(),
+            location: crate::ast::SrcSpan { start: 22, end: 23 },
+            src: "pub fn main() {\n  let x = 1\n  1\n}\n".to_string(),
+            title: "Unused variable".to_string(),
+            label: "".to_string(),
+        },
+        Severity::Warning,
+    );
+    writeln!(buffer, "The variable x is never used.\n").unwrap();
+    write_project(
+        &mut buffer,
+        ProjectErrorDiagnostic {
+            code: Code::E0048,
+            title: "Import cycle".to_string(),
+            label: "The imports form a cycle.".to_string(),
+        },
+    );
+
+    let diagnostics = match buffer {
+        DiagnosticBuffer::Json(diagnostics) => diagnostics,
+        DiagnosticBuffer::Human(_) => unreachable!(),
+    };
+    assert_eq!(
+        json_lines(diagnostics),
+        vec![
+            r#"{"severity":"warning","code":"W0008","title":"Unused variable","file":"src/one.gleam","span":{"start":22,"end":23,"start_line":2,"start_column":7,"end_line":2,"end_column":8},"label":null,"message":"The variable x is never used."}"#,
+            r#"{"severity":"error","code":"E0048","title":"Import cycle","file":null,"span":null,"label":null,"message":"The imports form a cycle."}"#,
+        ]
+    );
+}
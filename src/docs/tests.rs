@@ -46,7 +46,9 @@ pub fn complicated_fun(
         version: Default::default(),
         description: Default::default(),
         dependencies: Default::default(),
+        dev_dependencies: Default::default(),
         otp_start_module: None,
+        warnings_as_errors: false,
     };
 
     let analysed = crate::project::analysed(vec![input]).expect("Compilation failed");
@@ -539,7 +539,9 @@ fn pattern(p: &TypedPattern, env: &mut Env) -> Document {
     match p {
         Pattern::Nil { .. } => "[]".to_doc(),
 
-        Pattern::Let { name, pattern: p } => pattern(p, env)
+        Pattern::Let {
+            name, pattern: p, ..
+        } => pattern(p, env)
             .append(" = ")
             .append(env.next_local_var_name(name.to_string())),
 
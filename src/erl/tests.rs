@@ -208,14 +208,17 @@ tail(List) ->
     );
 
     assert_erl!(
-        "fn tail(list) { case list { [x, ..] -> x } }",
+        "fn tail(list) { case list { [x, ..] -> x [] -> 0 } }",
         r#"-module(the_app).
 -compile(no_auto_import).
 
 tail(List) ->
     case List of
         [X | _] ->
-            X
+            X;
+
+        [] ->
+            0
     end.
 "#,
     );
@@ -830,6 +833,32 @@ main() ->
 "#,
     );
 
+    // Names bound with as are assigned the matched value
+    assert_erl!(
+        r#"
+pub fn main(x) {
+  case x {
+    [_] as list -> list
+    _ -> []
+  }
+}
+"#,
+        r#"-module(the_app).
+-compile(no_auto_import).
+
+-export([main/1]).
+
+main(X) ->
+    case X of
+        [_] = List ->
+            List;
+
+        _ ->
+            []
+    end.
+"#,
+    );
+
     assert_erl!(
         r#"
 pub fn main() {
@@ -863,6 +892,7 @@ pub fn main() {
   let x = 0.123
   case x {
     _ if x == 3.14 -> 1
+    _ -> 0
   }
 }
 "#,
@@ -875,7 +905,10 @@ main() ->
     X = 0.123,
     case X of
         _ when X =:= 3.14 ->
-            1
+            1;
+
+        _ ->
+            0
     end.
 "#,
     );
@@ -886,6 +919,7 @@ pub fn main() {
   let x = 0.123
   case x {
     _ if 0.123 <. x -> 1
+    _ -> 0
   }
 }
 "#,
@@ -898,7 +932,10 @@ main() ->
     X = 0.123,
     case X of
         _ when 0.123 < X ->
-            1
+            1;
+
+        _ ->
+            0
     end.
 "#,
     );
@@ -908,6 +945,7 @@ main() ->
 pub fn main(x) {
   case x {
     _ if x == [1, 2, 3] -> 1
+    _ -> 0
   }
 }
 "#,
@@ -919,7 +957,10 @@ pub fn main(x) {
 main(X) ->
     case X of
         _ when X =:= [1, 2, 3] ->
-            1
+            1;
+
+        _ ->
+            0
     end.
 "#,
     );
@@ -1019,6 +1060,7 @@ pub fn main() {
   let x = 0
   case x {
     _ if x == 0 -> 1
+    _ -> 0
   }
 }
 "#,
@@ -1031,7 +1073,10 @@ main() ->
     X = 0,
     case X of
         _ when X =:= 0 ->
-            1
+            1;
+
+        _ ->
+            0
     end.
 "#,
     );
@@ -1042,6 +1087,7 @@ pub fn main() {
   let x = 0
   case x {
     _ if 0 < x -> 1
+    _ -> 0
   }
 }
 "#,
@@ -1054,7 +1100,10 @@ main() ->
     X = 0,
     case X of
         _ when 0 < X ->
-            1
+            1;
+
+        _ ->
+            0
     end.
 "#,
     );
@@ -1066,6 +1115,7 @@ main() ->
 pub fn main() {
   case "test" {
     x if x == "test" -> 1
+    _ -> 0
   }
 }
 "#,
@@ -1077,7 +1127,10 @@ pub fn main() {
 main() ->
     case <<"test"/utf8>> of
         X when X =:= <<"test"/utf8>> ->
-            1
+            1;
+
+        _ ->
+            0
     end.
 "#,
     );
@@ -1746,6 +1799,7 @@ pub fn test() {
       let duplicate_name = duplicate_name + 1
       duplicate_name
     }
+    _ -> 0
   }
 }"#,
         r#"-module(the_app).
@@ -1762,7 +1816,10 @@ test() ->
 
         2 ->
             DuplicateName1 = DuplicateName + 1,
-            DuplicateName1
+            DuplicateName1;
+
+        _ ->
+            0
     end.
 "#,
     );
@@ -1799,6 +1856,7 @@ pub fn test() {
 
     case 1 {
         1 | 2 if duplicate_name == 1 -> duplicate_name
+        _ -> 0
     }
 }"#,
         r#"-module(the_app).
@@ -1813,7 +1871,10 @@ test() ->
             DuplicateName;
 
         2 when DuplicateName =:= 1 ->
-            DuplicateName
+            DuplicateName;
+
+        _ ->
+            0
     end.
 "#,
     );
@@ -1,15 +1,15 @@
 use crate::{
     cli,
     diagnostic::{
-        write, write_diagnostic, write_project, Diagnostic, DiagnosticLabel, LabelStyle,
-        MultiLineDiagnostic, ProjectErrorDiagnostic, Severity,
+        write, write_diagnostic, write_project, Code, Diagnostic, DiagnosticBuffer,
+        DiagnosticFormat, DiagnosticLabel, JsonDiagnostic, LabelStyle, MultiLineDiagnostic,
+        ProjectErrorDiagnostic, Severity,
     },
     typ::pretty::Printer,
 };
 use itertools::Itertools;
 use std::fmt::Debug;
-use std::path::PathBuf;
-use termcolor::Buffer;
+use std::path::{Path, PathBuf};
 
 pub type Src = String;
 pub type Name = String;
@@ -74,7 +74,7 @@ pub enum Error {
     Type {
         path: PathBuf,
         src: Src,
-        error: crate::typ::Error,
+        errors: Vec<crate::typ::Error>,
     },
 
     UnknownImport {
@@ -139,6 +139,74 @@ pub enum Error {
         command: String,
         err: Option<std::io::ErrorKind>,
     },
+
+    Multiple {
+        errors: Vec<Error>,
+    },
+
+    UnknownErrorCode {
+        code: String,
+    },
+
+    WarningsAsErrors {
+        count: usize,
+    },
+
+    LanguageServer(String),
+
+    Rename {
+        name: Name,
+        new_name: Name,
+        problem: RenameProblem,
+    },
+
+    DownloadPackage {
+        package: Name,
+        version: String,
+        problem: DownloadProblem,
+    },
+
+    DependencyResolution {
+        package: Name,
+        problem: ResolutionProblem,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RenameProblem {
+    UnknownName,
+    InvalidName,
+    Dependency,
+    ProjectErrors,
+    ChangesMeaning,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum DownloadProblem {
+    NotFound,
+    InvalidTarball(String),
+    ChecksumMismatch { expected: String, actual: String },
+    ManifestChecksumMismatch { expected: String, actual: String },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ResolutionProblem {
+    InvalidRequirement {
+        required_by: String,
+        requirement: String,
+    },
+    UnknownPackage {
+        required_by: String,
+    },
+    /// A package to update is not one of the dependencies of the project.
+    NotADependency,
+    /// No version meets the requirements on the package. The requirements
+    /// are given with the package, or package release, they come from, for
+    /// each release that was tried.
+    Conflict {
+        requirements: Vec<(String, String)>,
+        versions: Vec<String>,
+    },
 }
 
 #[derive(Debug, PartialEq)]
@@ -214,8 +282,18 @@ fn did_you_mean(name: &str, options: &mut Vec<String>, alt: &'static str) -> Str
 }
 
 impl Error {
-    pub fn pretty(&self, buffer: &mut Buffer) {
-        use crate::typ::Error::*;
+    /// Combine the errors from several independently compiled modules into a
+    /// single error, returning None if there are no errors.
+    ///
+    pub fn from_errors(mut errors: Vec<Error>) -> Option<Error> {
+        match errors.len() {
+            0 => None,
+            1 => errors.pop(),
+            _ => Some(Error::Multiple { errors }),
+        }
+    }
+
+    pub fn pretty(&self, buffer: &mut DiagnosticBuffer) {
         use std::io::Write;
 
         buffer
@@ -225,6 +303,7 @@ impl Error {
         match self {
             Error::ShellCommand { command, err: None } => {
                 let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0057,
                     title: "Shell command failure".to_string(),
                     label: format!(
                         "There was a problem when running the shell command `{}`.",
@@ -239,6 +318,7 @@ impl Error {
                 err: Some(err),
             } => {
                 let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0057,
                     title: "Shell command failure".to_string(),
                     label: format!(
                         "There was a problem when running the shell command `{}`.
@@ -255,6 +335,7 @@ The error from the shell command library was:
 
             Error::Gzip(detail) => {
                 let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0056,
                     title: "Gzip compression failure".to_string(),
                     label: format!(
                         "There was a problem when applying gzip compression.
@@ -270,6 +351,7 @@ This was error from the gzip library:
 
             Error::Tar { path, err } => {
                 let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0054,
                     title: "Failure creating tar archive".to_string(),
                     label: format!(
                         "There was a problem when attempting to add the file {}
@@ -287,6 +369,7 @@ This was error from the tar library:
 
             Error::TarFinish(detail) => {
                 let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0055,
                     title: "Failure creating tar archive".to_string(),
                     label: format!(
                         "There was a problem when creating a tar archive.
@@ -302,6 +385,7 @@ This was error from the tar library:
 
             Error::Hex(detail) => {
                 let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0053,
                     title: "Hex API failure".to_string(),
                     label: format!(
                         "There was a problem when using the Hex API.
@@ -323,6 +407,7 @@ This was error from the Hex client library:
                 test_module,
             } => {
                 let diagnostic = Diagnostic {
+                    code: Code::E0047,
                     title: "App importing test module".to_string(),
                     label: "Imported here".to_string(),
                     file: path.to_str().unwrap().to_string(),
@@ -347,6 +432,7 @@ cannot import them. Perhaps move the `{}` module to the src directory.",
                 second,
             } => {
                 let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0046,
                     title: "Duplicate module".to_string(),
                     label: format!(
                         "The module `{}` is defined multiple times.
@@ -375,6 +461,7 @@ Second: {}",
                     None => "".to_string(),
                 };
                 let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0050,
                     title: "File IO failure".to_string(),
                     label: format!(
                         "An error occurred while trying to {} this {}:
@@ -390,1037 +477,1354 @@ Second: {}",
                 write_project(buffer, diagnostic);
             }
 
-            Error::Type { path, src, error } => match error {
-                UnknownLabels {
-                    unknown,
-                    valid,
-                    supplied,
-                } => {
-                    let mut other_labels = valid
-                        .into_iter()
-                        .cloned()
-                        .filter(|label| !supplied.contains(label))
-                        .collect();
-
-                    let title = if unknown.len() > 1 {
-                        "Unknown labels"
-                    } else {
-                        "Unknown label"
-                    };
-
-                    let diagnostic = MultiLineDiagnostic {
-                        title: title.to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        labels: unknown
-                            .iter()
-                            .map(|(label, location)| DiagnosticLabel {
-                                label: did_you_mean(label, &mut other_labels, "Unexpected label"),
-                                location: location.clone(),
-                                style: LabelStyle::Primary,
-                            })
-                            .collect(),
-                    };
-                    write_diagnostic(buffer, diagnostic, Severity::Error);
-
-                    if valid.is_empty() {
-                        writeln!(
-                            buffer,
-                            "This constructor does not accept any labelled arguments."
-                        )
-                        .unwrap();
-                    } else {
-                        if other_labels.is_empty() {
-                            writeln!(
-                                buffer,
-                                "You have already supplied all the labelled arguments that this constructor accepts."
-                            )
-                            .unwrap();
-                        } else {
-                            writeln!(
-                                buffer,
-                                "The other labelled arguments that this constructor accepts are `{}`.",
-                                other_labels.iter().join("`, `")
-                            )
-                            .unwrap();
-                        }
+            Error::Type { path, src, errors } => {
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        buffer
+                            .write_all(b"\n")
+                            .expect("error pretty buffer write space between errors");
                     }
+                    type_error(buffer, path, src, error);
                 }
+            }
 
-                UnexpectedLabelledArg { location, label } => {
-                    let diagnostic = Diagnostic {
-                        title: "Unexpected labelled argument".to_string(),
-                        label: "".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "
-This argument has been given a label but the constructor does not expect any.
-Please remove the label `{}`.",
-                        label
-                    )
-                    .unwrap();
-                }
-
-                PositionalArgumentAfterLabelled { location } => {
-                    let diagnostic = Diagnostic {
-                        title: "Unexpected positional argument".to_string(),
-                        label: "".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "This unlablled argument has been supplied after a labelled argument.
-Once a labelled argument has been supplied all following arguments must
-also be labelled.",
-                    )
-                    .unwrap();
-                }
+            Error::Parse { path, src, error } => {
+                use lalrpop_util::ParseError::*;
 
-                DuplicateName {
-                    location,
-                    name: fun,
-                    previous_location,
-                    ..
-                } => {
-                    let diagnostic = MultiLineDiagnostic {
-                        title: format!("Duplicate function definition with name `{}`", fun),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        labels: vec![
-                            DiagnosticLabel {
-                                label: "redefined here".to_string(),
-                                location: location.clone(),
-                                style: LabelStyle::Primary,
-                            },
-                            DiagnosticLabel {
-                                label: "previously defined here".to_string(),
-                                location: previous_location.clone(),
-                                style: LabelStyle::Secondary,
+                match error {
+                    UnrecognizedToken {
+                        token: (start, _, end),
+                        expected,
+                    } => {
+                        let diagnostic = Diagnostic {
+                            code: Code::E0041,
+                            title: "Syntax error".to_string(),
+                            label: "Unexpected token".to_string(),
+                            file: path.to_str().unwrap().to_string(),
+                            src: src.to_string(),
+                            location: crate::ast::SrcSpan {
+                                start: *start,
+                                end: *end,
                             },
-                        ],
-                    };
-                    write_diagnostic(buffer, diagnostic, Severity::Error);
-                }
+                        };
+                        write(buffer, diagnostic, Severity::Error);
+                        writeln!(buffer, "Expected one of {}", expected.join(", "))
+                            .expect("error pretty buffer write");
+                    }
 
-                DuplicateTypeName {
-                    name,
-                    location,
-                    previous_location,
-                    ..
-                } => {
-                    let diagnostic = MultiLineDiagnostic {
-                        title: format!("Duplicate type definition with name `{}`", name),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        labels: vec![
-                            DiagnosticLabel {
-                                label: "redefined here".to_string(),
-                                location: location.clone(),
-                                style: LabelStyle::Primary,
-                            },
-                            DiagnosticLabel {
-                                label: "previously defined here".to_string(),
-                                location: previous_location.clone(),
-                                style: LabelStyle::Secondary,
+                    UnrecognizedEOF { .. } => {
+                        let diagnostic = Diagnostic {
+                            code: Code::E0042,
+                            title: "Syntax error".to_string(),
+                            label: "Unexpected end of file".to_string(),
+                            file: path.to_str().unwrap().to_string(),
+                            src: src.to_string(),
+                            location: crate::ast::SrcSpan {
+                                start: src.len().saturating_sub(2),
+                                end: src.len().saturating_sub(1),
                             },
-                        ],
-                    };
-                    write_diagnostic(buffer, diagnostic, Severity::Error);
-                }
+                        };
+                        write(buffer, diagnostic, Severity::Error);
+                    }
 
-                DuplicateField { location, label } => {
-                    let diagnostic = Diagnostic {
-                        title: "Duplicate field".to_string(),
-                        label: "".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "The field `{}` has already been defined. Rename this field.",
-                        label
-                    )
-                    .unwrap();
-                }
+                    InvalidToken { location } => {
+                        let diagnostic = Diagnostic {
+                            code: Code::E0043,
+                            title: "Syntax error".to_string(),
+                            label: "Unknown token".to_string(),
+                            file: path.to_str().unwrap().to_string(),
+                            src: src.to_string(),
+                            location: crate::ast::SrcSpan {
+                                start: *location,
+                                end: *location + 1,
+                            },
+                        };
+                        write(buffer, diagnostic, Severity::Error);
+                        writeln!(
+                            buffer,
+                            "I don't know what this character means. Is it a typo?"
+                        )
+                        .expect("error pretty buffer write");
+                    }
 
-                DuplicateArgument { location, label } => {
-                    let diagnostic = Diagnostic {
-                        title: "Duplicate argument".to_string(),
-                        label: "".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "The labelled argument `{}` has already been supplied.",
-                        label
-                    )
-                    .unwrap();
-                }
+                    ExtraToken { .. } => unimplemented!(),
 
-                RecursiveType { location } => {
-                    let diagnostic = Diagnostic {
-                        title: "Recursive type".to_string(),
-                        label: "".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
+                    User { error } => {
+                        use crate::parser::Error;
+                        match error {
+                            Error::TooManyHolesInCapture { location, count } => {
+                                let diagnostic = Diagnostic {
+                                    code: Code::E0044,
+                                    title: "Invalid capture".to_string(),
+                                    label: "".to_string(),
+                                    file: path.to_str().unwrap().to_string(),
+                                    src: src.to_string(),
+                                    location: location.clone(),
+                                };
+                                write(buffer, diagnostic, Severity::Error);
+                                let chars: String = (97..(97 + count))
+                                    .map(|x| x as u8 as char)
+                                    .map(|c| c.to_string())
+                                    .intersperse(", ".to_string())
+                                    .collect();
+                                writeln!(
+                                    buffer,
+                                    "The function capture syntax can only be used with a single _ argument,
+but this one uses {}. Rewrite this using the fn({}) {{ ... }} syntax.",
+                                    count, chars
+                                )
+                                .expect("error pretty buffer write");
+                            }
+                        }
+                    }
                 }
+            }
 
-                NotFn { location, typ } => {
-                    let diagnostic = Diagnostic {
-                        title: "Type mismatch".to_string(),
-                        label: "".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    let mut printer = Printer::new();
+            Error::ImportCycle { modules } => {
+                crate::diagnostic::write_title(buffer, Code::E0048, "Import cycle");
+                writeln!(
+                    buffer,
+                    "The import statements for these modules form a cycle:\n"
+                )
+                .unwrap();
+                import_cycle(buffer, modules.as_ref());
 
-                    writeln!(
-                        buffer,
-                        "This value is being called as a function but its type is:\n\n{}",
-                        printer.pretty_print(typ, 4)
-                    )
-                    .unwrap();
-                }
+                writeln!(
+                    buffer,
+                    "Gleam doesn't support import cycles like these, please break the
+cycle to continue."
+                )
+                .unwrap();
+            }
 
-                UnknownField {
-                    location,
-                    typ,
-                    label,
-                    fields,
-                } => {
-                    let mut fields = fields.clone();
-                    let diagnostic = Diagnostic {
-                        title: "Unknown field".to_string(),
-                        label: did_you_mean(
-                            label.as_ref(),
-                            &mut fields,
-                            "This field does not exist",
-                        ),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    let mut printer = Printer::new();
+            Error::PackageCycle { packages } => {
+                crate::diagnostic::write_title(buffer, Code::E0049, "Dependency cycle");
+                writeln!(
+                    buffer,
+                    "The dependencies for these packages form a cycle:\n"
+                )
+                .unwrap();
+                import_cycle(buffer, packages.as_ref());
+                writeln!(
+                    buffer,
+                    "Gleam doesn't support dependency cycles like these, please break the
+cycle to continue."
+                )
+                .unwrap();
+            }
 
-                    writeln!(
-                        buffer,
-                        "The value has this type:
+            Error::UnknownImport {
+                module,
+                import,
+                location,
+                path,
+                src,
+                modules,
+            } => {
+                let mut modules = modules.clone();
+                let diagnostic = Diagnostic {
+                    code: Code::E0045,
+                    title: "Unknown import".to_string(),
+                    label: did_you_mean(import, &mut modules, ""),
+                    file: path.to_str().unwrap().to_string(),
+                    src: src.to_string(),
+                    location: location.clone(),
+                };
+                write(buffer, diagnostic, Severity::Error);
+                writeln!(
+                    buffer,
+                    "The module `{}` is trying to import the module `{}`,
+but it cannot be found.",
+                    module, import
+                )
+                .expect("error pretty buffer write");
+            }
 
-{}
-",
-                        printer.pretty_print(typ, 4)
-                    )
-                    .unwrap();
+            Error::StandardIO { action, err } => {
+                let err = match err {
+                    Some(e) => format!(
+                        "\nThe error message from the stdio library was:\n\n    {}\n",
+                        std_io_error_kind_text(e)
+                    ),
+                    None => "".to_string(),
+                };
+                let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0051,
+                    title: "Standard IO failure".to_string(),
+                    label: format!(
+                        "An error occurred while trying to {}:
 
-                    if fields.is_empty() {
-                        writeln!(buffer, "It does not have any fields.",).unwrap();
-                    } else {
-                        write!(buffer, "It has these fields:\n\n").unwrap();
-                        for field in fields {
-                            writeln!(buffer, "    .{}", field).unwrap();
-                        }
+{}",
+                        action.text(),
+                        err,
+                    ),
+                };
+                write_project(buffer, diagnostic);
+            }
+            Error::Format { problem_files } => {
+                // Each unformatted file is a separate JSON diagnostic so that
+                // tools can annotate the files individually
+                if let DiagnosticBuffer::Json(diagnostics) = buffer {
+                    for formatted in problem_files {
+                        let mut diagnostic = JsonDiagnostic::new(
+                            Severity::Error,
+                            Code::E0052,
+                            "This file has not been formatted".to_string(),
+                        );
+                        diagnostic.file = Some(formatted.source.to_string_lossy().to_string());
+                        diagnostics.push(diagnostic);
                     }
+                    return;
                 }
 
-                CouldNotUnify {
-                    location,
-                    expected,
-                    given,
-                } => {
-                    let diagnostic = Diagnostic {
-                        title: "Type mismatch".to_string(),
-                        label: "".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    let mut printer = Printer::new();
+                let files: Vec<_> = problem_files
+                    .iter()
+                    .flat_map(|formatted| formatted.source.to_str())
+                    .map(|p| format!("  - {}", p))
+                    .sorted()
+                    .collect();
+                let mut label = files.iter().join("\n");
+                label.push('\n');
+                let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0052,
+                    title: "These files have not been formatted".to_string(),
+                    label,
+                };
 
-                    writeln!(
-                        buffer,
-                        "Expected type:
+                write_project(buffer, diagnostic);
+            }
 
-{}
+            Error::Multiple { errors } => {
+                for error in errors {
+                    error.pretty(buffer);
+                }
+            }
 
-Found type:
+            Error::UnknownErrorCode { code } => {
+                let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0058,
+                    title: "Unknown error code".to_string(),
+                    label: format!(
+                        "There is no error or warning with the code `{}`.
 
-{}",
-                        printer.pretty_print(expected, 4),
-                        printer.pretty_print(given, 4),
-                    )
-                    .unwrap();
-                }
+Codes are shown in the title of each error and warning, as in E0013.",
+                        code
+                    ),
+                };
+                write_project(buffer, diagnostic);
+            }
 
-                IncorrectTypeArity {
-                    location,
-                    expected,
-                    given,
-                    ..
-                } => {
-                    let diagnostic = Diagnostic {
-                        title: "Incorrect arity".to_string(),
-                        label: format!("expected {} arguments, got {}", expected, given),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                }
+            Error::WarningsAsErrors { count } => {
+                let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0059,
+                    title: "Warnings treated as errors".to_string(),
+                    label: format!(
+                        "Compilation produced {} {}, and warnings are being treated as errors.
 
-                IncorrectArity {
-                    labels,
-                    location,
-                    expected,
-                    given,
-                } => {
-                    let diagnostic = Diagnostic {
-                        title: "Incorrect arity".to_string(),
-                        label: format!("expected {} arguments, got {}", expected, given),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    if !labels.is_empty() {
-                        let labels = labels
+Fix the warnings, or remove the --warnings-as-errors flag and the
+warnings_as_errors setting in gleam.toml to allow them.",
+                        count,
+                        if *count == 1 { "warning" } else { "warnings" }
+                    ),
+                };
+                write_project(buffer, diagnostic);
+            }
+
+            Error::LanguageServer(detail) => {
+                let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0060,
+                    title: "Language server failure".to_string(),
+                    label: format!(
+                        "There was a problem communicating with the editor.
+
+This was the error:
+
+    {}",
+                        detail
+                    ),
+                };
+                write_project(buffer, diagnostic);
+            }
+
+            Error::Rename {
+                name,
+                new_name,
+                problem,
+            } => {
+                let label = match problem {
+                    RenameProblem::UnknownName => format!(
+                        "There is no function, constant, record constructor, or type called
+`{}`. Names are given with the module they are defined in, as in
+`my_app/user.create`.",
+                        name
+                    ),
+
+                    RenameProblem::InvalidName if name.starts_with(char::is_uppercase) => format!(
+                        "`{}` is not a valid name for a type or record constructor. These names
+start with an uppercase letter and contain only letters and digits, as in
+`UserId`.",
+                        new_name
+                    ),
+
+                    RenameProblem::InvalidName => format!(
+                        "`{}` is not a valid name for a function or constant. These names start
+with a lowercase letter and contain only lowercase letters, digits, and
+underscores, as in `user_id`.",
+                        new_name
+                    ),
+
+                    RenameProblem::Dependency => format!(
+                        "`{}` is defined in a dependency, so it cannot be renamed here.",
+                        name
+                    ),
+
+                    RenameProblem::ProjectErrors => format!(
+                        "The project contains errors, so not every reference to `{}` can be
+found. Fix the errors and try again.",
+                        name
+                    ),
+
+                    RenameProblem::ChangesMeaning => format!(
+                        "Renaming `{}` to `{}` would change the meaning of the program, as the
+new name is already in use where `{}` is referred to.",
+                        name, new_name, name
+                    ),
+                };
+                let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0061,
+                    title: "Rename not possible".to_string(),
+                    label,
+                };
+                write_project(buffer, diagnostic);
+            }
+
+            Error::DownloadPackage {
+                package,
+                version,
+                problem,
+            } => {
+                let label = match problem {
+                    DownloadProblem::NotFound => format!(
+                        "There is no release of `{}` with the version {} on Hex.",
+                        package, version
+                    ),
+
+                    DownloadProblem::InvalidTarball(detail) => format!(
+                        "The tarball for {} v{} downloaded from Hex could not be read.
+
+This was the problem:
+
+    {}",
+                        package, version, detail
+                    ),
+
+                    DownloadProblem::ChecksumMismatch { expected, actual } => format!(
+                        "The tarball for {} v{} downloaded from Hex does not match its
+checksum, so it has not been used.
+
+Expected checksum: {}
+Actual checksum:   {}",
+                        package, version, expected, actual
+                    ),
+
+                    DownloadProblem::ManifestChecksumMismatch { expected, actual } => format!(
+                        "The tarball for {} v{} downloaded from Hex does not match the
+checksum recorded in manifest.toml, so it has not been used.
+
+Expected checksum: {}
+Actual checksum:   {}
+
+If the release has been republished on Hex run `gleam deps update {}`
+to record the new checksum.",
+                        package, version, expected, actual, package
+                    ),
+                };
+                let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0062,
+                    title: "Package download failed".to_string(),
+                    label,
+                };
+                write_project(buffer, diagnostic);
+            }
+
+            Error::DependencyResolution { package, problem } => {
+                let label = match problem {
+                    ResolutionProblem::InvalidRequirement {
+                        required_by,
+                        requirement,
+                    } => format!(
+                        "{} requires `{}` with the version requirement `{}`, which is not
+valid. Requirements are written as in `~> 1.2`, `>= 1.0.0 and < 2.0.0`,
+or `1.4.0`.",
+                        required_by, package, requirement
+                    ),
+
+                    ResolutionProblem::UnknownPackage { required_by } => format!(
+                        "{} requires the package `{}`, which could not be found on Hex.",
+                        required_by, package
+                    ),
+
+                    ResolutionProblem::NotADependency => format!(
+                        "`{}` cannot be updated as it is not one of the dependencies of the
+project.",
+                        package
+                    ),
+
+                    ResolutionProblem::Conflict {
+                        requirements,
+                        versions,
+                    } => {
+                        let width = requirements
+                            .iter()
+                            .map(|(required_by, _)| required_by.len())
+                            .max()
+                            .unwrap_or(0);
+                        let requirements = requirements
                             .iter()
-                            .map(|p| format!("  - {}", p))
-                            .sorted()
+                            .map(|(required_by, requirement)| {
+                                format!(
+                                    "    {:width$} requires {}",
+                                    required_by,
+                                    requirement,
+                                    width = width
+                                )
+                            })
                             .join("\n");
-                        writeln!(
-                            buffer,
-                            "This call accepts these additional labelled arguments:\n\n{}\n",
-                            labels,
+                        let versions = if versions.is_empty() {
+                            "There are no releases of it.".to_string()
+                        } else {
+                            format!("Its releases are {}.", versions.join(", "))
+                        };
+                        format!(
+                            "There is no version of `{}` that meets the requirements on it:
+
+{}
+
+{}",
+                            package, requirements, versions
                         )
-                        .unwrap();
                     }
-                }
+                };
+                let diagnostic = ProjectErrorDiagnostic {
+                    code: Code::E0063,
+                    title: "Dependency resolution failed".to_string(),
+                    label,
+                };
+                write_project(buffer, diagnostic);
+            }
+        }
+    }
+
+    pub fn print(&self, format: DiagnosticFormat) {
+        let buffer_writer = cli::stderr_buffer_writer();
+        let mut buffer = DiagnosticBuffer::new(format, &buffer_writer);
+        self.pretty(&mut buffer);
+        buffer.print(&buffer_writer);
+    }
+}
 
-                UnnecessarySpreadOperator { location, arity } => {
-                    let diagnostic = Diagnostic {
-                        title: "Unnecessary spread operator".to_string(),
-                        label: format!(""),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
+fn type_error(buffer: &mut DiagnosticBuffer, path: &Path, src: &Src, error: &crate::typ::Error) {
+    use crate::typ::Error::*;
+    use std::io::Write;
+
+    match error {
+        UnknownLabels {
+            unknown,
+            valid,
+            supplied,
+        } => {
+            let mut other_labels = valid
+                .into_iter()
+                .cloned()
+                .filter(|label| !supplied.contains(label))
+                .collect();
+
+            let title = if unknown.len() > 1 {
+                "Unknown labels"
+            } else {
+                "Unknown label"
+            };
+
+            let diagnostic = MultiLineDiagnostic {
+                code: Code::E0001,
+                title: title.to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                labels: unknown
+                    .iter()
+                    .map(|(label, location)| DiagnosticLabel {
+                        label: did_you_mean(label, &mut other_labels, "Unexpected label"),
                         location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
+                        style: LabelStyle::Primary,
+                    })
+                    .collect(),
+            };
+            write_diagnostic(buffer, diagnostic, Severity::Error);
 
+            if valid.is_empty() {
+                writeln!(
+                    buffer,
+                    "This constructor does not accept any labelled arguments."
+                )
+                .unwrap();
+            } else {
+                if other_labels.is_empty() {
+                    writeln!(
+                buffer,
+                "You have already supplied all the labelled arguments that this constructor accepts."
+            )
+            .unwrap();
+                } else {
                     writeln!(
                         buffer,
-                        "This record has {} fields and you have already assigned variables to all of them.",
-                        arity
+                        "The other labelled arguments that this constructor accepts are `{}`.",
+                        other_labels.iter().join("`, `")
                     )
                     .unwrap();
                 }
+            }
+        }
+
+        UnexpectedLabelledArg { location, label } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0020,
+                title: "Unexpected labelled argument".to_string(),
+                label: "".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "
+This argument has been given a label but the constructor does not expect any.
+Please remove the label `{}`.",
+                label
+            )
+            .unwrap();
+        }
 
-                UnknownType {
-                    location,
-                    name,
-                    types,
-                } => {
-                    let mut types = types.clone();
-                    let diagnostic = Diagnostic {
-                        title: "Unknown type".to_string(),
-                        label: did_you_mean(name, &mut types, ""),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
+        PositionalArgumentAfterLabelled { location } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0021,
+                title: "Unexpected positional argument".to_string(),
+                label: "".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "This unlablled argument has been supplied after a labelled argument.
+Once a labelled argument has been supplied all following arguments must
+also be labelled.",
+            )
+            .unwrap();
+        }
+
+        DuplicateName {
+            location,
+            name: fun,
+            previous_location,
+            ..
+        } => {
+            let diagnostic = MultiLineDiagnostic {
+                code: Code::E0015,
+                title: format!("Duplicate function definition with name `{}`", fun),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                labels: vec![
+                    DiagnosticLabel {
+                        label: "redefined here".to_string(),
                         location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "The type `{}` is not defined or imported in this module.",
-                        name
-                    )
-                    .unwrap();
-                }
+                        style: LabelStyle::Primary,
+                    },
+                    DiagnosticLabel {
+                        label: "previously defined here".to_string(),
+                        location: previous_location.clone(),
+                        style: LabelStyle::Secondary,
+                    },
+                ],
+            };
+            write_diagnostic(buffer, diagnostic, Severity::Error);
+        }
 
-                UnknownVariable {
-                    location,
-                    variables,
-                    name,
-                } => {
-                    let mut variables = variables.clone();
-                    let diagnostic = Diagnostic {
-                        title: "Unknown variable".to_string(),
-                        label: did_you_mean(name, &mut variables, ""),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
+        DuplicateTypeName {
+            name,
+            location,
+            previous_location,
+            ..
+        } => {
+            let diagnostic = MultiLineDiagnostic {
+                code: Code::E0016,
+                title: format!("Duplicate type definition with name `{}`", name),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                labels: vec![
+                    DiagnosticLabel {
+                        label: "redefined here".to_string(),
                         location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(buffer, "The name `{}` is not in scope here.", name).unwrap();
+                        style: LabelStyle::Primary,
+                    },
+                    DiagnosticLabel {
+                        label: "previously defined here".to_string(),
+                        location: previous_location.clone(),
+                        style: LabelStyle::Secondary,
+                    },
+                ],
+            };
+            write_diagnostic(buffer, diagnostic, Severity::Error);
+        }
+
+        DuplicateField { location, label } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0018,
+                title: "Duplicate field".to_string(),
+                label: "".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "The field `{}` has already been defined. Rename this field.",
+                label
+            )
+            .unwrap();
+        }
+
+        DuplicateArgument { location, label } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0017,
+                title: "Duplicate argument".to_string(),
+                label: "".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "The labelled argument `{}` has already been supplied.",
+                label
+            )
+            .unwrap();
+        }
+
+        RecursiveType { location } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0014,
+                title: "Recursive type".to_string(),
+                label: "".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+        }
+
+        NotFn { location, typ } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0008,
+                title: "Type mismatch".to_string(),
+                label: "".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            let mut printer = Printer::new();
+
+            writeln!(
+                buffer,
+                "This value is being called as a function but its type is:\n\n{}",
+                printer.pretty_print(typ, 4)
+            )
+            .unwrap();
+        }
+
+        UnknownField {
+            location,
+            typ,
+            label,
+            fields,
+        } => {
+            let mut fields = fields.clone();
+            let diagnostic = Diagnostic {
+                code: Code::E0009,
+                title: "Unknown field".to_string(),
+                label: did_you_mean(label.as_ref(), &mut fields, "This field does not exist"),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            let mut printer = Printer::new();
+
+            writeln!(
+                buffer,
+                "The value has this type:
+
+{}
+",
+                printer.pretty_print(typ, 4)
+            )
+            .unwrap();
+
+            if fields.is_empty() {
+                writeln!(buffer, "It does not have any fields.",).unwrap();
+            } else {
+                write!(buffer, "It has these fields:\n\n").unwrap();
+                for field in fields {
+                    writeln!(buffer, "    .{}", field).unwrap();
                 }
+            }
+        }
 
-                PrivateTypeLeak { location, leaked } => {
-                    let diagnostic = Diagnostic {
-                        title: "Private type used in public interface".to_string(),
-                        label: "".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    let mut printer = Printer::new();
-
-                    // TODO: be more precise.
-                    // - is being returned by this public function
-                    // - is taken as an argument by this public function
-                    // - is taken as an argument by this public enum constructor
-                    // etc
-                    writeln!(
-                        buffer,
-                        "The following type is private, but is being used by this public export.
+        CouldNotUnify {
+            location,
+            expected,
+            given,
+        } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0013,
+                title: "Type mismatch".to_string(),
+                label: "".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            let mut printer = Printer::new();
+
+            writeln!(
+                buffer,
+                "Expected type:
+
+{}
+
+Found type:
+
+{}",
+                printer.pretty_print(expected, 4),
+                printer.pretty_print(given, 4),
+            )
+            .unwrap();
+        }
+
+        IncorrectTypeArity {
+            location,
+            expected,
+            given,
+            ..
+        } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0012,
+                title: "Incorrect arity".to_string(),
+                label: format!("expected {} arguments, got {}", expected, given),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+        }
+
+        IncorrectArity {
+            labels,
+            location,
+            expected,
+            given,
+        } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0010,
+                title: "Incorrect arity".to_string(),
+                label: format!("expected {} arguments, got {}", expected, given),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            if !labels.is_empty() {
+                let labels = labels
+                    .iter()
+                    .map(|p| format!("  - {}", p))
+                    .sorted()
+                    .join("\n");
+                writeln!(
+                    buffer,
+                    "This call accepts these additional labelled arguments:\n\n{}\n",
+                    labels,
+                )
+                .unwrap();
+            }
+        }
+
+        UnnecessarySpreadOperator { location, arity } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0011,
+                title: "Unnecessary spread operator".to_string(),
+                label: format!(""),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+
+            writeln!(
+                buffer,
+                "This record has {} fields and you have already assigned variables to all of them.",
+                arity
+            )
+            .unwrap();
+        }
+
+        UnknownType {
+            location,
+            name,
+            types,
+        } => {
+            let mut types = types.clone();
+            let diagnostic = Diagnostic {
+                code: Code::E0003,
+                title: "Unknown type".to_string(),
+                label: did_you_mean(name, &mut types, ""),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "The type `{}` is not defined or imported in this module.",
+                name
+            )
+            .unwrap();
+        }
+
+        UnknownVariable {
+            location,
+            variables,
+            name,
+        } => {
+            let mut variables = variables.clone();
+            let diagnostic = Diagnostic {
+                code: Code::E0002,
+                title: "Unknown variable".to_string(),
+                label: did_you_mean(name, &mut variables, ""),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(buffer, "The name `{}` is not in scope here.", name).unwrap();
+        }
+
+        PrivateTypeLeak { location, leaked } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0019,
+                title: "Private type used in public interface".to_string(),
+                label: "".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            let mut printer = Printer::new();
+
+            // TODO: be more precise.
+            // - is being returned by this public function
+            // - is taken as an argument by this public function
+            // - is taken as an argument by this public enum constructor
+            // etc
+            writeln!(
+                buffer,
+                "The following type is private, but is being used by this public export.
 
 {}
 
 Private types can only be used within the module that defines them.",
-                        printer.pretty_print(leaked, 4),
-                    )
-                    .unwrap();
-                }
+                printer.pretty_print(leaked, 4),
+            )
+            .unwrap();
+        }
 
-                UnknownModule {
-                    location,
-                    name,
-                    imported_modules,
-                } => {
-                    let mut imported_modules = imported_modules.clone();
-                    let diagnostic = Diagnostic {
-                        title: "Unknown module".to_string(),
-                        label: did_you_mean(name, &mut imported_modules, ""),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "No module has been imported with the name `{}`.",
-                        name
-                    )
-                    .unwrap();
-                }
+        UnknownModule {
+            location,
+            name,
+            imported_modules,
+        } => {
+            let mut imported_modules = imported_modules.clone();
+            let diagnostic = Diagnostic {
+                code: Code::E0004,
+                title: "Unknown module".to_string(),
+                label: did_you_mean(name, &mut imported_modules, ""),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "No module has been imported with the name `{}`.",
+                name
+            )
+            .unwrap();
+        }
 
-                UnknownModuleType {
-                    location,
-                    name,
-                    module_name,
-                    type_constructors,
-                } => {
-                    let mut type_constructors = type_constructors.clone();
-                    let diagnostic = Diagnostic {
-                        title: "Unknown module type".to_string(),
-                        label: did_you_mean(name, &mut type_constructors, ""),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "The module `{}` does not have a `{}` type.",
-                        module_name.join("/"),
-                        name
-                    )
-                    .unwrap();
-                }
+        UnknownModuleType {
+            location,
+            name,
+            module_name,
+            type_constructors,
+        } => {
+            let mut type_constructors = type_constructors.clone();
+            let diagnostic = Diagnostic {
+                code: Code::E0005,
+                title: "Unknown module type".to_string(),
+                label: did_you_mean(name, &mut type_constructors, ""),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "The module `{}` does not have a `{}` type.",
+                module_name.join("/"),
+                name
+            )
+            .unwrap();
+        }
 
-                UnknownModuleValue {
-                    location,
-                    name,
-                    module_name,
-                    value_constructors,
-                } => {
-                    let mut value_constructors = value_constructors.clone();
-                    let diagnostic = Diagnostic {
-                        title: "Unknown module field".to_string(),
-                        label: did_you_mean(name, &mut value_constructors, ""),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "The module `{}` does not have a `{}` field.",
-                        module_name.join("/"),
-                        name
-                    )
-                    .unwrap();
-                }
+        UnknownModuleValue {
+            location,
+            name,
+            module_name,
+            value_constructors,
+        } => {
+            let mut value_constructors = value_constructors.clone();
+            let diagnostic = Diagnostic {
+                code: Code::E0006,
+                title: "Unknown module field".to_string(),
+                label: did_you_mean(name, &mut value_constructors, ""),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "The module `{}` does not have a `{}` field.",
+                module_name.join("/"),
+                name
+            )
+            .unwrap();
+        }
 
-                UnknownModuleField {
-                    location,
-                    name,
-                    module_name,
-                    type_constructors,
-                    value_constructors,
-                } => {
-                    let mut options: Vec<String> = type_constructors
-                        .iter()
-                        .chain(value_constructors.iter())
-                        .map(|s| s.to_string())
-                        .collect();
-                    let diagnostic = Diagnostic {
-                        title: "Unknown module field".to_string(),
-                        label: did_you_mean(name, &mut options, ""),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "The module `{}` does not have a `{}` field.",
-                        module_name.join("/"),
-                        name
-                    )
-                    .unwrap();
-                }
+        UnknownModuleField {
+            location,
+            name,
+            module_name,
+            type_constructors,
+            value_constructors,
+        } => {
+            let mut options: Vec<String> = type_constructors
+                .iter()
+                .chain(value_constructors.iter())
+                .map(|s| s.to_string())
+                .collect();
+            let diagnostic = Diagnostic {
+                code: Code::E0007,
+                title: "Unknown module field".to_string(),
+                label: did_you_mean(name, &mut options, ""),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "The module `{}` does not have a `{}` field.",
+                module_name.join("/"),
+                name
+            )
+            .unwrap();
+        }
 
-                IncorrectNumClausePatterns {
-                    location,
-                    expected,
-                    given,
-                } => {
-                    let diagnostic = Diagnostic {
-                        title: "Incorrect number of patterns".to_string(),
-                        label: format!("expected {} patterns, got {}", expected, given),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "This case expression has {} subjects, but this pattern matches {}.
+        IncorrectNumClausePatterns {
+            location,
+            expected,
+            given,
+        } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0022,
+                title: "Incorrect number of patterns".to_string(),
+                label: format!("expected {} patterns, got {}", expected, given),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "This case expression has {} subjects, but this pattern matches {}.
 Each clause must have a pattern for every subject value.",
-                        expected, given
-                    )
-                    .unwrap();
-                }
+                expected, given
+            )
+            .unwrap();
+        }
 
-                NonLocalClauseGuardVariable { location, name } => {
-                    let diagnostic = Diagnostic {
-                        title: "Invalid guard variable".to_string(),
-                        label: "is not locally defined".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "Variables used in guards must be either defined in the function, or be an
+        NonLocalClauseGuardVariable { location, name } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0023,
+                title: "Invalid guard variable".to_string(),
+                label: "is not locally defined".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "Variables used in guards must be either defined in the function, or be an
 argument to the function. The variable `{}` is not defined locally.",
-                        name
-                    )
-                    .unwrap();
-                }
+                name
+            )
+            .unwrap();
+        }
 
-                ExtraVarInAlternativePattern { location, name } => {
-                    let diagnostic = Diagnostic {
-                        title: "Extra alternative pattern variable".to_string(),
-                        label: "has not been previously defined".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "All alternative patterns must define the same variables as the initial
+        ExtraVarInAlternativePattern { location, name } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0024,
+                title: "Extra alternative pattern variable".to_string(),
+                label: "has not been previously defined".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "All alternative patterns must define the same variables as the initial
 pattern. This variable `{}` has not been previously defined.",
-                        name
-                    )
-                    .unwrap();
-                }
-
-                DuplicateVarInPattern { location, name } => {
-                    let diagnostic = Diagnostic {
-                        title: "Duplicate variable in pattern".to_string(),
-                        label: "has already been used".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
+                name
+            )
+            .unwrap();
+        }
 
-                    writeln!(
-                        buffer,
-                        "Variables can only be used once per pattern. This variable {} appears multiple times.
+        DuplicateVarInPattern { location, name } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0025,
+                title: "Duplicate variable in pattern".to_string(),
+                label: "has already been used".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+
+            writeln!(
+        buffer,
+        "Variables can only be used once per pattern. This variable {} appears multiple times.
 If you used the same variable twice deliberately in order to check for equality
 please use a guard clause instead e.g. (x, y) if x == y -> ...",
-                        name
-                    )
-                    .unwrap();
-                }
+        name
+    )
+            .unwrap();
+        }
 
-                OutOfBoundsTupleIndex {
-                    location, size: 0, ..
-                } => {
-                    let diagnostic = Diagnostic {
-                        title: "Out of bounds tuple index".to_string(),
-                        label: "this index is too large".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "This tuple has no elements so it cannot be indexed at all!"
-                    )
-                    .unwrap();
-                }
+        OutOfBoundsTupleIndex {
+            location, size: 0, ..
+        } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0026,
+                title: "Out of bounds tuple index".to_string(),
+                label: "this index is too large".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "This tuple has no elements so it cannot be indexed at all!"
+            )
+            .unwrap();
+        }
 
-                OutOfBoundsTupleIndex {
-                    location,
-                    index,
-                    size,
-                } => {
-                    let diagnostic = Diagnostic {
-                        title: "Out of bounds tuple index".to_string(),
-                        label: "this index is too large".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "The index being accessed for this tuple is {}, but this tuple has
+        OutOfBoundsTupleIndex {
+            location,
+            index,
+            size,
+        } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0026,
+                title: "Out of bounds tuple index".to_string(),
+                label: "this index is too large".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "The index being accessed for this tuple is {}, but this tuple has
 {} elements so the highest valid index is {}.",
-                        index,
-                        size,
-                        size - 1,
-                    )
-                    .unwrap();
-                }
-
-                NotATuple { location, given } => {
-                    let diagnostic = Diagnostic {
-                        title: "Type mismatch".to_string(),
-                        label: "is not a tuple".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    let mut printer = Printer::new();
+                index,
+                size,
+                size - 1,
+            )
+            .unwrap();
+        }
 
-                    writeln!(
-                        buffer,
-                        "To index into this value it needs to be a tuple, however it has this type:
+        NotATuple { location, given } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0027,
+                title: "Type mismatch".to_string(),
+                label: "is not a tuple".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            let mut printer = Printer::new();
+
+            writeln!(
+                buffer,
+                "To index into this value it needs to be a tuple, however it has this type:
 
 {}",
-                        printer.pretty_print(given, 4),
-                    )
-                    .unwrap();
-                }
-
-                NotATupleUnbound { location } => {
-                    let diagnostic = Diagnostic {
-                        title: "Type mismatch".to_string(),
-                        label: "what type is this?".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
+                printer.pretty_print(given, 4),
+            )
+            .unwrap();
+        }
 
-                    writeln!(
-                        buffer,
-                        "To index into a tuple we need to know it size, but we don't know anything
+        NotATupleUnbound { location } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0028,
+                title: "Type mismatch".to_string(),
+                label: "what type is this?".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+
+            writeln!(
+                buffer,
+                "To index into a tuple we need to know it size, but we don't know anything
 about this type yet. Please add some type annotations so we can continue.",
-                    )
-                    .unwrap();
-                }
-
-                RecordAccessUnknownType { location } => {
-                    let diagnostic = Diagnostic {
-                        title: "Unknown type for record access".to_string(),
-                        label: "I don't know what type this is".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
+            )
+            .unwrap();
+        }
 
-                    writeln!(
-                        buffer,
-                        "In order to access a record field we need to know what type it is, but
+        RecordAccessUnknownType { location } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0029,
+                title: "Unknown type for record access".to_string(),
+                label: "I don't know what type this is".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+
+            writeln!(
+                buffer,
+                "In order to access a record field we need to know what type it is, but
 I can't tell the type here. Try adding type annotations to your function
 and try again.
 ",
-                    )
-                    .unwrap();
-                }
+            )
+            .unwrap();
+        }
 
-                ConflictingBinaryTypeOptions { location, name, .. } => {
-                    let diagnostic = Diagnostic {
-                        title: "Duplicate bit string type option".to_string(),
-                        label: "given here".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(buffer, "This segment already has the type {}", name).unwrap();
-                }
+        ConflictingBinaryTypeOptions { location, name, .. } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0031,
+                title: "Duplicate bit string type option".to_string(),
+                label: "given here".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(buffer, "This segment already has the type {}", name).unwrap();
+        }
 
-                ConflictingBinarySignednessOptions { location, name, .. } => {
-                    let diagnostic = Diagnostic {
-                        title: "Duplicate bit string signedness".to_string(),
-                        label: "redefined here".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(buffer, "This segment already has a signedness of {}", name).unwrap();
-                }
+        ConflictingBinarySignednessOptions { location, name, .. } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0032,
+                title: "Duplicate bit string signedness".to_string(),
+                label: "redefined here".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(buffer, "This segment already has a signedness of {}", name).unwrap();
+        }
 
-                ConflictingBinaryEndiannessOptions { location, name, .. } => {
-                    let diagnostic = Diagnostic {
-                        title: "Duplicate bit string endianness".to_string(),
-                        label: "redefined here".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(buffer, "This segment already has an endianness of {}", name).unwrap();
-                }
+        ConflictingBinaryEndiannessOptions { location, name, .. } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0033,
+                title: "Duplicate bit string endianness".to_string(),
+                label: "redefined here".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(buffer, "This segment already has an endianness of {}", name).unwrap();
+        }
 
-                ConflictingBinarySizeOptions { location, .. } => {
-                    let diagnostic = Diagnostic {
-                        title: "Duplicate bit string size".to_string(),
-                        label: "redefined here".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(buffer, "This segment already has a size",).unwrap();
-                }
+        ConflictingBinarySizeOptions { location, .. } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0034,
+                title: "Duplicate bit string size".to_string(),
+                label: "redefined here".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(buffer, "This segment already has a size",).unwrap();
+        }
 
-                ConflictingBinaryUnitOptions { location, .. } => {
-                    let diagnostic = Diagnostic {
-                        title: "Duplicate bit string unit".to_string(),
-                        label: "redefined here".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(buffer, "This segment already has a unit",).unwrap();
-                }
+        ConflictingBinaryUnitOptions { location, .. } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0035,
+                title: "Duplicate bit string unit".to_string(),
+                label: "redefined here".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(buffer, "This segment already has a unit",).unwrap();
+        }
 
-                BinaryTypeDoesNotAllowUnit { location, typ, .. } => {
-                    let diagnostic = Diagnostic {
-                        title: "Unit cannot be specified for given type".to_string(),
-                        label: "".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "No unit specifier must be given for the types utf8, utf16, and utf32.
+        BinaryTypeDoesNotAllowUnit { location, typ, .. } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0036,
+                title: "Unit cannot be specified for given type".to_string(),
+                label: "".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "No unit specifier must be given for the types utf8, utf16, and utf32.
 This segment has a type of {}.",
-                        typ
-                    )
-                    .unwrap();
-                }
+                typ
+            )
+            .unwrap();
+        }
 
-                BinarySegmentMustHaveSize { location, .. } => {
-                    let diagnostic = Diagnostic {
-                        title: "Bit string segment without required size".to_string(),
-                        label: "specified here".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "Bit string segments without a size are only allowed
+        BinarySegmentMustHaveSize { location, .. } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0037,
+                title: "Bit string segment without required size".to_string(),
+                label: "specified here".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "Bit string segments without a size are only allowed
 at the end of a bin pattern",
-                    )
-                    .unwrap();
-                }
+            )
+            .unwrap();
+        }
 
-                InvalidBinarySegmentOption { label, location } => {
-                    let diagnostic = Diagnostic {
-                        title: "Invalid bit string segment option".to_string(),
-                        label: "specified here".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-                    writeln!(
-                        buffer,
-                        "{} is not a valid option for a bit string segment.
+        InvalidBinarySegmentOption { label, location } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0038,
+                title: "Invalid bit string segment option".to_string(),
+                label: "specified here".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+            writeln!(
+                buffer,
+                "{} is not a valid option for a bit string segment.
 Valid options are: binary, int, float, bit_string,
 utf8, utf16, utf32, utf8_codepoint, utf16_codepoint, utf32_codepoint,
 signed, unsigned, big, little, native, size, unit",
-                        label
-                    )
-                    .unwrap();
-                }
-
-                RecordUpdateInvalidConstructor { location } => {
-                    let diagnostic = Diagnostic {
-                        title: "Invalid record constructor".to_string(),
-                        label: "This is not a record constructor".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-
-                    writeln!(
-                        buffer,
-                        "You are attempting to update a record using a value that is not a record constructor",
-                    )
-                    .unwrap();
-                }
-
-                UnexpectedTypeHole { location } => {
-                    let diagnostic = Diagnostic {
-                        title: "Unexpected type hole".to_string(),
-                        label: "".to_string(),
-                        file: path.to_str().unwrap().to_string(),
-                        src: src.to_string(),
-                        location: location.clone(),
-                    };
-                    write(buffer, diagnostic, Severity::Error);
-
-                    writeln!(
-                        buffer,
-                        "We need to know the exact type here so type holes are not permitted.",
-                    )
-                    .unwrap();
-                }
-            },
-
-            Error::Parse { path, src, error } => {
-                use lalrpop_util::ParseError::*;
-
-                match error {
-                    UnrecognizedToken {
-                        token: (start, _, end),
-                        expected,
-                    } => {
-                        let diagnostic = Diagnostic {
-                            title: "Syntax error".to_string(),
-                            label: "Unexpected token".to_string(),
-                            file: path.to_str().unwrap().to_string(),
-                            src: src.to_string(),
-                            location: crate::ast::SrcSpan {
-                                start: *start,
-                                end: *end,
-                            },
-                        };
-                        write(buffer, diagnostic, Severity::Error);
-                        writeln!(buffer, "Expected one of {}", expected.join(", "))
-                            .expect("error pretty buffer write");
-                    }
-
-                    UnrecognizedEOF { .. } => {
-                        let diagnostic = Diagnostic {
-                            title: "Syntax error".to_string(),
-                            label: "Unexpected end of file".to_string(),
-                            file: path.to_str().unwrap().to_string(),
-                            src: src.to_string(),
-                            location: crate::ast::SrcSpan {
-                                start: src.len() - 2,
-                                end: src.len() - 1,
-                            },
-                        };
-                        write(buffer, diagnostic, Severity::Error);
-                    }
-
-                    InvalidToken { location } => {
-                        let diagnostic = Diagnostic {
-                            title: "Syntax error".to_string(),
-                            label: "Unknown token".to_string(),
-                            file: path.to_str().unwrap().to_string(),
-                            src: src.to_string(),
-                            location: crate::ast::SrcSpan {
-                                start: *location,
-                                end: *location + 1,
-                            },
-                        };
-                        write(buffer, diagnostic, Severity::Error);
-                        writeln!(
-                            buffer,
-                            "I don't know what this character means. Is it a typo?"
-                        )
-                        .expect("error pretty buffer write");
-                    }
-
-                    ExtraToken { .. } => unimplemented!(),
-
-                    User { error } => {
-                        use crate::parser::Error;
-                        match error {
-                            Error::TooManyHolesInCapture { location, count } => {
-                                let diagnostic = Diagnostic {
-                                    title: "Invalid capture".to_string(),
-                                    label: "".to_string(),
-                                    file: path.to_str().unwrap().to_string(),
-                                    src: src.to_string(),
-                                    location: location.clone(),
-                                };
-                                write(buffer, diagnostic, Severity::Error);
-                                let chars: String = (97..(97 + count))
-                                    .map(|x| x as u8 as char)
-                                    .map(|c| c.to_string())
-                                    .intersperse(", ".to_string())
-                                    .collect();
-                                writeln!(
-                                    buffer,
-                                    "The function capture syntax can only be used with a single _ argument,
-but this one uses {}. Rewrite this using the fn({}) {{ ... }} syntax.",
-                                    count, chars
-                                )
-                                .expect("error pretty buffer write");
-                            }
-                        }
-                    }
-                }
-            }
-
-            Error::ImportCycle { modules } => {
-                crate::diagnostic::write_title(buffer, "Import cycle");
-                writeln!(
-                    buffer,
-                    "The import statements for these modules form a cycle:\n"
-                )
-                .unwrap();
-                import_cycle(buffer, modules.as_ref());
+                label
+            )
+            .unwrap();
+        }
 
-                writeln!(
-                    buffer,
-                    "Gleam doesn't support import cycles like these, please break the
-cycle to continue."
-                )
-                .unwrap();
-            }
+        RecordUpdateInvalidConstructor { location } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0030,
+                title: "Invalid record constructor".to_string(),
+                label: "This is not a record constructor".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+
+            writeln!(
+        buffer,
+        "You are attempting to update a record using a value that is not a record constructor",
+    )
+            .unwrap();
+        }
 
-            Error::PackageCycle { packages } => {
-                crate::diagnostic::write_title(buffer, "Dependency cycle");
-                writeln!(
-                    buffer,
-                    "The dependencies for these packages form a cycle:\n"
-                )
-                .unwrap();
-                import_cycle(buffer, packages.as_ref());
-                writeln!(
-                    buffer,
-                    "Gleam doesn't support dependency cycles like these, please break the
-cycle to continue."
-                )
-                .unwrap();
-            }
+        UnexpectedTypeHole { location } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0039,
+                title: "Unexpected type hole".to_string(),
+                label: "".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
+
+            writeln!(
+                buffer,
+                "We need to know the exact type here so type holes are not permitted.",
+            )
+            .unwrap();
+        }
 
-            Error::UnknownImport {
-                module,
-                import,
-                location,
-                path,
-                src,
-                modules,
-            } => {
-                let mut modules = modules.clone();
-                let diagnostic = Diagnostic {
-                    title: "Unknown import".to_string(),
-                    label: did_you_mean(import, &mut modules, ""),
-                    file: path.to_str().unwrap().to_string(),
-                    src: src.to_string(),
-                    location: location.clone(),
-                };
-                write(buffer, diagnostic, Severity::Error);
-                writeln!(
-                    buffer,
-                    "The module `{}` is trying to import the module `{}`,
-but it cannot be found.",
-                    module, import
-                )
-                .expect("error pretty buffer write");
-            }
+        InexhaustiveCaseExpression { location, missing } => {
+            let diagnostic = Diagnostic {
+                code: Code::E0040,
+                title: "Inexhaustive patterns".to_string(),
+                label: "".to_string(),
+                file: path.to_str().unwrap().to_string(),
+                src: src.to_string(),
+                location: location.clone(),
+            };
+            write(buffer, diagnostic, Severity::Error);
 
-            Error::StandardIO { action, err } => {
-                let err = match err {
-                    Some(e) => format!(
-                        "\nThe error message from the stdio library was:\n\n    {}\n",
-                        std_io_error_kind_text(e)
-                    ),
-                    None => "".to_string(),
-                };
-                let diagnostic = ProjectErrorDiagnostic {
-                    title: "Standard IO failure".to_string(),
-                    label: format!(
-                        "An error occurred while trying to {}:
+            writeln!(
+                buffer,
+                "This case expression does not have a pattern for all possible values.
+If it is run on one of the values without a pattern then it will crash.
 
-{}",
-                        action.text(),
-                        err,
-                    ),
-                };
-                write_project(buffer, diagnostic);
-            }
-            Error::Format { problem_files } => {
-                let files: Vec<_> = problem_files
-                    .iter()
-                    .flat_map(|formatted| formatted.source.to_str())
-                    .map(|p| format!("  - {}", p))
-                    .sorted()
-                    .collect();
-                let mut label = files.iter().join("\n");
-                label.push('\n');
-                let diagnostic = ProjectErrorDiagnostic {
-                    title: "These files have not been formatted".to_string(),
-                    label,
-                };
+The missing patterns are:
 
-                write_project(buffer, diagnostic);
-            }
+    {}",
+                missing.join("\n    ")
+            )
+            .unwrap();
         }
     }
-
-    pub fn pretty_print(&self) {
-        let buffer_writer = cli::stderr_buffer_writer();
-        let mut buffer = buffer_writer.buffer();
-        self.pretty(&mut buffer);
-        buffer_writer.print(&buffer).unwrap();
-    }
 }
 
 fn std_io_error_kind_text(kind: &std::io::ErrorKind) -> String {
@@ -1447,10 +1851,18 @@ fn std_io_error_kind_text(kind: &std::io::ErrorKind) -> String {
     }
 }
 
-fn import_cycle(buffer: &mut Buffer, modules: &[String]) {
+fn import_cycle(buffer: &mut DiagnosticBuffer, modules: &[String]) {
     use std::io::Write;
     use termcolor::{Color, ColorSpec, WriteColor};
 
+    // JSON messages are plain text, so the cycle is written on one line
+    // rather than drawn
+    if let DiagnosticBuffer::Json(_) = buffer {
+        let cycle: Vec<_> = modules.iter().chain(modules.first()).cloned().collect();
+        writeln!(buffer, "{}\n", cycle.join(" -> ")).unwrap();
+        return;
+    }
+
     writeln!(
         buffer,
         "
@@ -1,5 +1,6 @@
 use crate::{
-    build::{self, project_root::ProjectRoot, Origin},
+    build::{self, project_root::ProjectRoot, Origin, Profile},
+    diagnostic::DiagnosticFormat,
     error::Error,
     fs::OutputFile,
 };
@@ -15,11 +16,12 @@ struct EunitFile {
 
 pub fn command(root_string: String) -> Result<(), Error> {
     let root_path = PathBuf::from(root_string);
-    let root = ProjectRoot::new(root_path.clone());
+    let mut root = ProjectRoot::new(root_path.clone());
+    root.profile = Profile::Test;
     let config = root.root_config()?;
 
-    // Build project
-    let packages = build::main(config, root_path)?;
+    // Build project with its tests
+    let packages = build::main(config, root_path, Profile::Test, DiagnosticFormat::Human)?;
 
     crate::cli::print_running("eunit");
 
@@ -74,7 +76,7 @@ pub fn command(root_string: String) -> Result<(), Error> {
     let mut command = Command::new("escript");
     command.arg(root.build_path().join("eunit_runner.erl"));
 
-    let ebin_paths: String = crate::fs::read_dir(root.default_build_lib_path())?
+    let ebin_paths: String = crate::fs::read_dir(root.build_lib_path())?
         .filter_map(Result::ok)
         .map(|entry| entry.path().join("ebin").as_path().display().to_string())
         .join(",");
@@ -0,0 +1,958 @@
+use crate::{diagnostic::Code, error::Error};
+
+/// Longer explanations for each of the error and warning codes shown in
+/// diagnostic titles. These are printed by the `gleam explain` command.
+///
+const EXPLANATIONS: &[(Code, &str)] = &[
+    (
+        Code::E0001,
+        r#"Unknown labels
+
+A function or record constructor was called with a labelled argument that it
+does not accept.
+
+    fn greet(name name: String) { name }
+
+    greet(nane: "Lucy")
+
+Check the spelling of the label, or remove it if the argument should be given
+positionally."#,
+    ),
+    (
+        Code::E0002,
+        r#"Unknown variable
+
+A variable was used that has not been defined in the current scope.
+
+    fn main() {
+      let x = 1
+      y
+    }
+
+Variables must be defined with `let`, a function argument, or a pattern before
+they can be used. Values from other modules need to be qualified with the
+module name, or imported unqualified."#,
+    ),
+    (
+        Code::E0003,
+        r#"Unknown type
+
+A type was referenced that has not been defined or imported.
+
+    fn main(x: Nuber) { x }
+
+Check the spelling of the type. Types from other modules need to be
+qualified with the module name, as in `option.Option(a)`, or imported
+unqualified."#,
+    ),
+    (
+        Code::E0004,
+        r#"Unknown module
+
+A module was referenced that has not been imported.
+
+    fn main() {
+      list.reverse([1, 2, 3])
+    }
+
+Import the module at the top of the file before using it:
+
+    import gleam/list"#,
+    ),
+    (
+        Code::E0005,
+        r#"Unknown module type
+
+A type was referenced from a module that does not define a public type with
+that name.
+
+    import gleam/option
+
+    fn main(x: option.Maybe(Int)) { x }
+
+Check the spelling of the type, and that it is public in the other module."#,
+    ),
+    (
+        Code::E0006,
+        r#"Unknown module value
+
+A value was referenced from a module that does not define a public value with
+that name.
+
+    import gleam/list
+
+    fn main() { list.reversed([1, 2]) }
+
+Check the spelling of the value, and that it is defined with `pub` in the
+other module."#,
+    ),
+    (
+        Code::E0007,
+        r#"Unknown module field
+
+An unqualified import names something that the imported module does not
+define publicly, neither as a value nor as a type.
+
+    import gleam/list.{reversed}
+
+Check the spelling, and that the item is public in the imported module."#,
+    ),
+    (
+        Code::E0008,
+        r#"Not a function
+
+A value that is not a function has been called as if it were one.
+
+    fn main() {
+      let x = 1
+      x(2)
+    }
+
+Only functions can be called. Check that the right value is being used."#,
+    ),
+    (
+        Code::E0009,
+        r#"Unknown record field
+
+A field was accessed on a record that does not have a field with that label.
+
+    type Person { Person(name: String) }
+
+    fn main(person: Person) { person.age }
+
+Record fields can only be accessed if every constructor of the type has a
+field with that label, and the type is not opaque when used from another
+module."#,
+    ),
+    (
+        Code::E0010,
+        r#"Incorrect arity
+
+A function or record constructor was called with the wrong number of
+arguments.
+
+    fn add(a, b) { a + b }
+
+    fn main() { add(1) }
+
+Supply one argument for each parameter. To partially apply a function use a
+capture, as in `add(1, _)`."#,
+    ),
+    (
+        Code::E0011,
+        r#"Unnecessary spread operator
+
+A record pattern used `..` to ignore the remaining fields, but every field has
+already been matched.
+
+    type Point { Point(x: Int, y: Int) }
+
+    fn main(p) {
+      let Point(x, y, ..) = p
+      x + y
+    }
+
+Remove the `..`, it has no effect here."#,
+    ),
+    (
+        Code::E0012,
+        r#"Incorrect number of type parameters
+
+A type was used with the wrong number of type parameters.
+
+    fn main(x: List(Int, Int)) { x }
+
+Supply exactly the number of type parameters that the type was defined with,
+as in `List(Int)`."#,
+    ),
+    (
+        Code::E0013,
+        r#"Type mismatch
+
+An expression was found to have a different type than the one required where
+it is used.
+
+    fn main() {
+      1 + 1.0
+    }
+
+Here `+` works on Ints, but a Float was given. Use `+.` for Floats, or
+convert the values so that both sides have the same type."#,
+    ),
+    (
+        Code::E0014,
+        r#"Recursive type
+
+A value was found whose type would have to contain itself, which is not
+possible.
+
+    fn main(f) { f(f) }
+
+This usually means a function has been given itself as an argument, or a
+value has been placed inside itself. Check the types of the values involved."#,
+    ),
+    (
+        Code::E0015,
+        r#"Duplicate definition
+
+A function, constant, or other value has been defined more than once in the
+same module.
+
+    fn main() { 1 }
+    fn main() { 2 }
+
+Each name in a module can only be defined once. Rename or remove one of the
+definitions."#,
+    ),
+    (
+        Code::E0016,
+        r#"Duplicate type definition
+
+A type has been defined more than once in the same module.
+
+    type Box { Box(Int) }
+    type Box { Box(Float) }
+
+Rename or remove one of the definitions."#,
+    ),
+    (
+        Code::E0017,
+        r#"Duplicate argument
+
+A function has been defined with two arguments of the same name.
+
+    fn add(x, x) { x + x }
+
+Each argument needs its own name."#,
+    ),
+    (
+        Code::E0018,
+        r#"Duplicate label
+
+The same label was given more than once, either in a function call or in a
+record definition.
+
+    type Person { Person(name: String, name: String) }
+
+Each label may only be used once."#,
+    ),
+    (
+        Code::E0019,
+        r#"Private type used in public interface
+
+A public function, constant, or type refers to a private type. Other modules
+would be able to use the value but not refer to its type.
+
+    type Secret { Secret }
+
+    pub fn main() { Secret }
+
+Either make the type public with `pub type`, or make the value private."#,
+    ),
+    (
+        Code::E0020,
+        r#"Unexpected labelled argument
+
+A labelled argument was given to a function that does not accept that label.
+
+    fn add(a, b) { a + b }
+
+    fn main() { add(1, b: 2) }
+
+Labelled arguments can only be used if the function was defined with labels,
+as in `fn add(a a, b b)`."#,
+    ),
+    (
+        Code::E0021,
+        r#"Unexpected positional argument
+
+A positional argument was given after a labelled argument.
+
+    fn main() { greet(greeting: "Hello", "Lucy") }
+
+All positional arguments must come before any labelled arguments."#,
+    ),
+    (
+        Code::E0022,
+        r#"Incorrect number of patterns
+
+A case clause has a different number of patterns than there are subjects in
+the case expression.
+
+    case x, y {
+      1 -> True
+      _, _ -> False
+    }
+
+Give one pattern for each subject, separated by commas."#,
+    ),
+    (
+        Code::E0023,
+        r#"Invalid guard variable
+
+A case clause guard used a variable that is not in scope. Guards may only use
+variables defined in the clause patterns or in the enclosing function.
+
+    case x {
+      _ if y -> 1
+      _ -> 2
+    }
+
+Check the spelling of the variable, or bind it in the pattern."#,
+    ),
+    (
+        Code::E0024,
+        r#"Extra alternative pattern variable
+
+An alternative pattern defines a variable that is not defined by the other
+alternatives in the same clause.
+
+    case x {
+      [a] | [a, b] -> b
+      _ -> 0
+    }
+
+Each alternative pattern must define the same set of variables, so that they
+are available no matter which alternative matched."#,
+    ),
+    (
+        Code::E0025,
+        r#"Duplicate variable in pattern
+
+A pattern binds the same variable name more than once.
+
+    let tuple(x, x) = tuple(1, 2)
+
+Each variable in a pattern must have a unique name. To check two values are
+equal, bind them to different names and compare them in a guard."#,
+    ),
+    (
+        Code::E0026,
+        r#"Out of bounds tuple index
+
+A tuple element was accessed with an index that is too large for the tuple.
+
+    let t = tuple(1, 2)
+    t.2
+
+Tuple indexes start at 0, so the last element of a tuple of size 2 has the
+index 1."#,
+    ),
+    (
+        Code::E0027,
+        r#"Not a tuple
+
+A tuple index was used on a value that is not a tuple.
+
+    let x = [1, 2]
+    x.0
+
+Tuple indexes can only be used on tuples. Lists can be accessed with
+functions from the `gleam/list` module."#,
+    ),
+    (
+        Code::E0028,
+        r#"Type of tuple not known
+
+A tuple index was used on a value whose type has not been inferred yet.
+
+    fn first(x) { x.0 }
+
+Add a type annotation so the compiler knows the value is a tuple:
+
+    fn first(x: tuple(a, b)) { x.0 }"#,
+    ),
+    (
+        Code::E0029,
+        r#"Type of record not known
+
+A record field was accessed on a value whose type has not been inferred yet.
+
+    fn name(person) { person.name }
+
+Add a type annotation so the compiler knows which record type is being used:
+
+    fn name(person: Person) { person.name }"#,
+    ),
+    (
+        Code::E0030,
+        r#"Invalid record update
+
+The record update syntax was used with something that is not a record
+constructor.
+
+    fn main(person) { main(..person) }
+
+Record updates must use the constructor of the record being updated, as in
+`Person(..person, name: "Lucy")`."#,
+    ),
+    (
+        Code::E0031,
+        r#"Conflicting bit string type options
+
+A bit string segment was given more than one type option.
+
+    <<x:int-float>>
+
+Each segment can only have one type, such as `int`, `float`, `binary`,
+`bit_string`, `utf8`, `utf16` or `utf32`."#,
+    ),
+    (
+        Code::E0032,
+        r#"Conflicting bit string signedness options
+
+A bit string segment was given both the `signed` and `unsigned` options, or
+one of them more than once.
+
+    <<x:signed-unsigned>>
+
+Choose one of the options."#,
+    ),
+    (
+        Code::E0033,
+        r#"Conflicting bit string endianness options
+
+A bit string segment was given more than one endianness option.
+
+    <<x:big-little>>
+
+Choose one of `big`, `little` or `native`."#,
+    ),
+    (
+        Code::E0034,
+        r#"Conflicting bit string size options
+
+A bit string segment was given the `size` option more than once.
+
+    <<x:size(8)-size(16)>>
+
+Each segment may only have one size."#,
+    ),
+    (
+        Code::E0035,
+        r#"Conflicting bit string unit options
+
+A bit string segment was given the `unit` option more than once.
+
+    <<x:size(1)-unit(8)-unit(16)>>
+
+Each segment may only have one unit."#,
+    ),
+    (
+        Code::E0036,
+        r#"Bit string type does not allow unit
+
+The `unit` option was used with a segment type that has a fixed unit.
+
+    <<x:utf8-unit(8)>>
+
+The `unit` option cannot be used with the `utf8`, `utf16` and `utf32`
+types."#,
+    ),
+    (
+        Code::E0037,
+        r#"Bit string segment without required size
+
+A `binary` or `bit_string` segment without a size was used in a bit string
+pattern before the final segment.
+
+    case bits {
+      <<head:binary, rest:binary>> -> head
+    }
+
+The size of such a segment can only be inferred when it is the last segment
+of the pattern, so give the earlier segments a size, as in
+`<<head:binary-size(4), rest:binary>>`."#,
+    ),
+    (
+        Code::E0038,
+        r#"Invalid bit string segment option
+
+A bit string segment was given an option that does not exist.
+
+    <<x:integer>>
+
+Valid options are: binary, int, float, bit_string, utf8, utf16, utf32,
+utf8_codepoint, utf16_codepoint, utf32_codepoint, signed, unsigned, big,
+little, native, size, and unit."#,
+    ),
+    (
+        Code::E0039,
+        r#"Unexpected type hole
+
+A type hole `_` was used in a type annotation where a type hole is not
+permitted, such as in a custom type definition or the signature of an
+external function.
+
+    type Box { Box(_) }
+
+Type holes can only be used where the compiler is able to infer the missing
+type, for example in `let` annotations and function signatures."#,
+    ),
+    (
+        Code::E0040,
+        r#"Inexhaustive case expression
+
+A case expression does not have a clause for every possible value of its
+subjects, so the program could crash at runtime.
+
+    type Colour { Red Green Blue }
+
+    fn name(colour) {
+      case colour {
+        Red -> "red"
+        Green -> "green"
+      }
+    }
+
+Add clauses for the missing patterns listed in the error, or a catch-all
+clause using the `_` pattern."#,
+    ),
+    (
+        Code::E0041,
+        r#"Syntax error: unexpected token
+
+The parser found a token that is not valid at this position.
+
+    fn main() { let = 1 }
+
+The error lists the tokens that would have been valid. Check for missing
+names, brackets, or commas near the highlighted token."#,
+    ),
+    (
+        Code::E0042,
+        r#"Syntax error: unexpected end of file
+
+The file ended before the parser finished reading a definition.
+
+    fn main() {
+      1
+
+Check that every opening bracket has a matching closing bracket."#,
+    ),
+    (
+        Code::E0043,
+        r#"Syntax error: unknown token
+
+The file contains a character that has no meaning in Gleam.
+
+    fn main() { 1 ~ 2 }
+
+Check for typos, or characters that were copied by mistake."#,
+    ),
+    (
+        Code::E0044,
+        r#"Invalid capture
+
+A function capture used more than one `_` placeholder.
+
+    add3(_, 1, _)
+
+The capture syntax can only create functions of one argument. Write an
+anonymous function instead:
+
+    fn(a, c) { add3(a, 1, c) }"#,
+    ),
+    (
+        Code::E0045,
+        r#"Unknown import
+
+A module imports another module that could not be found in the project or
+its dependencies.
+
+    import gleam/lsit
+
+Check the spelling of the module name, and that the package providing it is
+listed as a dependency."#,
+    ),
+    (
+        Code::E0046,
+        r#"Duplicate module
+
+Two source files define a module with the same name. This can happen when a
+module of the same name exists in both the `src` and `test` directories.
+
+Rename one of the modules so that every module name is unique."#,
+    ),
+    (
+        Code::E0047,
+        r#"Application module importing test module
+
+A module in the `src` directory imports a module from the `test` directory.
+
+Test modules are not included in production builds, so application modules
+cannot depend on them. Move the imported module to the `src` directory."#,
+    ),
+    (
+        Code::E0048,
+        r#"Import cycle
+
+The import statements of some modules form a cycle, for example module `a`
+imports `b` which imports `a`.
+
+Gleam modules must form a hierarchy without cycles. Move the shared code into
+a new module that both modules import."#,
+    ),
+    (
+        Code::E0049,
+        r#"Dependency cycle
+
+The dependencies of some packages form a cycle, for example package `a`
+depends on `b` which depends on `a`.
+
+Packages must form a hierarchy without cycles. Remove one of the
+dependencies."#,
+    ),
+    (
+        Code::E0050,
+        r#"File IO failure
+
+Gleam was unable to read, write, create, or delete a file or directory.
+
+The error includes the path and the message from the operating system. Check
+that the path exists and that you have permission to access it."#,
+    ),
+    (
+        Code::E0051,
+        r#"Standard IO failure
+
+Gleam was unable to read from standard input, for example when running
+`gleam format --stdin`.
+
+Check that the input is valid UTF-8 text."#,
+    ),
+    (
+        Code::E0052,
+        r#"Unformatted files
+
+`gleam format --check` found files that are not formatted.
+
+Run `gleam format` on these files to format them."#,
+    ),
+    (
+        Code::E0053,
+        r#"Hex API failure
+
+A request to the Hex package manager API failed.
+
+Check your internet connection and credentials, and try again."#,
+    ),
+    (
+        Code::E0054,
+        r#"Failure creating tar archive
+
+A file could not be added to a tar archive, for example when publishing
+documentation.
+
+Check that the file exists and is readable."#,
+    ),
+    (
+        Code::E0055,
+        r#"Failure finishing tar archive
+
+A tar archive could not be written, for example when publishing
+documentation.
+
+Check that there is enough disc space and try again."#,
+    ),
+    (
+        Code::E0056,
+        r#"Gzip compression failure
+
+An archive could not be compressed with gzip.
+
+Check that there is enough disc space and try again."#,
+    ),
+    (
+        Code::E0057,
+        r#"Shell command failure
+
+An external program run by Gleam, such as `erl` or `escript`, could not be
+started or failed.
+
+Check that Erlang is installed and that its programs are in your PATH."#,
+    ),
+    (
+        Code::E0058,
+        r#"Unknown error code
+
+`gleam explain` was given a code that does not belong to any error or
+warning.
+
+    gleam explain E9999
+
+Error codes start with `E` and warning codes start with `W`, followed by four
+digits. The code is shown in brackets in the title of each diagnostic."#,
+    ),
+    (
+        Code::E0059,
+        r#"Warnings treated as errors
+
+The build produced warnings and was configured to treat warnings as errors,
+either with the `--warnings-as-errors` flag or with this setting in
+`gleam.toml`:
+
+    warnings_as_errors = true
+
+Fix the warnings shown above the error to continue. This mode is useful in
+continuous integration to stop warnings from being merged."#,
+    ),
+    (
+        Code::E0060,
+        r#"Language server failure
+
+The language server started by `gleam lsp` could not communicate with the
+editor. This can happen if the editor sends a message the server does not
+understand, or if the connection is closed unexpectedly.
+
+The language server is normally started by the editor rather than by hand.
+Check the logs of the editor's Gleam plugin for more information."#,
+    ),
+    (
+        Code::E0061,
+        r#"Rename not possible
+
+A function, constant, record constructor, or type could not be renamed, either
+with `gleam rename` or with the rename action of an editor.
+
+    gleam rename my_app/user.create new_user
+
+Renaming is refused when:
+
+- The name is not defined in the given module.
+- The new name is not valid for the kind of thing being renamed. Functions and
+  constants are written in snake_case, while types and record constructors
+  are written in UpperCamelCase.
+- The name is defined in a dependency rather than in the project.
+- The project contains errors, so not every reference could be found.
+- The new name is already in use somewhere the old name is referred to, so
+  renaming would change which definition that code refers to."#,
+    ),
+    (
+        Code::E0062,
+        r#"Package download failed
+
+A dependency could not be downloaded from Hex by `gleam deps download`.
+
+Downloading fails when:
+
+- There is no release of the package with that version on Hex.
+- The downloaded tarball could not be read, or its contents do not match the
+  checksum it contains. This can happen if the download was interrupted, so
+  try again.
+- The downloaded tarball does not match the checksum recorded for it in
+  `manifest.toml`. The release may have been republished, or the download
+  tampered with. Once you trust the new release run `gleam deps update` with
+  the name of the package to record its checksum."#,
+    ),
+    (
+        Code::E0063,
+        r#"Dependency resolution failed
+
+A version could not be chosen for each of the dependencies of the project.
+The dependencies are listed in `gleam.toml` with a requirement on their
+version:
+
+    [dependencies]
+    gleam_stdlib = "~> 0.10"
+    gleam_http = ">= 1.0.0 and < 2.0.0"
+
+A version of each package is chosen to meet every requirement on it, both
+from `gleam.toml` and from the other packages that depend on it.
+
+Resolution fails when:
+
+- A requirement is not valid. `~> 1.2` allows any version from 1.2.0 up to
+  but not including 2.0.0, and `~> 1.2.3` allows any version from 1.2.3 up
+  to but not including 1.3.0. Versions can also be compared with `==`, `!=`,
+  `>`, `>=`, `<`, and `<=`, and requirements combined with `and` and `or`.
+- A required package does not exist on Hex.
+- A package given to `gleam deps update` is not one of the dependencies of
+  the project, either directly or through another package.
+- No version of a package meets all of the requirements on it. The error
+  lists each requirement and where it comes from, so one of them can be
+  relaxed or another version of the package requiring it chosen."#,
+    ),
+    (
+        Code::W0001,
+        r#"Todo found
+
+The `todo` keyword was used. It allows a program to type check while some of
+the code has not been written yet.
+
+    fn main() { todo }
+
+The program will crash if the `todo` is reached at runtime. Replace it with
+an implementation before running the program."#,
+    ),
+    (
+        Code::W0002,
+        r#"Unused result value
+
+An expression returning a `Result` was not assigned or returned, so any error
+it contains is silently ignored.
+
+    fn main() {
+      save(document)
+      Nil
+    }
+
+Handle the result with a case expression, or assign it to `_` to explicitly
+discard it:
+
+    let _ = save(document)"#,
+    ),
+    (
+        Code::W0003,
+        r#"Fieldless record update
+
+A record update did not change any fields.
+
+    Person(..person)
+
+The update has no effect. Either supply the fields to change or use the
+original record."#,
+    ),
+    (
+        Code::W0004,
+        r#"Redundant record update
+
+A record update changed every field of the record.
+
+    Person(..person, name: "Lucy", age: 31)
+
+The spread syntax is unnecessary, construct a new record instead:
+
+    Person(name: "Lucy", age: 31)"#,
+    ),
+    (
+        Code::W0005,
+        r#"Unused private type
+
+A private type is defined but never used in its module.
+
+    type Unused { Unused }
+
+It can be safely removed."#,
+    ),
+    (
+        Code::W0006,
+        r#"Unused private constructor
+
+A constructor of a private type is never used in its module.
+
+    type Colour { Red Green }
+
+    fn main() { Red }
+
+It can be safely removed."#,
+    ),
+    (
+        Code::W0007,
+        r#"Unreachable case clause
+
+A case clause can never match because the clauses before it already match
+every value it would match.
+
+    case x {
+      _ -> 1
+      0 -> 2
+    }
+
+Remove the clause, or move it before the clauses that make it unreachable."#,
+    ),
+    (
+        Code::W0008,
+        r#"Unused variable
+
+A variable or function argument is defined but never used.
+
+    fn main(x) {
+      let y = 1
+      2
+    }
+
+Remove it, or prefix the name with an underscore, as in `_y`, to show that it
+is intentionally unused."#,
+    ),
+    (
+        Code::W0009,
+        r#"Unused private function
+
+A private function is defined but never used in its module.
+
+    fn helper() { 1 }
+
+It can be safely removed. Unused private functions are not included in the
+compiled Erlang."#,
+    ),
+    (
+        Code::W0010,
+        r#"Unused private constant
+
+A private module constant is defined but never used in its module.
+
+    const limit = 10
+
+It can be safely removed."#,
+    ),
+    (
+        Code::W0011,
+        r#"Unused imported module
+
+A module is imported but none of its values or types are used.
+
+    import gleam/list
+
+It can be safely removed."#,
+    ),
+    (
+        Code::W0012,
+        r#"Unused imported value
+
+A value imported unqualified is never used.
+
+    import gleam/list.{reverse}
+
+Remove it from the list of unqualified imports."#,
+    ),
+];
+
+pub fn command(code: String) -> Result<(), Error> {
+    let code = code.to_uppercase();
+    match code.parse().ok().and_then(explanation) {
+        Some(text) => {
+            println!("{}: {}", code, text);
+            Ok(())
+        }
+        None => Err(Error::UnknownErrorCode { code }),
+    }
+}
+
+pub fn explanation(code: Code) -> Option<&'static str> {
+    EXPLANATIONS
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, text)| *text)
+}
+
+#[test]
+fn explanations_test() {
+    use strum::IntoEnumIterator;
+
+    // Every code has exactly one explanation
+    for code in Code::iter() {
+        let count = EXPLANATIONS.iter().filter(|(c, _)| *c == code).count();
+        assert_eq!((code, count), (code, 1));
+    }
+
+    // Codes are written in upper or lower case
+    assert_eq!(explanation(Code::E0001), EXPLANATIONS[0].1.into());
+    assert!(command("w0001".to_string()).is_ok());
+    assert_eq!(
+        command("E9999".to_string()),
+        Err(Error::UnknownErrorCode {
+            code: "E9999".to_string()
+        })
+    );
+}
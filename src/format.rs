@@ -15,13 +15,98 @@ const INDENT: isize = 2;
 
 pub fn pretty(src: &str) -> Result<String, crate::parser::LalrpopError> {
     let (stripped_src, comments) = crate::parser::strip_extra(src.as_ref());
-    let ast = crate::grammar::ModuleParser::new()
-        .parse(&stripped_src)
-        .map_err(|e| e.map_token(|crate::grammar::Token(a, b)| (a, b.to_string())))?;
+    let ast = parse(&stripped_src)?;
     let mut formatter = Formatter::with_comments(&comments);
     Ok(pretty_module(&ast, &mut formatter))
 }
 
+/// Format only the top level statements that overlap a range of the source,
+/// leaving the rest of the module untouched. The location of each statement
+/// in the source, including any comments before it, is returned with its
+/// formatted replacement.
+///
+pub fn pretty_statements(
+    src: &str,
+    range: &SrcSpan,
+) -> Result<Vec<(SrcSpan, String)>, crate::parser::LalrpopError> {
+    let (stripped_src, comments) = crate::parser::strip_extra(src.as_ref());
+    let ast = parse(&stripped_src)?;
+
+    // Statements end where the next one starts, less the whitespace and
+    // blanked out comments between them
+    let starts: Vec<_> = ast
+        .statements
+        .iter()
+        .map(|statement| statement_start(&stripped_src, statement))
+        .collect();
+    let mut previous_end = 0;
+    let mut formatted = vec![];
+    for (i, statement) in ast.statements.iter().enumerate() {
+        let next = starts
+            .get(i + 1)
+            .copied()
+            .unwrap_or_else(|| stripped_src.len());
+        let end = starts[i] + stripped_src[starts[i]..next].trim_end().len();
+        let first_comment = comments
+            .comments
+            .iter()
+            .chain(comments.doc_comments.iter())
+            .map(|comment| comment.start)
+            .filter(|start| previous_end <= *start && *start < starts[i])
+            .min();
+        let span = SrcSpan {
+            start: first_comment.unwrap_or(starts[i]),
+            end,
+        };
+        previous_end = end;
+        if span.end < range.start || range.end < span.start {
+            continue;
+        }
+
+        let mut formatter = Formatter {
+            comments: comments_within(&comments.comments, &span),
+            doc_comments: comments_within(&comments.doc_comments, &span),
+            module_comments: &[],
+            empty_lines: {
+                let lines = &comments.empty_lines;
+                let from = lines.iter().take_while(|line| **line < span.start).count();
+                let to = lines.iter().take_while(|line| **line < span.end).count();
+                &lines[from..to]
+            },
+        };
+        let leading = formatter.pop_comments(statement.location().start);
+        let document = match statement {
+            Statement::Import { .. } => formatter.statement(statement),
+            _ => formatter.documented_statement(statement),
+        };
+        let document = commented(document, leading).append(formatter.remaining_comments());
+        formatted.push((span, format(80, document)));
+    }
+    Ok(formatted)
+}
+
+fn parse(stripped_src: &str) -> Result<UntypedModule, crate::parser::LalrpopError> {
+    crate::grammar::ModuleParser::new()
+        .parse(stripped_src)
+        .map_err(|e| e.map_token(|crate::grammar::Token(a, b)| (a, b.to_string())))
+}
+
+/// The location of an import starts after the `import` keyword.
+///
+fn statement_start(stripped_src: &str, statement: &UntypedStatement) -> usize {
+    let start = statement.location().start;
+    match statement {
+        Statement::Import { .. } => stripped_src[..start].rfind("import").unwrap_or(start),
+        _ => start,
+    }
+}
+
+fn comments_within<'a>(comments: &'a [Comment<'a>], span: &SrcSpan) -> &'a [Comment<'a>] {
+    let from = comments.iter().take_while(|c| c.start < span.start).count();
+    let to = comments.iter().take_while(|c| c.start < span.end).count();
+    &comments[from..to]
+}
+
 #[derive(Debug, Clone)]
 pub struct Formatter<'a> {
     comments: &'a [Comment<'a>],
@@ -110,17 +195,6 @@ impl<'a> Formatter<'a> {
             nil()
         };
 
-        let doc_comments = concat(
-            self.doc_comments
-                .into_iter()
-                .map(|comment| line().append("///").append(comment.content)),
-        );
-        let comments = concat(
-            self.comments
-                .into_iter()
-                .map(|comment| line().append("//").append(comment.content)),
-        );
-
         let module_comments = if !self.module_comments.is_empty() {
             let comments = self
                 .module_comments
@@ -135,11 +209,25 @@ impl<'a> Formatter<'a> {
             .append(imports)
             .append(sep)
             .append(declarations)
-            .append(doc_comments)
-            .append(comments)
+            .append(self.remaining_comments())
             .append(line())
     }
 
+    // Comments that were not printed with any statement
+    fn remaining_comments(&self) -> Document {
+        let doc_comments = concat(
+            self.doc_comments
+                .iter()
+                .map(|comment| line().append("///").append(comment.content)),
+        );
+        let comments = concat(
+            self.comments
+                .iter()
+                .map(|comment| line().append("//").append(comment.content)),
+        );
+        doc_comments.append(comments)
+    }
+
     fn statement(&mut self, statement: &UntypedStatement) -> Document {
         match statement {
             Statement::Fn {
@@ -968,7 +1056,7 @@ impl<'a> Formatter<'a> {
         }
     }
 
-    fn clause(&mut self, clause: &UntypedClause, index: usize) -> Document {
+    pub fn clause(&mut self, clause: &UntypedClause, index: usize) -> Document {
         let space_before = self.pop_empty_lines(clause.location.start);
         let after_position = clause.location.end;
         let clause_doc = concat(
@@ -0,0 +1,604 @@
+mod code_action;
+pub mod compiler;
+mod completion;
+mod definition;
+mod formatting;
+mod hover;
+pub mod references;
+mod symbols;
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    ast::SrcSpan,
+    build::{project_root::ProjectRoot, Profile},
+    diagnostic::{DiagnosticBuffer, JsonDiagnostic},
+    error::{Error, GleamExpect},
+};
+use compiler::Compiled;
+use lsp_server::{Connection, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+        PublishDiagnostics, ShowMessage,
+    },
+    request::{
+        CodeActionRequest, Completion, DocumentSymbolRequest, Formatting, GotoDefinition,
+        HoverRequest, RangeFormatting, References, Rename, WorkspaceSymbol,
+    },
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, CompletionOptions, CompletionParams, CompletionResponse,
+    Diagnostic, DiagnosticSeverity, DocumentFormattingParams, DocumentRangeFormattingParams,
+    DocumentSymbolParams, DocumentSymbolResponse, GotoDefinitionParams, GotoDefinitionResponse,
+    Hover, HoverContents, HoverParams, HoverProviderCapability, InitializeParams, Location,
+    MarkupContent, MarkupKind, MessageType, NumberOrString, OneOf, Position,
+    PublishDiagnosticsParams, Range, ReferenceParams, RenameParams, ServerCapabilities,
+    ShowMessageParams, SymbolInformation, TextDocumentPositionParams, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextEdit, Url, WorkspaceEdit, WorkspaceSymbolParams,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+// The error code for a request that was valid but could not be carried out,
+// which lsp-server does not define.
+const REQUEST_FAILED: i32 = -32803;
+
+pub fn main() -> Result<(), Error> {
+    tracing::info!("Starting language server");
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = serde_json::to_value(server_capabilities())
+        .gleam_expect("Language server capabilities serialization");
+    let params = connection
+        .initialize(capabilities)
+        .map_err(|e| Error::LanguageServer(e.to_string()))?;
+    let params: InitializeParams = parse(params)?;
+
+    // Without a root the current directory is presumed to be the project.
+    // The path must be absolute so that file URLs can be created from it.
+    let root = match params.root_uri.and_then(|uri| uri.to_file_path().ok()) {
+        Some(root) => root,
+        None => std::env::current_dir().map_err(|e| Error::LanguageServer(e.to_string()))?,
+    };
+
+    // The test profile is used so that the test modules and their
+    // dev-dependencies are available, falling back to the dependencies of
+    // the default profile if the project has not been built for it yet
+    let mut root = ProjectRoot::new(root);
+    root.profile = Profile::Test;
+    LanguageServer::new(connection, root).run()?;
+
+    io_threads
+        .join()
+        .map_err(|e| Error::LanguageServer(e.to_string()))?;
+    tracing::info!("Language server stopped");
+    Ok(())
+}
+
+fn server_capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::Full)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![".".to_string()]),
+            ..Default::default()
+        }),
+        references_provider: Some(OneOf::Left(true)),
+        rename_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        workspace_symbol_provider: Some(OneOf::Left(true)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        document_range_formatting_provider: Some(OneOf::Left(true)),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        ..Default::default()
+    }
+}
+
+pub struct LanguageServer {
+    connection: Connection,
+    root: ProjectRoot,
+    /// The contents of the documents open in the editor, which may differ
+    /// from the files on disc.
+    documents: HashMap<PathBuf, String>,
+    compiled: Compiled,
+    /// Files that diagnostics have been published for, so they can be
+    /// cleared once the problems have been fixed.
+    published: HashSet<PathBuf>,
+}
+
+impl LanguageServer {
+    pub fn new(connection: Connection, root: ProjectRoot) -> Self {
+        Self {
+            connection,
+            root,
+            documents: HashMap::new(),
+            compiled: Compiled::default(),
+            published: HashSet::new(),
+        }
+    }
+
+    pub fn run(mut self) -> Result<(), Error> {
+        self.compile_and_publish()?;
+
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    let shutdown = self
+                        .connection
+                        .handle_shutdown(&request)
+                        .map_err(|e| Error::LanguageServer(e.to_string()))?;
+                    if shutdown {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request);
+                    self.send(Message::Response(response))?;
+                }
+
+                Message::Notification(notification) => {
+                    self.handle_notification(notification)?;
+                }
+
+                Message::Response(_) => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> Response {
+        use lsp_types::request::Request as _;
+
+        let Request { id, method, params } = request;
+        let result = match method.as_str() {
+            HoverRequest::METHOD => {
+                serde_json::from_value(params).map(|params| to_value(self.hover(params)))
+            }
+
+            Completion::METHOD => {
+                serde_json::from_value(params).map(|params| to_value(self.completion(params)))
+            }
+
+            GotoDefinition::METHOD => {
+                serde_json::from_value(params).map(|params| to_value(self.definition(params)))
+            }
+
+            References::METHOD => {
+                serde_json::from_value(params).map(|params| to_value(self.references(params)))
+            }
+
+            DocumentSymbolRequest::METHOD => {
+                serde_json::from_value(params).map(|params| to_value(self.document_symbols(params)))
+            }
+
+            WorkspaceSymbol::METHOD => serde_json::from_value(params)
+                .map(|params| to_value(self.workspace_symbols(params))),
+
+            Formatting::METHOD => {
+                serde_json::from_value(params).map(|params| to_value(self.format(params)))
+            }
+
+            RangeFormatting::METHOD => {
+                serde_json::from_value(params).map(|params| to_value(self.format_range(params)))
+            }
+
+            CodeActionRequest::METHOD => {
+                serde_json::from_value(params).map(|params| to_value(self.code_actions(params)))
+            }
+
+            // A rename that is not possible is reported to the user
+            Rename::METHOD => match serde_json::from_value(params) {
+                Ok(params) => match self.rename(params) {
+                    Ok(edit) => Ok(to_value(edit)),
+                    Err(error) => {
+                        return Response::new_err(id, REQUEST_FAILED, error_message(&error))
+                    }
+                },
+                Err(error) => Err(error),
+            },
+
+            _ => {
+                tracing::info!(method = ?method, "Unsupported request");
+                return Response::new_err(
+                    id,
+                    lsp_server::ErrorCode::MethodNotFound as i32,
+                    format!("Unsupported request {}", method),
+                );
+            }
+        };
+
+        match result {
+            Ok(value) => Response::new_ok(id, value),
+            Err(error) => Response::new_err(
+                id,
+                lsp_server::ErrorCode::InvalidParams as i32,
+                error.to_string(),
+            ),
+        }
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let (module, byte_index) = self.module_position(&params.text_document_position_params)?;
+        let contents = hover::hover(&self.compiled, module, byte_index)?;
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: contents,
+            }),
+            range: None,
+        })
+    }
+
+    fn completion(&self, params: CompletionParams) -> Option<CompletionResponse> {
+        let (module, byte_index) = self.module_position(&params.text_document_position)?;
+        let items = completion::completion(&self.compiled, module, byte_index);
+        Some(CompletionResponse::Array(items))
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let (module, byte_index) = self.module_position(&params.text_document_position_params)?;
+        let (module, span) = definition::definition(&self.compiled, module, byte_index)?;
+        Some(GotoDefinitionResponse::Scalar(Location {
+            uri: Url::from_file_path(&module.path).ok()?,
+            range: range(&module.code, &span),
+        }))
+    }
+
+    fn references(&self, params: ReferenceParams) -> Option<Vec<Location>> {
+        let (module, byte_index) = self.module_position(&params.text_document_position)?;
+        let index = references::Index::new(&self.compiled);
+        let entry = index.get(index.definition_at(&module.name, byte_index)?)?;
+        let declaration = Some(&entry.declaration).filter(|_| params.context.include_declaration);
+        let locations = declaration
+            .into_iter()
+            .chain(entry.references.iter())
+            .filter_map(|reference| {
+                let module = self.compiled.modules.get(&reference.module)?;
+                Some(Location {
+                    uri: Url::from_file_path(&module.path).ok()?,
+                    range: range(&module.code, &reference.location),
+                })
+            })
+            .collect();
+        Some(locations)
+    }
+
+    fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>, Error> {
+        let index = references::Index::new(&self.compiled);
+        let definition = self
+            .module_position(&params.text_document_position)
+            .and_then(|(module, byte_index)| index.definition_at(&module.name, byte_index));
+        let definition = match definition {
+            Some(definition) => definition,
+            None => return Ok(None),
+        };
+
+        let edits = index.rename(&self.compiled, &[definition], &params.new_name)?;
+        let changes = edits
+            .into_iter()
+            .filter_map(|(module, spans)| {
+                let module = self.compiled.modules.get(&module)?;
+                let edits = spans
+                    .iter()
+                    .map(|span| TextEdit {
+                        range: range(&module.code, span),
+                        new_text: params.new_name.clone(),
+                    })
+                    .collect();
+                Some((Url::from_file_path(&module.path).ok()?, edits))
+            })
+            .collect();
+        Ok(Some(WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        }))
+    }
+
+    fn document_symbols(&self, params: DocumentSymbolParams) -> Option<DocumentSymbolResponse> {
+        let path = params.text_document.uri.to_file_path().ok()?;
+        let module = self.compiled.module_for_path(&path)?;
+        Some(DocumentSymbolResponse::Nested(symbols::document_symbols(
+            module,
+        )))
+    }
+
+    fn workspace_symbols(&self, params: WorkspaceSymbolParams) -> Vec<SymbolInformation> {
+        symbols::workspace_symbols(&self.compiled, &params.query)
+    }
+
+    fn format(&self, params: DocumentFormattingParams) -> Option<Vec<TextEdit>> {
+        let code = self.document_code(&params.text_document.uri)?;
+        formatting::format_document(code)
+    }
+
+    fn format_range(&self, params: DocumentRangeFormattingParams) -> Option<Vec<TextEdit>> {
+        let code = self.document_code(&params.text_document.uri)?;
+        let selection = SrcSpan {
+            start: byte_index(code, params.range.start),
+            end: byte_index(code, params.range.end),
+        };
+        formatting::format_range(code, &selection)
+    }
+
+    fn code_actions(&self, params: CodeActionParams) -> Option<Vec<CodeActionOrCommand>> {
+        let path = params.text_document.uri.to_file_path().ok()?;
+        let module = self.compiled.module_for_path(&path)?;
+        let selection = SrcSpan {
+            start: byte_index(&module.code, params.range.start),
+            end: byte_index(&module.code, params.range.end),
+        };
+        let missing = code_action::missing_clauses(&self.compiled, module, &selection)?;
+        let insert_at = SrcSpan {
+            start: missing.insert_at,
+            end: missing.insert_at,
+        };
+        let edit = TextEdit {
+            range: range(&module.code, &insert_at),
+            new_text: missing.code,
+        };
+        let mut changes = HashMap::new();
+        let _ = changes.insert(params.text_document.uri, vec![edit]);
+        Some(vec![CodeActionOrCommand::CodeAction(CodeAction {
+            title: "Add missing case clauses".to_string(),
+            kind: Some(CodeActionKind::QUICKFIX),
+            edit: Some(WorkspaceEdit {
+                changes: Some(changes),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })])
+    }
+
+    /// The contents of a document as open in the editor, or as last read
+    /// from disc.
+    ///
+    fn document_code(&self, uri: &Url) -> Option<&str> {
+        let path = uri.to_file_path().ok()?;
+        match self.documents.get(&path) {
+            Some(code) => Some(code),
+            None => Some(&self.compiled.module_for_path(&path)?.code),
+        }
+    }
+
+    /// The compiled module of a document and the byte index of a position
+    /// within it.
+    ///
+    fn module_position(
+        &self,
+        params: &TextDocumentPositionParams,
+    ) -> Option<(&compiler::Module, usize)> {
+        let path = params.text_document.uri.to_file_path().ok()?;
+        let module = self.compiled.module_for_path(&path)?;
+        let byte_index = byte_index(&module.code, params.position);
+        Some((module, byte_index))
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<(), Error> {
+        use lsp_types::notification::Notification as _;
+
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: lsp_types::DidOpenTextDocumentParams = parse(notification.params)?;
+                if let Ok(path) = params.text_document.uri.to_file_path() {
+                    let _ = self.documents.insert(path, params.text_document.text);
+                }
+            }
+
+            // The full text of the document is sent as changes are not
+            // synchronised incrementally
+            DidChangeTextDocument::METHOD => {
+                let params: lsp_types::DidChangeTextDocumentParams = parse(notification.params)?;
+                let text = params.content_changes.into_iter().last();
+                if let (Ok(path), Some(change)) = (params.text_document.uri.to_file_path(), text) {
+                    let _ = self.documents.insert(path, change.text);
+                }
+            }
+
+            DidSaveTextDocument::METHOD => (),
+
+            DidCloseTextDocument::METHOD => {
+                let params: lsp_types::DidCloseTextDocumentParams = parse(notification.params)?;
+                if let Ok(path) = params.text_document.uri.to_file_path() {
+                    let _ = self.documents.remove(&path);
+                }
+            }
+
+            _ => return Ok(()),
+        }
+
+        self.compile_and_publish()
+    }
+
+    fn compile_and_publish(&mut self) -> Result<(), Error> {
+        tracing::info!("Compiling project");
+        let mut compiled = match compiler::read_sources(&self.root, &self.documents) {
+            Ok((root_package, sources)) => compiler::analyse(&root_package, sources),
+            Err(error) => Compiled {
+                errors: vec![error],
+                ..Default::default()
+            },
+        };
+
+        // Keep the last type checked version of modules that now have errors
+        for (name, module) in compiled.modules.iter_mut() {
+            if module.ast.is_none() {
+                module.previous_ast = self
+                    .compiled
+                    .modules
+                    .remove(name)
+                    .and_then(|previous| previous.ast.or(previous.previous_ast));
+            }
+        }
+        self.compiled = compiled;
+
+        let (diagnostics, messages) = diagnostics(&self.compiled);
+
+        for message in messages {
+            self.notify::<ShowMessage>(ShowMessageParams {
+                typ: MessageType::Error,
+                message,
+            })?;
+        }
+
+        // Publish an empty list for files that no longer have any problems
+        let published = diagnostics.keys().cloned().collect();
+        let cleared: Vec<_> = self.published.difference(&published).cloned().collect();
+        let cleared = cleared.into_iter().map(|path| (path, vec![]));
+        for (path, diagnostics) in diagnostics.into_iter().chain(cleared) {
+            if let Ok(uri) = Url::from_file_path(&path) {
+                self.notify::<PublishDiagnostics>(PublishDiagnosticsParams {
+                    uri,
+                    diagnostics,
+                    version: None,
+                })?;
+            }
+        }
+        self.published = published;
+        Ok(())
+    }
+
+    fn notify<N: lsp_types::notification::Notification>(
+        &self,
+        params: N::Params,
+    ) -> Result<(), Error> {
+        let notification = Notification::new(N::METHOD.to_string(), params);
+        self.send(Message::Notification(notification))
+    }
+
+    fn send(&self, message: Message) -> Result<(), Error> {
+        self.connection
+            .sender
+            .send(message)
+            .map_err(|e| Error::LanguageServer(e.to_string()))
+    }
+}
+
+fn parse<P: serde::de::DeserializeOwned>(params: serde_json::Value) -> Result<P, Error> {
+    serde_json::from_value(params).map_err(|e| Error::LanguageServer(e.to_string()))
+}
+
+fn to_value(result: impl serde::Serialize) -> serde_json::Value {
+    serde_json::to_value(result).gleam_expect("Language server response serialization")
+}
+
+/// Convert the errors and warnings of a compiled project into LSP
+/// diagnostics for each file. Problems that do not belong to any file, such
+/// as an import cycle, are returned as messages to be shown to the user.
+///
+pub fn diagnostics(compiled: &Compiled) -> (HashMap<PathBuf, Vec<Diagnostic>>, Vec<String>) {
+    let mut buffer = DiagnosticBuffer::Json(vec![]);
+    for error in compiled.errors.iter() {
+        error.pretty(&mut buffer);
+    }
+    for warning in compiled.warnings.iter() {
+        warning.pretty(&mut buffer);
+    }
+
+    let mut diagnostics: HashMap<PathBuf, Vec<Diagnostic>> = HashMap::new();
+    let mut messages = vec![];
+    for json in buffer.into_json_diagnostics() {
+        let path = json.file.as_ref().map(PathBuf::from);
+        match (path, &json.span) {
+            (Some(path), Some(span)) => {
+                let src = compiled
+                    .module_for_path(&path)
+                    .map(|module| module.code.as_str())
+                    .unwrap_or("");
+                let span = SrcSpan {
+                    start: span.start,
+                    end: span.end,
+                };
+                let diagnostic = lsp_diagnostic(range(src, &span), json);
+                diagnostics.entry(path).or_default().push(diagnostic);
+            }
+
+            (Some(path), None) => {
+                let diagnostic = lsp_diagnostic(Range::default(), json);
+                diagnostics.entry(path).or_default().push(diagnostic);
+            }
+
+            (None, _) => messages.push(message_text(&json)),
+        }
+    }
+    (diagnostics, messages)
+}
+
+fn lsp_diagnostic(range: Range, json: JsonDiagnostic) -> Diagnostic {
+    let severity = match json.severity {
+        "warning" => DiagnosticSeverity::Warning,
+        _ => DiagnosticSeverity::Error,
+    };
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        code: Some(NumberOrString::String(json.code.to_string())),
+        source: Some("gleam".to_string()),
+        message: message_text(&json),
+        ..Default::default()
+    }
+}
+
+fn error_message(error: &Error) -> String {
+    let mut buffer = DiagnosticBuffer::Json(vec![]);
+    error.pretty(&mut buffer);
+    buffer
+        .into_json_diagnostics()
+        .iter()
+        .map(message_text)
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn message_text(json: &JsonDiagnostic) -> String {
+    let mut text = json.title.clone();
+    if let Some(label) = &json.label {
+        text.push_str(": ");
+        text.push_str(label);
+    }
+    if !json.message.is_empty() {
+        text.push_str("\n\n");
+        text.push_str(&json.message);
+    }
+    text
+}
+
+/// The LSP position of a byte index in the source. LSP columns are counted
+/// in UTF-16 code units.
+///
+pub fn position(src: &str, byte_index: usize) -> Position {
+    let byte_index = byte_index.min(src.len());
+    let before = &src[..byte_index];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: before[line_start..].encode_utf16().count() as u32,
+    }
+}
+
+pub fn range(src: &str, span: &SrcSpan) -> Range {
+    Range {
+        start: position(src, span.start),
+        end: position(src, span.end),
+    }
+}
+
+/// The byte index in the source of an LSP position, the inverse of
+/// `position`.
+///
+pub fn byte_index(src: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match src[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return src.len(),
+        }
+    }
+    let mut utf16_count = 0;
+    for (i, c) in src[line_start..].char_indices() {
+        if utf16_count >= position.character as usize || c == '\n' {
+            return line_start + i;
+        }
+        utf16_count += c.len_utf16();
+    }
+    src.len()
+}
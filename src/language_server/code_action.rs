@@ -0,0 +1,342 @@
+use super::compiler::{Compiled, Module};
+use crate::{
+    ast::{
+        CallArg, Clause, Located, Pattern, SrcSpan, Statement, TypedClause, TypedExpr, TypedModule,
+        TypedPattern, UntypedClause, UntypedExpr,
+    },
+    build::package_compiler,
+    error::Error,
+    format::Formatter,
+    pretty::format,
+    typ::{self, collapse_links, HasType, PatternConstructor, Type, ValueConstructorVariant},
+};
+
+/// The code of the clauses for each record constructor missing from a case
+/// expression, to be inserted after its last clause.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingClauses {
+    pub insert_at: usize,
+    pub code: String,
+}
+
+const CATCH_ALL: &str = "\n_ -> todo\n";
+
+/// Clauses with `todo` bodies for each record constructor not matched by an
+/// inexhaustive case expression overlapping the selection.
+///
+/// As the module cannot be type checked with inexhaustive cases the type of
+/// the subject is found by checking it again with a catch all clause added
+/// to each of them. Only the first error of a function is reported, so this
+/// is repeated until the module type checks.
+///
+pub fn missing_clauses(
+    compiled: &Compiled,
+    module: &Module,
+    selection: &SrcSpan,
+) -> Option<MissingClauses> {
+    let cases = inexhaustive_cases(compiled, module);
+    let case = cases
+        .iter()
+        .find(|case| case.start <= selection.end && selection.start <= case.end)?;
+
+    // The catch all clauses are inserted before the closing brace of each
+    // case, and their positions are kept in terms of the original code
+    let mut inserted = closing_braces(&cases)?;
+    let ast = loop {
+        inserted.sort_unstable();
+        let mut code = module.code.clone();
+        for closing in inserted.iter().rev() {
+            code.insert_str(*closing, CATCH_ALL);
+        }
+        let ast = package_compiler::parse_source(&code, &module.name, &module.path).ok()?;
+        match typ::infer_module(&mut 0, ast, &compiled.module_types, &mut vec![]) {
+            Ok(ast) => break ast,
+            Err(errors) => {
+                let closings = closing_braces(&inexhaustive_locations(&errors)?)?;
+                if closings.is_empty() {
+                    return None;
+                }
+                let closings: Vec<_> = closings
+                    .into_iter()
+                    .map(|closing| original_position(&inserted, closing))
+                    .collect();
+                inserted.extend(closings);
+            }
+        }
+    };
+    let case_start =
+        case.start + CATCH_ALL.len() * inserted.iter().filter(|at| **at <= case.start).count();
+
+    let (subjects, clauses) = match ast.find_node(case_start)? {
+        Located::Expression(TypedExpr::Case {
+            subjects, clauses, ..
+        }) => (subjects, clauses),
+        _ => return None,
+    };
+    // Only the case of a single value can be completed, and the catch all
+    // clause added above is not one of the user's clauses
+    let subject = match subjects.as_slice() {
+        [subject] => subject,
+        _ => return None,
+    };
+    let clauses = &clauses[..clauses.len() - 1];
+    let last = clauses.last()?;
+
+    let (type_module, type_name) = match collapse_links(subject.typ()).as_ref() {
+        Type::App { module, name, .. } => (module.clone(), name.clone()),
+        _ => return None,
+    };
+    let constructors = if type_module == ast.name {
+        local_constructors(&ast, &type_name)?
+    } else if type_module.is_empty() {
+        prelude_constructors(&type_name)?
+    } else {
+        let (_, interface) = compiled.module_types.get(&type_module.join("/"))?;
+        imported_constructors(interface, &type_name)?
+    };
+
+    let indent = line_indent(
+        &module.code,
+        original_position(&inserted, last.location.start),
+    );
+    let mut formatted = String::new();
+    for (name, labels) in constructors {
+        if clauses.iter().any(|clause| matches_all_of(clause, &name)) {
+            continue;
+        }
+        let (module, name) = constructor_name(&ast, &type_module, &name);
+        let clause = todo_clause(module, name, labels);
+        let clause = format(80, Formatter::new().clause(&clause, 0));
+        for line in clause.lines() {
+            formatted.push('\n');
+            if !line.is_empty() {
+                formatted.push_str(&indent);
+            }
+            formatted.push_str(line);
+        }
+    }
+    if formatted.is_empty() {
+        return None;
+    }
+    Some(MissingClauses {
+        insert_at: original_position(&inserted, last.location.end),
+        code: formatted,
+    })
+}
+
+fn inexhaustive_cases(compiled: &Compiled, module: &Module) -> Vec<SrcSpan> {
+    compiled
+        .errors
+        .iter()
+        .flat_map(|error| match error {
+            Error::Type { path, errors, .. } if *path == module.path => {
+                inexhaustive_locations(errors).unwrap_or_default()
+            }
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// The locations of the inexhaustive cases, or `None` if there are any other
+/// errors.
+///
+fn inexhaustive_locations(errors: &[typ::Error]) -> Option<Vec<SrcSpan>> {
+    errors
+        .iter()
+        .map(|error| match error {
+            typ::Error::InexhaustiveCaseExpression { location, .. } => Some(location.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The position in the original code of a position in the code with catch all
+/// clauses inserted at the sorted original positions `inserted`.
+///
+fn original_position(inserted: &[usize], position: usize) -> usize {
+    let before = inserted
+        .iter()
+        .enumerate()
+        .filter(|(index, at)| **at + CATCH_ALL.len() * index <= position)
+        .count();
+    position - CATCH_ALL.len() * before
+}
+
+fn closing_braces(cases: &[SrcSpan]) -> Option<Vec<usize>> {
+    cases.iter().map(|case| case.end.checked_sub(1)).collect()
+}
+
+/// The constructors of a type defined in the module being checked, which may
+/// be private and so missing from its interface.
+///
+fn local_constructors(
+    ast: &TypedModule,
+    type_name: &str,
+) -> Option<Vec<(String, Vec<Option<String>>)>> {
+    ast.statements.iter().find_map(|statement| match statement {
+        Statement::CustomType {
+            name, constructors, ..
+        } if name == type_name => Some(
+            constructors
+                .iter()
+                .map(|constructor| {
+                    let labels = constructor.args.iter().map(|(label, _, _)| label.clone());
+                    (constructor.name.clone(), labels.collect())
+                })
+                .collect(),
+        ),
+        _ => None,
+    })
+}
+
+/// The constructors of a type registered in the interface of another module,
+/// with the labels of their fields.
+///
+fn imported_constructors(
+    interface: &typ::Module,
+    type_name: &str,
+) -> Option<Vec<(String, Vec<Option<String>>)>> {
+    let constructors = interface.types_constructors.get(type_name)?;
+    let constructors = constructors.iter().map(|constructor| {
+        let mut labels = vec![None; constructor.arity];
+        if let Some(ValueConstructorVariant::Record {
+            field_map: Some(field_map),
+            ..
+        }) = interface
+            .values
+            .get(&constructor.name)
+            .map(|value| &value.variant)
+        {
+            for (label, index) in field_map.fields.iter() {
+                if let Some(slot) = labels.get_mut(*index) {
+                    *slot = Some(label.clone());
+                }
+            }
+        }
+        (constructor.name.clone(), labels)
+    });
+    Some(constructors.collect())
+}
+
+/// The constructors of a type defined in the prelude, none of which have
+/// labelled fields.
+///
+fn prelude_constructors(type_name: &str) -> Option<Vec<(String, Vec<Option<String>>)>> {
+    let constructors = typ::type_constructors(type_name)?
+        .into_iter()
+        .map(|constructor| (constructor.name, vec![None; constructor.arity]));
+    Some(constructors.collect())
+}
+
+/// Whether an unguarded clause matches every value built with a constructor.
+///
+fn matches_all_of(clause: &TypedClause, constructor: &str) -> bool {
+    fn matches(pattern: &TypedPattern, constructor: &str) -> bool {
+        match pattern {
+            Pattern::Let { pattern, .. } => matches(pattern, constructor),
+            Pattern::Constructor {
+                constructor: PatternConstructor::Record { name },
+                args,
+                ..
+            } => name == constructor && args.iter().all(|arg| irrefutable(&arg.value)),
+            _ => irrefutable(pattern),
+        }
+    }
+
+    fn irrefutable(pattern: &TypedPattern) -> bool {
+        match pattern {
+            Pattern::Var { .. } | Pattern::Discard { .. } => true,
+            Pattern::Let { pattern, .. } => irrefutable(pattern),
+            _ => false,
+        }
+    }
+
+    clause.guard.is_none()
+        && std::iter::once(&clause.pattern)
+            .chain(clause.alternative_patterns.iter())
+            .any(|patterns| patterns.first().map_or(false, |p| matches(p, constructor)))
+}
+
+/// The module alias and name to use for a constructor in the module being
+/// edited, preferring any unqualified import of it.
+///
+fn constructor_name(
+    ast: &TypedModule,
+    type_module: &[String],
+    name: &str,
+) -> (Option<String>, String) {
+    if type_module == ast.name.as_slice() {
+        return (None, name.to_string());
+    }
+    let import = ast.statements.iter().find_map(|statement| match statement {
+        Statement::Import {
+            module,
+            as_name,
+            unqualified,
+            ..
+        } if module.as_slice() == type_module => Some((as_name, unqualified)),
+        _ => None,
+    });
+    match import {
+        Some((as_name, unqualified)) => {
+            match unqualified.iter().find(|import| import.name == name) {
+                Some(import) => (
+                    None,
+                    import.as_name.clone().unwrap_or_else(|| name.to_string()),
+                ),
+                None => (
+                    as_name.clone().or_else(|| type_module.last().cloned()),
+                    name.to_string(),
+                ),
+            }
+        }
+        None => (type_module.last().cloned(), name.to_string()),
+    }
+}
+
+fn todo_clause(module: Option<String>, name: String, labels: Vec<Option<String>>) -> UntypedClause {
+    let args = labels
+        .into_iter()
+        .map(|label| CallArg {
+            location: SrcSpan::default(),
+            value: match &label {
+                Some(label) => Pattern::Var {
+                    location: SrcSpan::default(),
+                    name: label.clone(),
+                    typ: (),
+                },
+                None => Pattern::Discard {
+                    location: SrcSpan::default(),
+                    name: "_".to_string(),
+                },
+            },
+            label,
+        })
+        .collect();
+    Clause {
+        location: SrcSpan::default(),
+        pattern: vec![Pattern::Constructor {
+            location: SrcSpan::default(),
+            name,
+            args,
+            module,
+            constructor: (),
+            with_spread: false,
+        }],
+        alternative_patterns: vec![],
+        guard: None,
+        then: UntypedExpr::Todo {
+            location: SrcSpan::default(),
+            label: None,
+        },
+    }
+}
+
+fn line_indent(code: &str, byte_index: usize) -> String {
+    let line_start = code[..byte_index].rfind('\n').map_or(0, |i| i + 1);
+    code[line_start..byte_index]
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect()
+}
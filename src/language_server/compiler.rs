@@ -0,0 +1,262 @@
+use crate::{
+    ast::TypedModule,
+    build::{dep_tree, package_compiler, project_root::ProjectRoot, Origin, Profile},
+    error::{Error, GleamExpect},
+    typ,
+    warning::Warning,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Source {
+    pub package: String,
+    pub origin: Origin,
+    pub name: String,
+    pub path: PathBuf,
+    pub code: String,
+}
+
+#[derive(Debug)]
+pub struct Module {
+    pub name: String,
+    pub package: String,
+    pub origin: Origin,
+    pub path: PathBuf,
+    pub code: String,
+    /// None if the module could not be parsed or type checked
+    pub ast: Option<TypedModule>,
+    /// The last version of the module that could be type checked, kept while
+    /// the module contains errors so that completions can still be offered
+    /// as code is being written.
+    pub previous_ast: Option<TypedModule>,
+}
+
+#[derive(Debug, Default)]
+pub struct Compiled {
+    pub root_package: String,
+    pub modules: HashMap<String, Module>,
+    pub module_types: HashMap<String, (Origin, typ::Module)>,
+    pub errors: Vec<Error>,
+    pub warnings: Vec<Warning>,
+}
+
+impl Compiled {
+    pub fn module_for_path(&self, path: &PathBuf) -> Option<&Module> {
+        self.modules.values().find(|module| &module.path == path)
+    }
+
+    /// The source code of every module, so that the project can be analysed
+    /// again with some of them changed.
+    ///
+    pub fn sources(&self) -> Vec<Source> {
+        self.modules
+            .values()
+            .map(|module| Source {
+                package: module.package.clone(),
+                origin: module.origin,
+                name: module.name.clone(),
+                path: module.path.clone(),
+                code: module.code.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Read the source code of the top level package and its dependencies,
+/// using the contents of any documents open in the editor in place of the
+/// files on disc.
+///
+pub fn read_sources(
+    root: &ProjectRoot,
+    documents: &HashMap<PathBuf, String>,
+) -> Result<(String, Vec<Source>), Error> {
+    let root_name = root.root_config()?.name;
+    let mut sources = vec![];
+
+    let mut read = |package: &str, origin: Origin, package_path: PathBuf| -> Result<(), Error> {
+        for path in crate::fs::gleam_files(&package_path) {
+            let code = match documents.get(&path) {
+                Some(code) => code.clone(),
+                None => crate::fs::read(&path)?,
+            };
+            sources.push(Source {
+                package: package.to_string(),
+                name: package_compiler::module_name(&package_path, &path),
+                origin,
+                path,
+                code,
+            });
+        }
+        Ok(())
+    };
+
+    read(&root_name, Origin::Src, root.src_path())?;
+    read(&root_name, Origin::Test, root.test_path())?;
+
+    // Dependencies are only available once they have been copied into
+    // _build by the build tool. If the project has not been built for the
+    // test profile yet those of the default profile are used instead, so
+    // only the dev-dependencies are missing.
+    let fallback;
+    let deps_root = if root.profile == Profile::Test && !root.build_lib_path().is_dir() {
+        fallback = ProjectRoot {
+            root: root.root.clone(),
+            profile: Profile::Default,
+        };
+        &fallback
+    } else {
+        root
+    };
+    let configs = deps_root.package_configs(&root_name).unwrap_or_default();
+    for name in configs.keys() {
+        read(
+            name,
+            Origin::Src,
+            deps_root.build_lib_package_src_path(name),
+        )?;
+    }
+
+    Ok((root_name, sources))
+}
+
+/// Parse and type check every module that can be. Unlike the build tool a
+/// failure does not stop compilation, so that the rest of the project can
+/// still be used by the language server.
+///
+/// Warnings are only kept for the modules of the top level package, as
+/// the user cannot fix warnings in their dependencies.
+///
+pub fn analyse(root_package: &str, sources: Vec<Source>) -> Compiled {
+    let mut compiled = Compiled {
+        root_package: root_package.to_string(),
+        ..Default::default()
+    };
+    let mut parsed = HashMap::with_capacity(sources.len());
+
+    // Modules that could not be type checked, either because they contain
+    // errors or because they import a module that could not be type checked
+    let mut failed = HashSet::new();
+
+    for source in sources {
+        if let Some(existing) = compiled.modules.get(&source.name) {
+            compiled.errors.push(Error::DuplicateModule {
+                module: source.name.clone(),
+                first: existing.path.clone(),
+                second: source.path.clone(),
+            });
+            continue;
+        }
+
+        match package_compiler::parse_source(&source.code, &source.name, &source.path) {
+            Ok(ast) => {
+                let _ = parsed.insert(source.name.clone(), ast);
+            }
+            Err(error) => {
+                compiled.errors.push(error);
+                let _ = failed.insert(source.name.clone());
+            }
+        }
+
+        let _ = compiled.modules.insert(
+            source.name.clone(),
+            Module {
+                name: source.name.clone(),
+                package: source.package,
+                origin: source.origin,
+                path: source.path,
+                code: source.code,
+                ast: None,
+                previous_ast: None,
+            },
+        );
+    }
+
+    let dependencies = parsed
+        .iter()
+        .map(|(name, ast)| {
+            let deps = ast.dependencies().into_iter().map(|(dep, _)| dep).collect();
+            (name.clone(), deps)
+        })
+        .collect();
+    let sequence = match dep_tree::toposort_deps(dependencies) {
+        Ok(sequence) => sequence,
+        Err(dep_tree::Error::Cycle(modules)) => {
+            compiled.errors.push(Error::ImportCycle { modules });
+            return compiled;
+        }
+    };
+
+    let mut uid = 0;
+    for name in sequence {
+        let ast = parsed
+            .remove(&name)
+            .gleam_expect("Getting parsed module for name");
+
+        // The build tool checks imports before type checking, which would
+        // otherwise fail on a module that does not exist
+        let unknown = ast
+            .dependencies()
+            .into_iter()
+            .find(|(dep, _)| !compiled.modules.contains_key(dep));
+        if let Some((import, location)) = unknown {
+            let module = &compiled.modules[&name];
+            let error = Error::UnknownImport {
+                module: name.clone(),
+                import,
+                location,
+                path: module.path.clone(),
+                src: module.code.clone(),
+                modules: compiled.modules.keys().cloned().collect(),
+            };
+            compiled.errors.push(error);
+            let _ = failed.insert(name);
+            continue;
+        }
+
+        let module = compiled
+            .modules
+            .get_mut(&name)
+            .gleam_expect("Getting module for name");
+
+        if ast
+            .dependencies()
+            .iter()
+            .any(|(dep, _)| failed.contains(dep))
+        {
+            let _ = failed.insert(name);
+            continue;
+        }
+
+        let mut warnings = vec![];
+        let result = typ::infer_module(&mut uid, ast, &compiled.module_types, &mut warnings);
+        if module.package == root_package {
+            compiled
+                .warnings
+                .extend(warnings.into_iter().map(|warning| Warning::Type {
+                    path: module.path.clone(),
+                    src: module.code.clone(),
+                    warning,
+                }));
+        }
+
+        match result {
+            Ok(ast) => {
+                let _ = compiled
+                    .module_types
+                    .insert(name, (module.origin, ast.type_info.clone()));
+                module.ast = Some(ast);
+            }
+            Err(errors) => {
+                compiled.errors.push(Error::Type {
+                    path: module.path.clone(),
+                    src: module.code.clone(),
+                    errors,
+                });
+                let _ = failed.insert(name);
+            }
+        }
+    }
+
+    compiled
+}
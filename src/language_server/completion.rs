@@ -0,0 +1,494 @@
+use super::{
+    compiler::{Compiled, Module},
+    definition, hover,
+};
+use crate::{
+    ast::{ArgNames, Pattern, SrcSpan, Statement, TypedArg, TypedExpr, TypedModule, TypedPattern},
+    typ::{self, collapse_links, pretty::Printer, HasType, Type, ValueConstructorVariant},
+};
+use lsp_types::{CompletionItem, CompletionItemKind, Documentation, MarkupContent, MarkupKind};
+use std::{collections::HashMap, sync::Arc};
+
+/// The local variables in scope and their types. The type of a name bound
+/// with `as` is not recorded, so it is only known where it can be worked out
+/// from the type of the value being matched.
+///
+type Scope = HashMap<String, Option<Arc<Type>>>;
+
+/// The completions for a position in a module. Following a `.` these are
+/// the members of an imported module or the fields of a record, otherwise
+/// they are the variables in scope, the values defined in or imported into
+/// the module, and the names of imported modules.
+///
+pub fn completion(compiled: &Compiled, module: &Module, byte_index: usize) -> Vec<CompletionItem> {
+    // The module is likely to contain errors while code is being written, in
+    // which case the last version that could be type checked is used
+    let ast = match module.ast.as_ref().or(module.previous_ast.as_ref()) {
+        Some(ast) => ast,
+        None => return vec![],
+    };
+    let scope = variables_in_scope(ast, byte_index);
+
+    let mut items = match qualifier(&module.code, byte_index) {
+        Some(name) => match scope.get(name) {
+            Some(Some(typ)) => record_fields(compiled, ast, typ.clone()),
+            Some(None) => vec![],
+            None => match definition::imported_module(ast, name) {
+                Some(module) => module_members(compiled, &module),
+                None => vec![],
+            },
+        },
+
+        None => scope
+            .into_iter()
+            .map(|(name, typ)| item(name, CompletionItemKind::Variable, typ.as_deref(), None))
+            .chain(module_values(ast))
+            .chain(imports(compiled, ast))
+            .collect(),
+    };
+    items.sort_by(|a, b| a.label.cmp(&b.label));
+    items
+}
+
+fn item(
+    label: String,
+    kind: CompletionItemKind,
+    typ: Option<&Type>,
+    documentation: Option<&str>,
+) -> CompletionItem {
+    CompletionItem {
+        label,
+        kind: Some(kind),
+        detail: typ.map(|typ| Printer::new().pretty_print(typ, 0)),
+        documentation: documentation.map(|doc| {
+            Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: doc.trim().to_string(),
+            })
+        }),
+        ..Default::default()
+    }
+}
+
+fn value_kind(variant: &ValueConstructorVariant) -> CompletionItemKind {
+    match variant {
+        ValueConstructorVariant::LocalVariable => CompletionItemKind::Variable,
+        ValueConstructorVariant::ModuleConstant { .. } => CompletionItemKind::Constant,
+        ValueConstructorVariant::ModuleFn { .. } => CompletionItemKind::Function,
+        ValueConstructorVariant::Record { .. } => CompletionItemKind::Constructor,
+    }
+}
+
+/// The name before the `.` preceding the word being typed, if there is one.
+///
+fn qualifier(src: &str, byte_index: usize) -> Option<&str> {
+    let before = &src[..byte_index.min(src.len())];
+    let word_start = |text: &str| {
+        text.char_indices()
+            .rev()
+            .find(|(_, c)| !(c.is_alphanumeric() || *c == '_'))
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0)
+    };
+    let before = &before[..word_start(before)];
+    if !before.ends_with('.') {
+        return None;
+    }
+    let before = &before[..before.len() - 1];
+    let name = &before[word_start(before)..];
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+fn module_members(compiled: &Compiled, module: &str) -> Vec<CompletionItem> {
+    let interface = match compiled.module_types.get(module) {
+        Some((_, interface)) => interface,
+        None => return vec![],
+    };
+    let ast = compiled
+        .modules
+        .get(module)
+        .and_then(|module| module.ast.as_ref());
+    let documentation = |origin: &SrcSpan| ast.and_then(|ast| hover::documentation(ast, origin));
+
+    let values = interface.values.iter().map(|(name, value)| {
+        item(
+            name.clone(),
+            value_kind(&value.variant),
+            Some(&value.typ),
+            documentation(&value.origin),
+        )
+    });
+    let types = interface.types.iter().map(|(name, typ)| {
+        item(
+            name.clone(),
+            CompletionItemKind::Class,
+            None,
+            documentation(&typ.origin),
+        )
+    });
+    values.chain(types).collect()
+}
+
+fn record_fields(compiled: &Compiled, ast: &TypedModule, typ: Arc<Type>) -> Vec<CompletionItem> {
+    let (module, name) = match typ::collapse_links(typ).as_ref() {
+        Type::App { module, name, .. } => (module.join("/"), name.clone()),
+        _ => return vec![],
+    };
+
+    // Only the accessors of public types are kept in a module's interface
+    let accessors = if module == ast.name_string() {
+        ast.type_info.accessors.get(&name)
+    } else {
+        compiled
+            .module_types
+            .get(&module)
+            .and_then(|(_, interface)| interface.accessors.get(&name))
+    };
+    if let Some(accessors) = accessors {
+        return accessors
+            .accessors
+            .values()
+            .map(|accessor| {
+                let label = accessor.label.clone();
+                item(label, CompletionItemKind::Field, Some(&accessor.typ), None)
+            })
+            .collect();
+    }
+
+    // The fields of private types in this module are taken from their
+    // definition, without their types
+    if module != ast.name_string() {
+        return vec![];
+    }
+    ast.statements
+        .iter()
+        .find_map(|statement| match statement {
+            Statement::CustomType {
+                name: type_name,
+                constructors,
+                ..
+            } if type_name == &name && constructors.len() == 1 => Some(&constructors[0].args),
+            _ => None,
+        })
+        .into_iter()
+        .flatten()
+        .filter_map(|(label, _, _)| label.clone())
+        .map(|label| item(label, CompletionItemKind::Field, None, None))
+        .collect()
+}
+
+/// The functions, constants, and record constructors defined in a module.
+///
+fn module_values(ast: &TypedModule) -> Vec<CompletionItem> {
+    let public_type = |name: &str| {
+        ast.type_info
+            .values
+            .get(name)
+            .map(|value| value.typ.clone())
+    };
+    let mut items = vec![];
+    for statement in ast.statements.iter() {
+        match statement {
+            Statement::Fn {
+                name,
+                args,
+                return_type,
+                doc,
+                ..
+            } => {
+                let args = args.iter().map(|arg| arg.typ.clone()).collect();
+                let typ = typ::fn_(args, return_type.clone());
+                let kind = CompletionItemKind::Function;
+                items.push(item(name.clone(), kind, Some(&typ), doc.as_deref()));
+            }
+
+            Statement::ExternalFn { name, doc, .. } => {
+                let typ = public_type(name);
+                let kind = CompletionItemKind::Function;
+                items.push(item(name.clone(), kind, typ.as_deref(), doc.as_deref()));
+            }
+
+            Statement::ModuleConstant { name, typ, doc, .. } => {
+                let kind = CompletionItemKind::Constant;
+                items.push(item(name.clone(), kind, Some(typ), doc.as_deref()));
+            }
+
+            Statement::CustomType { constructors, .. } => {
+                for constructor in constructors {
+                    let name = &constructor.name;
+                    let typ = public_type(name);
+                    let doc = constructor.documentation.as_deref();
+                    let kind = CompletionItemKind::Constructor;
+                    items.push(item(name.clone(), kind, typ.as_deref(), doc));
+                }
+            }
+
+            Statement::TypeAlias { .. }
+            | Statement::ExternalType { .. }
+            | Statement::Import { .. } => (),
+        }
+    }
+    items
+}
+
+/// The names of imported modules and of the values imported unqualified.
+///
+fn imports(compiled: &Compiled, ast: &TypedModule) -> Vec<CompletionItem> {
+    let mut items = vec![];
+    for statement in ast.statements.iter() {
+        if let Statement::Import {
+            module,
+            as_name,
+            unqualified,
+            ..
+        } = statement
+        {
+            let module_name = module.join("/");
+            if let Some(alias) = as_name.as_ref().or_else(|| module.last()) {
+                items.push(CompletionItem {
+                    detail: Some(module_name.clone()),
+                    ..item(alias.clone(), CompletionItemKind::Module, None, None)
+                });
+            }
+
+            let interface = compiled.module_types.get(&module_name);
+            let imported_ast = compiled
+                .modules
+                .get(&module_name)
+                .and_then(|module| module.ast.as_ref());
+            for import in unqualified {
+                let value = match interface.and_then(|(_, i)| i.values.get(&import.name)) {
+                    Some(value) => value,
+                    None => continue,
+                };
+                let label = import.as_name.as_ref().unwrap_or(&import.name).clone();
+                let doc = imported_ast.and_then(|ast| hover::documentation(ast, &value.origin));
+                let kind = value_kind(&value.variant);
+                items.push(item(label, kind, Some(&value.typ), doc));
+            }
+        }
+    }
+    items
+}
+
+/// The local variables in scope at a position in a module, found by walking
+/// the expressions that contain the position.
+///
+fn variables_in_scope(ast: &TypedModule, byte_index: usize) -> Scope {
+    let mut scope = HashMap::new();
+    for statement in ast.statements.iter() {
+        if let Statement::Fn {
+            location,
+            end_location,
+            args,
+            body,
+            ..
+        } = statement
+        {
+            if location.start <= byte_index && byte_index <= *end_location {
+                bind_args(args, &mut scope);
+                walk(body, byte_index, &mut scope);
+            }
+        }
+    }
+    scope
+}
+
+/// The span of source code covered by an expression, including all of the
+/// expressions following a let binding.
+///
+fn extent(expr: &TypedExpr) -> SrcSpan {
+    match expr {
+        TypedExpr::Seq { first, then, .. } => SrcSpan {
+            start: extent(first).start,
+            end: extent(then).end,
+        },
+        TypedExpr::Let { location, then, .. } => SrcSpan {
+            start: location.start,
+            end: extent(then).end,
+        },
+        _ => expr.location().clone(),
+    }
+}
+
+fn walk_within(expr: &TypedExpr, byte_index: usize, scope: &mut Scope) {
+    let extent = extent(expr);
+    if extent.start <= byte_index && byte_index <= extent.end {
+        walk(expr, byte_index, scope)
+    }
+}
+
+fn walk(expr: &TypedExpr, byte_index: usize, scope: &mut Scope) {
+    match expr {
+        // Variables bound by a let are in scope for the rest of the sequence
+        TypedExpr::Let {
+            pattern,
+            value,
+            then,
+            ..
+        } => {
+            if byte_index <= extent(value).end {
+                walk_within(value, byte_index, scope)
+            } else {
+                bind_pattern(pattern, Some(value.typ()), scope);
+                walk(then, byte_index, scope)
+            }
+        }
+
+        TypedExpr::Seq { first, then, .. } => {
+            if byte_index <= extent(first).end {
+                walk_within(first, byte_index, scope)
+            } else {
+                walk(then, byte_index, scope)
+            }
+        }
+
+        _ if !expr.location().contains(byte_index) => (),
+
+        TypedExpr::Fn { args, body, .. } => {
+            bind_args(args, scope);
+            walk(body, byte_index, scope)
+        }
+
+        TypedExpr::Case {
+            subjects, clauses, ..
+        } => {
+            for subject in subjects {
+                walk_within(subject, byte_index, scope);
+            }
+            for clause in clauses {
+                let location = &clause.location;
+                if location.start <= byte_index && byte_index <= location.end {
+                    for (pattern, subject) in clause.pattern.iter().zip(subjects) {
+                        bind_pattern(pattern, Some(subject.typ()), scope);
+                    }
+                    walk(&clause.then, byte_index, scope);
+                }
+            }
+        }
+
+        TypedExpr::Call { fun, args, .. } => {
+            walk_within(fun, byte_index, scope);
+            for arg in args {
+                walk_within(&arg.value, byte_index, scope);
+            }
+        }
+
+        TypedExpr::BinOp { left, right, .. } | TypedExpr::Pipe { left, right, .. } => {
+            walk_within(left, byte_index, scope);
+            walk_within(right, byte_index, scope);
+        }
+
+        TypedExpr::ListCons { head, tail, .. } => {
+            walk_within(head, byte_index, scope);
+            walk_within(tail, byte_index, scope);
+        }
+
+        TypedExpr::Tuple { elems, .. } => {
+            for elem in elems {
+                walk_within(elem, byte_index, scope);
+            }
+        }
+
+        TypedExpr::TupleIndex { tuple: record, .. } | TypedExpr::RecordAccess { record, .. } => {
+            walk_within(record, byte_index, scope)
+        }
+
+        TypedExpr::RecordUpdate {
+            constructor,
+            spread,
+            args,
+            ..
+        } => {
+            walk_within(constructor, byte_index, scope);
+            walk_within(spread, byte_index, scope);
+            for arg in args {
+                walk_within(&arg.value, byte_index, scope);
+            }
+        }
+
+        TypedExpr::BitString { segments, .. } => {
+            for segment in segments {
+                walk_within(&segment.value, byte_index, scope);
+            }
+        }
+
+        TypedExpr::Int { .. }
+        | TypedExpr::Float { .. }
+        | TypedExpr::String { .. }
+        | TypedExpr::Var { .. }
+        | TypedExpr::ListNil { .. }
+        | TypedExpr::ModuleSelect { .. }
+        | TypedExpr::Todo { .. } => (),
+    }
+}
+
+fn bind_args(args: &[TypedArg], scope: &mut Scope) {
+    for arg in args {
+        match &arg.names {
+            ArgNames::Named { name } | ArgNames::NamedLabelled { name, .. } => {
+                let _ = scope.insert(name.clone(), Some(arg.typ.clone()));
+            }
+            ArgNames::Discard { .. } | ArgNames::LabelledDiscard { .. } => (),
+        }
+    }
+}
+
+fn bind_pattern(pattern: &TypedPattern, typ: Option<Arc<Type>>, scope: &mut Scope) {
+    match pattern {
+        Pattern::Var { name, typ, .. } => {
+            let _ = scope.insert(name.clone(), Some(typ.clone()));
+        }
+
+        Pattern::Let { name, pattern, .. } => {
+            let _ = scope.insert(name.clone(), typ.clone());
+            bind_pattern(pattern, typ, scope)
+        }
+
+        Pattern::Cons { head, tail, .. } => {
+            let element = typ
+                .as_ref()
+                .and_then(|typ| match collapse_links(typ.clone()).as_ref() {
+                    Type::App {
+                        module, name, args, ..
+                    } if module.is_empty() && name == "List" => args.first().cloned(),
+                    _ => None,
+                });
+            bind_pattern(head, element, scope);
+            bind_pattern(tail, typ, scope);
+        }
+
+        Pattern::Constructor { args, .. } => {
+            for arg in args {
+                bind_pattern(&arg.value, None, scope);
+            }
+        }
+
+        Pattern::Tuple { elems, .. } => {
+            let types = match typ.map(collapse_links).as_deref() {
+                Some(Type::Tuple { elems }) => elems.clone(),
+                _ => vec![],
+            };
+            for (index, elem) in elems.iter().enumerate() {
+                bind_pattern(elem, types.get(index).cloned(), scope);
+            }
+        }
+
+        Pattern::BitString { segments, .. } => {
+            for segment in segments {
+                bind_pattern(&segment.value, None, scope);
+            }
+        }
+
+        Pattern::Int { .. }
+        | Pattern::Float { .. }
+        | Pattern::String { .. }
+        | Pattern::VarCall { .. }
+        | Pattern::Discard { .. }
+        | Pattern::Nil { .. } => (),
+    }
+}
@@ -0,0 +1,145 @@
+use super::compiler::{Compiled, Module};
+use crate::ast::{Located, Pattern, SrcSpan, Statement, TypeAst, TypedExpr, TypedModule};
+
+/// The module and location where the node found at a position in a module
+/// was defined.
+///
+pub fn definition<'a>(
+    compiled: &'a Compiled,
+    module: &'a Module,
+    byte_index: usize,
+) -> Option<(&'a Module, SrcSpan)> {
+    let ast = module.ast.as_ref()?;
+    match ast.find_node(byte_index)? {
+        // Values from the prelude have no module and so no definition
+        Located::Expression(TypedExpr::Var { constructor, .. }) => {
+            let module = compiled.modules.get(&constructor.module.join("/"))?;
+            Some((module, constructor.origin.clone()))
+        }
+
+        Located::Expression(TypedExpr::ModuleSelect {
+            module_name, label, ..
+        }) => public_value(compiled, &module_name.join("/"), label),
+
+        Located::Pattern(Pattern::Constructor {
+            module: alias,
+            name,
+            ..
+        }) => match alias {
+            Some(alias) => public_value(compiled, &imported_module(ast, alias)?, name),
+            None => match unqualified_import(ast, name) {
+                Some((imported, name)) => public_value(compiled, &imported, name),
+                None => local_constructor(ast, name).map(|origin| (module, origin)),
+            },
+        },
+
+        Located::Annotation(TypeAst::Constructor {
+            module: alias,
+            name,
+            ..
+        }) => match alias {
+            Some(alias) => public_type(compiled, &imported_module(ast, alias)?, name),
+            None => match unqualified_import(ast, name) {
+                Some((imported, name)) => public_type(compiled, &imported, name),
+                None => local_type(ast, name).map(|origin| (module, origin)),
+            },
+        },
+
+        Located::UnqualifiedImport { module, import } => {
+            let module = module.join("/");
+            public_value(compiled, &module, &import.name)
+                .or_else(|| public_type(compiled, &module, &import.name))
+        }
+
+        Located::Statement(Statement::Import { module, .. }) => {
+            let module = compiled.modules.get(&module.join("/"))?;
+            Some((module, SrcSpan::default()))
+        }
+
+        _ => None,
+    }
+}
+
+/// The definition of a value in the public interface of a module.
+///
+pub fn public_value<'a>(
+    compiled: &'a Compiled,
+    module: &str,
+    name: &str,
+) -> Option<(&'a Module, SrcSpan)> {
+    let (_, interface) = compiled.module_types.get(module)?;
+    let value = interface.values.get(name)?;
+    let module = compiled.modules.get(&value.module.join("/"))?;
+    Some((module, value.origin.clone()))
+}
+
+fn public_type<'a>(
+    compiled: &'a Compiled,
+    module: &str,
+    name: &str,
+) -> Option<(&'a Module, SrcSpan)> {
+    let (_, interface) = compiled.module_types.get(module)?;
+    let typ = interface.types.get(name)?;
+    let module = compiled.modules.get(&typ.module.join("/"))?;
+    Some((module, typ.origin.clone()))
+}
+
+fn local_constructor(ast: &TypedModule, name: &str) -> Option<SrcSpan> {
+    ast.statements.iter().find_map(|statement| match statement {
+        Statement::CustomType { constructors, .. } => constructors
+            .iter()
+            .find(|constructor| constructor.name == name)
+            .map(|constructor| constructor.location.clone()),
+        _ => None,
+    })
+}
+
+fn local_type(ast: &TypedModule, name: &str) -> Option<SrcSpan> {
+    ast.statements.iter().find_map(|statement| match statement {
+        Statement::CustomType {
+            name: type_name,
+            location,
+            ..
+        }
+        | Statement::ExternalType {
+            name: type_name,
+            location,
+            ..
+        }
+        | Statement::TypeAlias {
+            alias: type_name,
+            location,
+            ..
+        } if type_name == name => Some(location.clone()),
+        _ => None,
+    })
+}
+
+/// The name of the module imported with the given alias, which is the last
+/// segment of the module name unless `as` was used.
+///
+pub fn imported_module(ast: &TypedModule, alias: &str) -> Option<String> {
+    ast.statements.iter().find_map(|statement| match statement {
+        Statement::Import {
+            module, as_name, ..
+        } if as_name.as_ref().or_else(|| module.last())? == alias => Some(module.join("/")),
+        _ => None,
+    })
+}
+
+/// The module and original name of a value or type imported unqualified
+/// with the given name, as in `import one.{Thing as Other}`.
+///
+pub fn unqualified_import<'a>(ast: &'a TypedModule, name: &str) -> Option<(String, &'a str)> {
+    ast.statements.iter().find_map(|statement| match statement {
+        Statement::Import {
+            module,
+            unqualified,
+            ..
+        } => unqualified
+            .iter()
+            .find(|import| import.as_name.as_ref().unwrap_or(&import.name) == name)
+            .map(|import| (module.join("/"), import.name.as_str())),
+        _ => None,
+    })
+}
@@ -0,0 +1,37 @@
+use super::range;
+use crate::ast::SrcSpan;
+use lsp_types::TextEdit;
+
+/// An edit replacing the whole of a document with its formatted code, if it
+/// is not already formatted. Code that does not parse is left alone.
+///
+pub fn format_document(code: &str) -> Option<Vec<TextEdit>> {
+    let formatted = crate::format::pretty(code).ok()?;
+    if formatted == code {
+        return Some(vec![]);
+    }
+    let whole = SrcSpan {
+        start: 0,
+        end: code.len(),
+    };
+    Some(vec![TextEdit {
+        range: range(code, &whole),
+        new_text: formatted,
+    }])
+}
+
+/// Edits formatting each top level definition or import that overlaps the
+/// given part of a document, along with its comments.
+///
+pub fn format_range(code: &str, selection: &SrcSpan) -> Option<Vec<TextEdit>> {
+    let statements = crate::format::pretty_statements(code, selection).ok()?;
+    let edits = statements
+        .into_iter()
+        .filter(|(span, formatted)| code[span.start..span.end] != *formatted)
+        .map(|(span, formatted)| TextEdit {
+            range: range(code, &span),
+            new_text: formatted,
+        })
+        .collect();
+    Some(edits)
+}
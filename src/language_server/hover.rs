@@ -0,0 +1,100 @@
+use super::{
+    compiler::{Compiled, Module},
+    definition,
+};
+use crate::{
+    ast::{Located, Pattern, SrcSpan, Statement, TypedExpr, TypedModule},
+    typ::{self, pretty::Printer, HasType, Type, ValueConstructorVariant},
+};
+
+/// The Markdown shown when hovering over a position in a module: the type
+/// of the node found there, followed by the documentation of the function,
+/// constant, or record constructor it refers to.
+///
+pub fn hover(compiled: &Compiled, module: &Module, byte_index: usize) -> Option<String> {
+    let ast = module.ast.as_ref()?;
+    let (typ, doc) = match ast.find_node(byte_index)? {
+        Located::Expression(expr) => (expr.typ(), expression_doc(compiled, expr)),
+
+        Located::Pattern(Pattern::Var { typ, .. })
+        | Located::Pattern(Pattern::VarCall { typ, .. }) => (typ.clone(), None),
+
+        Located::Pattern(_) => return None,
+
+        Located::Arg(arg) => (arg.typ.clone(), None),
+
+        Located::Statement(Statement::Fn {
+            args,
+            return_type,
+            doc,
+            ..
+        }) => {
+            let args = args.iter().map(|arg| arg.typ.clone()).collect();
+            (typ::fn_(args, return_type.clone()), doc.as_deref())
+        }
+
+        Located::Statement(Statement::ModuleConstant { typ, doc, .. }) => {
+            (typ.clone(), doc.as_deref())
+        }
+
+        Located::Annotation(_) | Located::UnqualifiedImport { .. } | Located::Statement(_) => {
+            return None
+        }
+    };
+    Some(contents(&typ, doc))
+}
+
+fn contents(typ: &Type, doc: Option<&str>) -> String {
+    let typ = Printer::new().pretty_print(typ, 0);
+    match doc {
+        Some(doc) => format!("```gleam\n{}\n```\n\n{}", typ, doc.trim()),
+        None => format!("```gleam\n{}\n```", typ),
+    }
+}
+
+fn expression_doc<'a>(compiled: &'a Compiled, expr: &TypedExpr) -> Option<&'a str> {
+    let (module, origin) = match expr {
+        TypedExpr::ModuleSelect {
+            module_name, label, ..
+        } => definition::public_value(compiled, &module_name.join("/"), label)?,
+
+        TypedExpr::Var { constructor, .. }
+            if constructor.variant == ValueConstructorVariant::LocalVariable =>
+        {
+            return None
+        }
+
+        TypedExpr::Var { constructor, .. } => {
+            let module = compiled.modules.get(&constructor.module.join("/"))?;
+            (module, constructor.origin.clone())
+        }
+
+        _ => return None,
+    };
+    documentation(module.ast.as_ref()?, &origin)
+}
+
+/// The documentation of the function, constant, type, or record constructor
+/// defined at a location in a module.
+///
+pub fn documentation<'a>(ast: &'a TypedModule, origin: &SrcSpan) -> Option<&'a str> {
+    ast.statements.iter().find_map(|statement| match statement {
+        Statement::Fn { location, doc, .. }
+        | Statement::ExternalFn { location, doc, .. }
+        | Statement::ModuleConstant { location, doc, .. }
+        | Statement::CustomType { location, doc, .. }
+        | Statement::ExternalType { location, doc, .. }
+        | Statement::TypeAlias { location, doc, .. }
+            if location == origin =>
+        {
+            doc.as_deref()
+        }
+
+        Statement::CustomType { constructors, .. } => constructors
+            .iter()
+            .find(|constructor| &constructor.location == origin)
+            .and_then(|constructor| constructor.documentation.as_deref()),
+
+        _ => None,
+    })
+}
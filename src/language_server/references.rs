@@ -0,0 +1,697 @@
+use super::{
+    compiler::{self, Compiled},
+    definition,
+};
+use crate::{
+    ast::{
+        BitStringSegment, BitStringSegmentOption, SrcSpan, Statement, TypeAst, TypedConstant,
+        TypedExpr, TypedModule, TypedPattern, TypedStatement,
+    },
+    error::{Error, RenameProblem},
+    typ::ValueConstructorVariant,
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Value,
+    Type,
+}
+
+/// A module level function, constant, record constructor, or type,
+/// identified by the module it is defined in and the location of its
+/// definition.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Definition {
+    pub module: String,
+    pub kind: Kind,
+    pub origin: SrcSpan,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub module: String,
+    pub location: SrcSpan,
+    /// False when the name does not appear at the location because an alias
+    /// is used, as in `import one.{add_one as increment}`. These references
+    /// are left unchanged when renaming.
+    pub named: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    pub declaration: Reference,
+    pub references: Vec<Reference>,
+}
+
+/// Every reference to each module level value and type, built from the
+/// typed ASTs of a compiled project. Modules that could not be type checked
+/// are not included.
+///
+#[derive(Debug, Default)]
+pub struct Index {
+    entries: HashMap<Definition, Entry>,
+}
+
+impl Index {
+    pub fn new(compiled: &Compiled) -> Self {
+        let mut index = Self::default();
+        for module in compiled.modules.values() {
+            if let Some(ast) = &module.ast {
+                index.insert_definitions(&module.name, &module.code, ast);
+            }
+        }
+        for module in compiled.modules.values() {
+            if let Some(ast) = &module.ast {
+                let mut walker = Walker {
+                    index: &mut index,
+                    compiled,
+                    ast,
+                    module: &module.name,
+                    code: &module.code,
+                };
+                for statement in ast.statements.iter() {
+                    walker.statement(statement);
+                }
+            }
+        }
+        index
+    }
+
+    fn insert_definitions(&mut self, module: &str, code: &str, ast: &TypedModule) {
+        let mut insert = |kind, name: &str, origin: &SrcSpan| {
+            let definition = Definition {
+                module: module.to_string(),
+                kind,
+                origin: origin.clone(),
+            };
+            let entry = Entry {
+                name: name.to_string(),
+                declaration: reference(module, code, origin, name),
+                references: vec![],
+            };
+            let _ = self.entries.insert(definition, entry);
+        };
+
+        for statement in ast.statements.iter() {
+            match statement {
+                Statement::Fn { name, location, .. }
+                | Statement::ExternalFn { name, location, .. }
+                | Statement::ModuleConstant { name, location, .. } => {
+                    insert(Kind::Value, name, location)
+                }
+
+                Statement::CustomType {
+                    name,
+                    location,
+                    constructors,
+                    ..
+                } => {
+                    insert(Kind::Type, name, location);
+                    for constructor in constructors {
+                        insert(Kind::Value, &constructor.name, &constructor.location);
+                    }
+                }
+
+                Statement::ExternalType { name, location, .. }
+                | Statement::TypeAlias {
+                    alias: name,
+                    location,
+                    ..
+                } => insert(Kind::Type, name, location),
+
+                Statement::Import { .. } => (),
+            }
+        }
+    }
+
+    pub fn get(&self, definition: &Definition) -> Option<&Entry> {
+        self.entries.get(definition)
+    }
+
+    /// The definitions with a name in a module. A type and a record
+    /// constructor may share the same name.
+    ///
+    pub fn find(&self, module: &str, name: &str) -> Vec<&Definition> {
+        self.entries
+            .iter()
+            .filter(|(definition, entry)| definition.module == module && entry.name == name)
+            .map(|(definition, _)| definition)
+            .collect()
+    }
+
+    fn find_kind(&self, module: &str, kind: Kind, name: &str) -> Option<Definition> {
+        self.find(module, name)
+            .into_iter()
+            .find(|definition| definition.kind == kind)
+            .cloned()
+    }
+
+    /// The definition declared or referred to at a position in a module. If
+    /// references overlap, as with aliased types, the narrowest is used.
+    ///
+    pub fn definition_at(&self, module: &str, byte_index: usize) -> Option<&Definition> {
+        self.entries
+            .iter()
+            .flat_map(|(definition, entry)| {
+                std::iter::once(&entry.declaration)
+                    .chain(entry.references.iter())
+                    .map(move |reference| (definition, reference))
+            })
+            .filter(|(_, reference)| {
+                reference.module == module
+                    && reference.location.start <= byte_index
+                    && byte_index <= reference.location.end
+            })
+            .min_by_key(|(_, reference)| reference.location.end - reference.location.start)
+            .map(|(definition, _)| definition)
+    }
+
+    /// The locations to replace with a new name in each module to rename
+    /// the given definitions. To be sure that no code would refer to a
+    /// different definition afterwards the project is analysed again with
+    /// the changes made.
+    ///
+    pub fn rename(
+        &self,
+        compiled: &Compiled,
+        definitions: &[&Definition],
+        new_name: &str,
+    ) -> Result<HashMap<String, Vec<SrcSpan>>, Error> {
+        let mut edits: HashMap<String, Vec<SrcSpan>> = HashMap::new();
+        for definition in definitions {
+            let entry = match self.entries.get(definition) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            let error = |problem| Error::Rename {
+                name: format!("{}.{}", definition.module, entry.name),
+                new_name: new_name.to_string(),
+                problem,
+            };
+
+            if !compiled.errors.is_empty() {
+                return Err(error(RenameProblem::ProjectErrors));
+            }
+            match compiled.modules.get(&definition.module) {
+                Some(module) if module.package == compiled.root_package => (),
+                _ => return Err(error(RenameProblem::Dependency)),
+            }
+            if !valid_name(&entry.name, new_name) {
+                return Err(error(RenameProblem::InvalidName));
+            }
+
+            for reference in std::iter::once(&entry.declaration).chain(entry.references.iter()) {
+                if reference.named {
+                    edits
+                        .entry(reference.module.clone())
+                        .or_default()
+                        .push(reference.location.clone());
+                }
+            }
+        }
+
+        let mut sources = compiled.sources();
+        for source in sources.iter_mut() {
+            if let Some(spans) = edits.get(&source.name) {
+                source.code = apply(&source.code, spans, new_name);
+            }
+        }
+        let renamed = compiler::analyse(&compiled.root_package, sources);
+        let renamed_index = Self::new(&renamed);
+
+        // Every reference must refer to the renamed definition, from the
+        // same modules as before
+        for definition in definitions {
+            let entry = match self.entries.get(definition) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            let renamed_entry = renamed_index
+                .find_kind(&definition.module, definition.kind, new_name)
+                .and_then(|definition| renamed_index.entries.get(&definition));
+            let same = match renamed_entry {
+                Some(renamed_entry) if renamed.errors.is_empty() => {
+                    reference_modules(entry) == reference_modules(renamed_entry)
+                }
+                _ => false,
+            };
+            if !same {
+                return Err(Error::Rename {
+                    name: format!("{}.{}", definition.module, entry.name),
+                    new_name: new_name.to_string(),
+                    problem: RenameProblem::ChangesMeaning,
+                });
+            }
+        }
+
+        Ok(edits)
+    }
+}
+
+fn reference_modules(entry: &Entry) -> Vec<&str> {
+    let mut modules: Vec<_> = entry
+        .references
+        .iter()
+        .map(|reference| reference.module.as_str())
+        .collect();
+    modules.sort_unstable();
+    modules
+}
+
+/// Functions and constants are named in snake_case, while types and record
+/// constructors are named in UpperCamelCase.
+///
+fn valid_name(name: &str, new_name: &str) -> bool {
+    let mut chars = new_name.chars();
+    match chars.next() {
+        Some(first) if name.starts_with(char::is_uppercase) => {
+            first.is_ascii_uppercase() && chars.all(|c| c.is_ascii_alphanumeric())
+        }
+        Some(first) => {
+            first.is_ascii_lowercase()
+                && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        }
+        None => false,
+    }
+}
+
+/// Replace the source code at each location with a new name.
+///
+pub fn apply(code: &str, spans: &[SrcSpan], new_name: &str) -> String {
+    let mut spans = spans.to_vec();
+    spans.sort_by_key(|span| span.start);
+    spans.dedup();
+    let mut code = code.to_string();
+    for span in spans.iter().rev() {
+        code.replace_range(span.start..span.end, new_name);
+    }
+    code
+}
+
+/// A reference at the location of a node, narrowed to the location of the
+/// name where it appears. Only the text before any arguments is searched,
+/// so the name of the node is not confused with those of its arguments.
+///
+pub fn reference(module: &str, code: &str, location: &SrcSpan, name: &str) -> Reference {
+    let text = &code[location.start..location.end];
+    let text = &text[..text.find('(').unwrap_or(text.len())];
+    let is_name_char = |c: char| c.is_alphanumeric() || c == '_';
+    let found = text.match_indices(name).find(|(i, _)| {
+        let before = text[..*i].chars().next_back();
+        let after = text[i + name.len()..].chars().next();
+        !before.map_or(false, is_name_char) && !after.map_or(false, is_name_char)
+    });
+    let (location, named) = match found {
+        Some((i, _)) => {
+            let start = location.start + i;
+            let end = start + name.len();
+            (SrcSpan { start, end }, true)
+        }
+        None => (location.clone(), false),
+    };
+    Reference {
+        module: module.to_string(),
+        location,
+        named,
+    }
+}
+
+struct Walker<'a> {
+    index: &'a mut Index,
+    compiled: &'a Compiled,
+    ast: &'a TypedModule,
+    module: &'a str,
+    code: &'a str,
+}
+
+impl<'a> Walker<'a> {
+    fn add(&mut self, definition: Definition, location: &SrcSpan) {
+        if let Some(entry) = self.index.entries.get_mut(&definition) {
+            let reference = reference(self.module, self.code, location, &entry.name);
+            entry.references.push(reference);
+        }
+    }
+
+    /// The definition of a name as written in this module, which may be
+    /// qualified with the alias of an imported module, imported unqualified,
+    /// or defined in this module.
+    ///
+    fn resolve(&self, alias: Option<&str>, name: &str, kind: Kind) -> Option<Definition> {
+        match alias {
+            Some(alias) => {
+                let module = definition::imported_module(self.ast, alias)?;
+                self.public(&module, name, kind)
+            }
+            None => match definition::unqualified_import(self.ast, name) {
+                Some((module, name)) => self.public(&module, name, kind),
+                None => self.index.find_kind(self.module, kind, name),
+            },
+        }
+    }
+
+    fn public(&self, module: &str, name: &str, kind: Kind) -> Option<Definition> {
+        let (_, interface) = self.compiled.module_types.get(module)?;
+        let (module, origin) = match kind {
+            Kind::Value => {
+                let value = interface.values.get(name)?;
+                (&value.module, &value.origin)
+            }
+            Kind::Type => {
+                let typ = interface.types.get(name)?;
+                (&typ.module, &typ.origin)
+            }
+        };
+        Some(Definition {
+            module: module.join("/"),
+            kind,
+            origin: origin.clone(),
+        })
+    }
+
+    fn statement(&mut self, statement: &TypedStatement) {
+        match statement {
+            Statement::Fn {
+                args,
+                return_annotation,
+                body,
+                ..
+            } => {
+                for annotation in args.iter().filter_map(|arg| arg.annotation.as_ref()) {
+                    self.type_ast(annotation);
+                }
+                if let Some(annotation) = return_annotation {
+                    self.type_ast(annotation);
+                }
+                self.expr(body);
+            }
+
+            Statement::TypeAlias { resolved_type, .. } => self.type_ast(resolved_type),
+
+            Statement::CustomType { constructors, .. } => {
+                for (_, annotation, _) in constructors.iter().flat_map(|c| c.args.iter()) {
+                    self.type_ast(annotation);
+                }
+            }
+
+            Statement::ExternalFn { args, retrn, .. } => {
+                for arg in args {
+                    self.type_ast(&arg.typ);
+                }
+                self.type_ast(retrn);
+            }
+
+            Statement::ExternalType { .. } => (),
+
+            // An unqualified import may refer to both a type and a record
+            // constructor
+            Statement::Import {
+                module,
+                unqualified,
+                ..
+            } => {
+                let module = module.join("/");
+                for import in unqualified {
+                    for kind in &[Kind::Value, Kind::Type] {
+                        if let Some(definition) = self.public(&module, &import.name, *kind) {
+                            self.add(definition, &import.location);
+                        }
+                    }
+                }
+            }
+
+            Statement::ModuleConstant {
+                annotation, value, ..
+            } => {
+                if let Some(annotation) = annotation {
+                    self.type_ast(annotation);
+                }
+                self.constant(value);
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &TypedExpr) {
+        match expr {
+            TypedExpr::Var {
+                location,
+                constructor,
+                ..
+            } => {
+                // Local variables and values from the prelude have no
+                // definition in the index
+                if constructor.variant != ValueConstructorVariant::LocalVariable {
+                    let definition = Definition {
+                        module: constructor.module.join("/"),
+                        kind: Kind::Value,
+                        origin: constructor.origin.clone(),
+                    };
+                    self.add(definition, location);
+                }
+            }
+
+            TypedExpr::ModuleSelect {
+                location,
+                module_name,
+                label,
+                ..
+            } => {
+                if let Some(definition) = self.public(&module_name.join("/"), label, Kind::Value) {
+                    self.add(definition, location);
+                }
+            }
+
+            TypedExpr::Fn {
+                args,
+                return_annotation,
+                body,
+                ..
+            } => {
+                for annotation in args.iter().filter_map(|arg| arg.annotation.as_ref()) {
+                    self.type_ast(annotation);
+                }
+                if let Some(annotation) = return_annotation {
+                    self.type_ast(annotation);
+                }
+                self.expr(body);
+            }
+
+            TypedExpr::Let {
+                pattern,
+                value,
+                then,
+                ..
+            } => {
+                self.pattern(pattern);
+                self.expr(value);
+                self.expr(then);
+            }
+
+            // Clause guards are not searched as module constants used in
+            // them are replaced with their values during type checking
+            TypedExpr::Case {
+                subjects, clauses, ..
+            } => {
+                for subject in subjects {
+                    self.expr(subject);
+                }
+                for clause in clauses {
+                    let patterns = std::iter::once(&clause.pattern)
+                        .chain(clause.alternative_patterns.iter())
+                        .flatten();
+                    for pattern in patterns {
+                        self.pattern(pattern);
+                    }
+                    self.expr(&clause.then);
+                }
+            }
+
+            TypedExpr::Seq { first, then, .. } => {
+                self.expr(first);
+                self.expr(then);
+            }
+
+            TypedExpr::Call { fun, args, .. } => {
+                self.expr(fun);
+                for arg in args {
+                    self.expr(&arg.value);
+                }
+            }
+
+            TypedExpr::BinOp { left, right, .. } | TypedExpr::Pipe { left, right, .. } => {
+                self.expr(left);
+                self.expr(right);
+            }
+
+            TypedExpr::ListCons { head, tail, .. } => {
+                self.expr(head);
+                self.expr(tail);
+            }
+
+            TypedExpr::Tuple { elems, .. } => {
+                for elem in elems {
+                    self.expr(elem);
+                }
+            }
+
+            TypedExpr::TupleIndex { tuple: record, .. }
+            | TypedExpr::RecordAccess { record, .. } => self.expr(record),
+
+            TypedExpr::RecordUpdate {
+                constructor,
+                spread,
+                args,
+                ..
+            } => {
+                self.expr(constructor);
+                self.expr(spread);
+                for arg in args {
+                    self.expr(&arg.value);
+                }
+            }
+
+            TypedExpr::BitString { segments, .. } => {
+                for segment in segments {
+                    self.segment(segment, Self::expr);
+                }
+            }
+
+            TypedExpr::Int { .. }
+            | TypedExpr::Float { .. }
+            | TypedExpr::String { .. }
+            | TypedExpr::ListNil { .. }
+            | TypedExpr::Todo { .. } => (),
+        }
+    }
+
+    fn segment<Value, Type>(
+        &mut self,
+        segment: &BitStringSegment<Value, Type>,
+        walk: fn(&mut Self, &Value),
+    ) {
+        walk(self, &segment.value);
+        for option in segment.options.iter() {
+            if let BitStringSegmentOption::Size { value, .. }
+            | BitStringSegmentOption::Unit { value, .. } = option
+            {
+                walk(self, value);
+            }
+        }
+    }
+
+    fn pattern(&mut self, pattern: &TypedPattern) {
+        match pattern {
+            TypedPattern::Constructor {
+                location,
+                module,
+                name,
+                args,
+                ..
+            } => {
+                if let Some(definition) = self.resolve(module.as_deref(), name, Kind::Value) {
+                    self.add(definition, location);
+                }
+                for arg in args {
+                    self.pattern(&arg.value);
+                }
+            }
+
+            TypedPattern::Let { pattern, .. } => self.pattern(pattern),
+
+            TypedPattern::Cons { head, tail, .. } => {
+                self.pattern(head);
+                self.pattern(tail);
+            }
+
+            TypedPattern::Tuple { elems, .. } => {
+                for elem in elems {
+                    self.pattern(elem);
+                }
+            }
+
+            TypedPattern::BitString { segments, .. } => {
+                for segment in segments {
+                    self.segment(segment, Self::pattern);
+                }
+            }
+
+            TypedPattern::Int { .. }
+            | TypedPattern::Float { .. }
+            | TypedPattern::String { .. }
+            | TypedPattern::Var { .. }
+            | TypedPattern::VarCall { .. }
+            | TypedPattern::Discard { .. }
+            | TypedPattern::Nil { .. } => (),
+        }
+    }
+
+    fn type_ast(&mut self, annotation: &TypeAst) {
+        match annotation {
+            TypeAst::Constructor {
+                location,
+                module,
+                name,
+                args,
+            } => {
+                if let Some(definition) = self.resolve(module.as_deref(), name, Kind::Type) {
+                    self.add(definition, location);
+                }
+                for arg in args {
+                    self.type_ast(arg);
+                }
+            }
+
+            TypeAst::Fn { args, retrn, .. } => {
+                for arg in args {
+                    self.type_ast(arg);
+                }
+                self.type_ast(retrn);
+            }
+
+            TypeAst::Tuple { elems, .. } => {
+                for elem in elems {
+                    self.type_ast(elem);
+                }
+            }
+
+            TypeAst::Var { .. } | TypeAst::Hole { .. } => (),
+        }
+    }
+
+    fn constant(&mut self, constant: &TypedConstant) {
+        match constant {
+            TypedConstant::Record {
+                location,
+                module,
+                name,
+                args,
+                ..
+            } => {
+                if let Some(definition) = self.resolve(module.as_deref(), name, Kind::Value) {
+                    self.add(definition, location);
+                }
+                for arg in args {
+                    self.constant(&arg.value);
+                }
+            }
+
+            TypedConstant::Tuple { elements, .. } | TypedConstant::List { elements, .. } => {
+                for element in elements {
+                    self.constant(element);
+                }
+            }
+
+            TypedConstant::BitString { segments, .. } => {
+                for segment in segments {
+                    self.segment(segment, Self::constant);
+                }
+            }
+
+            TypedConstant::Int { .. }
+            | TypedConstant::Float { .. }
+            | TypedConstant::String { .. } => {}
+        }
+    }
+}
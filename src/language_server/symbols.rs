@@ -0,0 +1,185 @@
+use super::{
+    compiler::{Compiled, Module},
+    range, references,
+};
+use crate::{
+    ast::{SrcSpan, Statement, UntypedStatement},
+    build::package_compiler,
+};
+use lsp_types::{DocumentSymbol, Location, SymbolInformation, SymbolKind, Url};
+
+/// A function, constant, type, or record constructor defined in a module.
+///
+#[derive(Debug, Clone, PartialEq)]
+struct Symbol {
+    name: String,
+    kind: SymbolKind,
+    /// The whole of the definition, including the body of a function and
+    /// the constructors of a type.
+    span: SrcSpan,
+    name_span: SrcSpan,
+    children: Vec<Symbol>,
+}
+
+/// The symbols defined in a module, with the record constructors of each
+/// custom type as its children. The module is parsed rather than type
+/// checked so that the outline is available while it contains type errors.
+///
+#[allow(deprecated)]
+pub fn document_symbols(module: &Module) -> Vec<DocumentSymbol> {
+    fn convert(code: &str, symbol: Symbol) -> DocumentSymbol {
+        DocumentSymbol {
+            name: symbol.name,
+            detail: None,
+            kind: symbol.kind,
+            tags: None,
+            deprecated: None,
+            range: range(code, &symbol.span),
+            selection_range: range(code, &symbol.name_span),
+            children: if symbol.children.is_empty() {
+                None
+            } else {
+                let children = symbol.children.into_iter();
+                Some(children.map(|child| convert(code, child)).collect())
+            },
+        }
+    }
+
+    symbols(module)
+        .into_iter()
+        .map(|symbol| convert(&module.code, symbol))
+        .collect()
+}
+
+/// The symbols defined in the modules of the top level package with a name
+/// containing the query, ignoring case. Each is contained by its module, or
+/// by its custom type for record constructors.
+///
+#[allow(deprecated)]
+pub fn workspace_symbols(compiled: &Compiled, query: &str) -> Vec<SymbolInformation> {
+    let query = query.to_lowercase();
+    let mut found = vec![];
+    for module in compiled.modules.values() {
+        if module.package != compiled.root_package {
+            continue;
+        }
+        let uri = match Url::from_file_path(&module.path) {
+            Ok(uri) => uri,
+            Err(_) => continue,
+        };
+
+        let mut push = |symbol: &Symbol, container: &str| {
+            if symbol.name.to_lowercase().contains(&query) {
+                found.push(SymbolInformation {
+                    name: symbol.name.clone(),
+                    kind: symbol.kind,
+                    tags: None,
+                    deprecated: None,
+                    location: Location {
+                        uri: uri.clone(),
+                        range: range(&module.code, &symbol.span),
+                    },
+                    container_name: Some(container.to_string()),
+                });
+            }
+        };
+        for symbol in symbols(module) {
+            push(&symbol, &module.name);
+            for child in symbol.children.iter() {
+                push(child, &format!("{}.{}", module.name, symbol.name));
+            }
+        }
+    }
+    found.sort_by(|a, b| (&a.name, &a.container_name).cmp(&(&b.name, &b.container_name)));
+    found
+}
+
+fn symbols(module: &Module) -> Vec<Symbol> {
+    match package_compiler::parse_source(&module.code, &module.name, &module.path) {
+        Ok(ast) => ast
+            .statements
+            .iter()
+            .filter_map(|statement| statement_symbol(&module.name, &module.code, statement))
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+fn statement_symbol(module: &str, code: &str, statement: &UntypedStatement) -> Option<Symbol> {
+    let symbol = |name: &str, kind, location: &SrcSpan, span| Symbol {
+        name: name.to_string(),
+        kind,
+        span,
+        name_span: references::reference(module, code, location, name).location,
+        children: vec![],
+    };
+
+    match statement {
+        // The location of a function is only its head, and the body ends
+        // with a closing brace
+        Statement::Fn {
+            name,
+            location,
+            end_location,
+            ..
+        } => {
+            let span = SrcSpan {
+                start: location.start,
+                end: (end_location + 1).min(code.len()),
+            };
+            Some(symbol(name, SymbolKind::Function, location, span))
+        }
+
+        Statement::ExternalFn { name, location, .. } => Some(symbol(
+            name,
+            SymbolKind::Function,
+            location,
+            location.clone(),
+        )),
+
+        Statement::ModuleConstant { name, location, .. } => Some(symbol(
+            name,
+            SymbolKind::Constant,
+            location,
+            location.clone(),
+        )),
+
+        Statement::ExternalType { name, location, .. }
+        | Statement::TypeAlias {
+            alias: name,
+            location,
+            ..
+        } => Some(symbol(name, SymbolKind::Class, location, location.clone())),
+
+        // The location of a custom type is only its head, and the
+        // constructors are followed by a closing brace
+        Statement::CustomType {
+            name,
+            location,
+            constructors,
+            ..
+        } => {
+            let last = constructors
+                .last()
+                .map_or(location.end, |constructor| constructor.location.end);
+            let end = code[last..].find('}').map_or(last, |i| last + i + 1);
+            let span = SrcSpan {
+                start: location.start,
+                end,
+            };
+            Some(Symbol {
+                children: constructors
+                    .iter()
+                    .map(|constructor| {
+                        let location = &constructor.location;
+                        let kind = SymbolKind::Constructor;
+                        symbol(&constructor.name, kind, location, location.clone())
+                    })
+                    .collect(),
+                ..symbol(name, SymbolKind::Class, location, span)
+            })
+        }
+
+        Statement::Import { .. } => None,
+    }
+}
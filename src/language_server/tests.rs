@@ -0,0 +1,1032 @@
+use super::{
+    compiler::{analyse, Source},
+    *,
+};
+use crate::{build::Origin, error::RenameProblem, fs::OutputFile};
+use lsp_types::{CompletionItemKind, SymbolKind};
+
+fn source(package: &str, name: &str, code: &str) -> Source {
+    Source {
+        package: package.to_string(),
+        origin: Origin::Src,
+        name: name.to_string(),
+        path: PathBuf::from(format!("/{}/src/{}.gleam", package, name)),
+        code: code.to_string(),
+    }
+}
+
+fn summary(diagnostic: &Diagnostic) -> (Range, Option<DiagnosticSeverity>, Option<NumberOrString>) {
+    (
+        diagnostic.range,
+        diagnostic.severity,
+        diagnostic.code.clone(),
+    )
+}
+
+fn range_of(start: (u32, u32), end: (u32, u32)) -> Range {
+    Range {
+        start: Position {
+            line: start.0,
+            character: start.1,
+        },
+        end: Position {
+            line: end.0,
+            character: end.1,
+        },
+    }
+}
+
+#[test]
+fn position_test() {
+    let src = "one\ntwo\n";
+    assert_eq!(position(src, 0), Position::new(0, 0));
+    assert_eq!(position(src, 2), Position::new(0, 2));
+    assert_eq!(position(src, 4), Position::new(1, 0));
+    assert_eq!(position(src, 6), Position::new(1, 2));
+    assert_eq!(position(src, 100), Position::new(2, 0));
+
+    // Columns are counted in UTF-16 code units
+    let src = "\"😀\" 1";
+    assert_eq!(position(src, 7), Position::new(0, 5));
+}
+
+#[test]
+fn diagnostics_test() {
+    let compiled = analyse(
+        "app",
+        vec![
+            source("app", "one", "pub fn main() {\n  1 + 1.0\n}\n"),
+            source("app", "two", "pub fn main() {\n  let x = 1\n  2\n}\n"),
+        ],
+    );
+    let (diagnostics, messages) = diagnostics(&compiled);
+    assert_eq!(messages, Vec::<String>::new());
+
+    let one = &diagnostics[&PathBuf::from("/app/src/one.gleam")];
+    assert_eq!(
+        one.iter().map(summary).collect::<Vec<_>>(),
+        vec![(
+            range_of((1, 6), (1, 9)),
+            Some(DiagnosticSeverity::Error),
+            Some(NumberOrString::String("E0013".to_string())),
+        )]
+    );
+    assert_eq!(one[0].source, Some("gleam".to_string()));
+
+    let two = &diagnostics[&PathBuf::from("/app/src/two.gleam")];
+    assert_eq!(
+        two.iter().map(summary).collect::<Vec<_>>(),
+        vec![(
+            range_of((1, 6), (1, 7)),
+            Some(DiagnosticSeverity::Warning),
+            Some(NumberOrString::String("W0008".to_string())),
+        )]
+    );
+}
+
+#[test]
+fn diagnostics_dependency_warnings_test() {
+    // Warnings in dependencies are not reported as they cannot be fixed
+    let compiled = analyse(
+        "app",
+        vec![
+            source("dep", "dep", "pub fn main() {\n  let x = 1\n  2\n}\n"),
+            source(
+                "app",
+                "app",
+                "import dep\npub fn main() {\n  dep.main()\n}\n",
+            ),
+        ],
+    );
+    let (diagnostics, messages) = diagnostics(&compiled);
+    assert_eq!(messages, Vec::<String>::new());
+    assert_eq!(diagnostics, HashMap::new());
+    assert!(compiled.modules["app"].ast.is_some());
+}
+
+#[test]
+fn diagnostics_failed_dependency_test() {
+    // Modules importing a module with errors are not type checked, so only
+    // the original error is reported
+    let compiled = analyse(
+        "app",
+        vec![
+            source("app", "one", "pub fn main() {\n  1 + 1.0\n}\n"),
+            source(
+                "app",
+                "two",
+                "import one\npub fn main() {\n  one.nope()\n}\n",
+            ),
+        ],
+    );
+    let (diagnostics, _) = diagnostics(&compiled);
+    assert_eq!(
+        diagnostics.keys().collect::<Vec<_>>(),
+        vec![&PathBuf::from("/app/src/one.gleam")]
+    );
+    assert!(compiled.modules["two"].ast.is_none());
+}
+
+#[test]
+fn diagnostics_import_cycle_test() {
+    let compiled = analyse(
+        "app",
+        vec![
+            source("app", "one", "import two\n"),
+            source("app", "two", "import one\n"),
+        ],
+    );
+    let (diagnostics, messages) = diagnostics(&compiled);
+    assert_eq!(diagnostics, HashMap::new());
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].starts_with("Import cycle"));
+
+    // The cycle is given as plain text rather than drawn
+    let cycle = messages[0].lines().nth(4);
+    assert!(
+        cycle == Some("one -> two -> one") || cycle == Some("two -> one -> two"),
+        "{}",
+        messages[0]
+    );
+}
+
+#[test]
+fn diagnostics_unknown_import_test() {
+    let compiled = analyse(
+        "app",
+        vec![source("app", "one", "import nope\npub fn main() { 1 }\n")],
+    );
+    let (diagnostics, _) = diagnostics(&compiled);
+    let one = &diagnostics[&PathBuf::from("/app/src/one.gleam")];
+    assert_eq!(one.len(), 1);
+    assert_eq!(one[0].range, range_of((0, 7), (0, 11)));
+    assert!(compiled.modules["one"].ast.is_none());
+}
+
+#[test]
+fn read_sources_test() {
+    let mut root = ProjectRoot::new(
+        std::env::temp_dir().join(format!("gleam_read_sources_test_{}", std::process::id())),
+    );
+    let write = |path: PathBuf, text: &str| {
+        crate::fs::write_output(&OutputFile {
+            path,
+            text: text.to_string(),
+        })
+        .expect("Writing file")
+    };
+    write(root.root.join("gleam.toml"), "name = \"app\"\n");
+    write(root.root.join("src/app.gleam"), "pub fn main() { 1 }\n");
+    write(
+        root.root.join("test/app_test.gleam"),
+        "pub fn main() { 2 }\n",
+    );
+    let lib = root.build_lib_path();
+    write(lib.join("dep/gleam.toml"), "name = \"dep\"\n");
+    write(lib.join("dep/src/dep.gleam"), "pub fn main() { 3 }\n");
+
+    let names = |root: &ProjectRoot| {
+        let (root_name, sources) =
+            compiler::read_sources(root, &HashMap::new()).expect("Reading sources");
+        assert_eq!(root_name, "app");
+        let mut names: Vec<_> = sources
+            .into_iter()
+            .map(|source| (source.package, source.name, source.origin))
+            .collect();
+        names.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        names
+    };
+    let expected = vec![
+        ("app".to_string(), "app".to_string(), Origin::Src),
+        ("app".to_string(), "app_test".to_string(), Origin::Test),
+        ("dep".to_string(), "dep".to_string(), Origin::Src),
+    ];
+
+    // The dependencies of the default profile are used until the project
+    // has been built for the test profile
+    root.profile = Profile::Test;
+    assert_eq!(names(&root), expected);
+
+    // After which only those of the test profile are used
+    write(
+        root.build_lib_path().join("other/gleam.toml"),
+        "name = \"other\"\n",
+    );
+    assert_eq!(names(&root), expected[..2].to_vec());
+
+    crate::fs::delete_dir(&root.root).expect("Deleting project directory");
+}
+
+#[test]
+fn byte_index_test() {
+    let src = "one\n\"😀\" 1\n";
+    assert_eq!(byte_index(src, Position::new(0, 2)), 2);
+    assert_eq!(byte_index(src, Position::new(1, 0)), 4);
+    assert_eq!(byte_index(src, Position::new(1, 4)), 10);
+    assert_eq!(byte_index(src, Position::new(0, 100)), 3);
+    assert_eq!(byte_index(src, Position::new(100, 0)), src.len());
+    for i in &[0, 4, 5, 9, 10, 11] {
+        assert_eq!(byte_index(src, position(src, *i)), *i);
+    }
+}
+
+#[test]
+fn hover_test() {
+    let one = r#"
+/// Adds one
+pub fn add_one(x) { x + 1 }
+
+pub type Box {
+  /// A box
+  Box(inner: Int)
+}
+
+/// The answer
+pub const answer = 42
+"#;
+    let two = r#"
+import one.{Box, add_one as increment}
+
+/// Unwrap a box
+fn unwrap(box: one.Box) {
+  let Box(value) = box
+  value
+}
+
+pub fn main() {
+  let values = [1.0, 2.0]
+  let wrapped = Box(one.answer)
+  one.add_one(increment(unwrap(wrapped)))
+}
+"#;
+    let compiled = analyse(
+        "app",
+        vec![source("app", "one", one), source("app", "two", two)],
+    );
+    assert_eq!(compiled.errors, vec![]);
+    let module = &compiled.modules["two"];
+    let hover = |needle: &str| {
+        let byte_index = two.find(needle).expect("needle") + 1;
+        hover::hover(&compiled, module, byte_index)
+    };
+
+    // Variables, both where they are bound and used
+    assert_eq!(
+        hover("values ="),
+        Some("```gleam\nList(Float)\n```".to_string())
+    );
+    assert_eq!(hover("value\n"), Some("```gleam\nInt\n```".to_string()));
+    assert_eq!(hover("box: one"), Some("```gleam\nBox\n```".to_string()));
+
+    // Functions with documentation, in this module and others
+    assert_eq!(
+        hover("unwrap(wrapped)"),
+        Some("```gleam\nfn(Box) -> Int\n```\n\nUnwrap a box".to_string())
+    );
+    assert_eq!(
+        hover("add_one("),
+        Some("```gleam\nfn(Int) -> Int\n```\n\nAdds one".to_string())
+    );
+    assert_eq!(
+        hover("answer"),
+        Some("```gleam\nInt\n```\n\nThe answer".to_string())
+    );
+
+    // Function definitions
+    assert_eq!(
+        hover("fn unwrap"),
+        Some("```gleam\nfn(Box) -> Int\n```\n\nUnwrap a box".to_string())
+    );
+
+    // Unqualified imports
+    assert_eq!(
+        hover("Box(one"),
+        Some("```gleam\nfn(Int) -> Box\n```\n\nA box".to_string())
+    );
+    assert_eq!(
+        hover("increment("),
+        Some("```gleam\nfn(Int) -> Int\n```\n\nAdds one".to_string())
+    );
+
+    // The cursor just after the end of a name, as it is after typing it
+    let hover_end = |needle: &str, name: &str| {
+        let byte_index = two.find(needle).expect("needle") + name.len();
+        hover::hover(&compiled, module, byte_index)
+    };
+    assert_eq!(
+        hover_end("wrapped)))", "wrapped"),
+        Some("```gleam\nBox\n```".to_string())
+    );
+    assert_eq!(
+        hover_end("increment(", "increment"),
+        Some("```gleam\nfn(Int) -> Int\n```\n\nAdds one".to_string())
+    );
+
+    // Whitespace
+    assert_eq!(hover("\n\n/// Unwrap"), None);
+}
+
+#[test]
+fn definition_test() {
+    let dep = "pub fn dep_fn() { 1 }\n";
+    let one = r#"pub fn add_one(x) { x + 1 }
+
+pub type Box {
+  Box(inner: Int)
+}
+"#;
+    let two = r#"import dep
+import one.{Box, add_one as increment}
+
+fn unwrap(box: one.Box) -> Int {
+  let Box(value) = box
+  value
+}
+
+pub fn main() {
+  let result = Ok(Box(dep.dep_fn()))
+  let wrapped = one.Box(1)
+  one.add_one(increment(unwrap(wrapped)))
+}
+
+fn rewrap(box: Box) -> Box {
+  box
+}
+"#;
+    let compiled = analyse(
+        "app",
+        vec![
+            source("dep", "dep", dep),
+            source("app", "one", one),
+            source("app", "two", two),
+        ],
+    );
+    assert_eq!(compiled.errors, vec![]);
+    let module = &compiled.modules["two"];
+    let definition = |needle: &str| {
+        let byte_index = two.find(needle).expect("needle") + 1;
+        definition::definition(&compiled, module, byte_index).map(|(module, span)| {
+            (
+                module.path.to_str().unwrap().to_string(),
+                module.code[span.start..span.end].to_string(),
+            )
+        })
+    };
+    let expected = |path: &str, code: &str| Some((path.to_string(), code.to_string()));
+
+    // Local variables
+    assert_eq!(
+        definition("value\n"),
+        expected("/app/src/two.gleam", "value")
+    );
+    assert_eq!(
+        definition("box\n  value"),
+        expected("/app/src/two.gleam", "box: one.Box")
+    );
+
+    // Functions in this module, other modules, and other packages
+    assert_eq!(
+        definition("unwrap(wrapped)"),
+        expected("/app/src/two.gleam", "fn unwrap(box: one.Box) -> Int")
+    );
+    assert_eq!(
+        definition("add_one(increment"),
+        expected("/app/src/one.gleam", "pub fn add_one(x)")
+    );
+    assert_eq!(
+        definition("increment(unwrap"),
+        expected("/app/src/one.gleam", "pub fn add_one(x)")
+    );
+    assert_eq!(
+        definition("dep_fn()"),
+        expected("/dep/src/dep.gleam", "pub fn dep_fn()")
+    );
+
+    // Record constructors, in expressions and patterns
+    assert_eq!(
+        definition("Box(dep"),
+        expected("/app/src/one.gleam", "Box(inner: Int)")
+    );
+    assert_eq!(
+        definition("Box(1)"),
+        expected("/app/src/one.gleam", "Box(inner: Int)")
+    );
+    assert_eq!(
+        definition("Box(value)"),
+        expected("/app/src/one.gleam", "Box(inner: Int)")
+    );
+
+    // Type annotations, qualified and unqualified
+    assert_eq!(
+        definition("Box) -> Int"),
+        expected("/app/src/one.gleam", "pub type Box ")
+    );
+    assert_eq!(
+        definition("Box) -> Box"),
+        expected("/app/src/one.gleam", "pub type Box ")
+    );
+
+    // Imports
+    assert_eq!(
+        definition("add_one as"),
+        expected("/app/src/one.gleam", "pub fn add_one(x)")
+    );
+    assert_eq!(
+        definition("Box, add_one"),
+        expected("/app/src/one.gleam", "Box(inner: Int)")
+    );
+    assert_eq!(definition("dep\n"), expected("/dep/src/dep.gleam", ""));
+
+    // Values from the prelude have no definition
+    assert_eq!(definition("Ok("), None);
+
+    // The cursor may be just after the end of a name
+    let byte_index = two.find("unwrap(wrapped").expect("needle") + "unwrap".len();
+    assert_eq!(
+        definition::definition(&compiled, module, byte_index)
+            .map(|(module, span)| module.code[span.start..span.end].to_string()),
+        Some("fn unwrap(box: one.Box) -> Int".to_string())
+    );
+}
+
+#[test]
+fn completion_test() {
+    let one = r#"
+/// Adds one
+pub fn add_one(x) { x + 1 }
+
+pub type Box {
+  Box(inner: Int, label: String)
+}
+
+pub const answer = 42
+"#;
+    let two = r#"
+import one.{add_one as increment}
+
+type Pair {
+  Pair(first: Int, second: Int)
+}
+
+fn sum(pair: Pair) {
+  pair.first + pair.second
+}
+
+pub fn main(argument) {
+  let box = one.Box(1, "one")
+  let total = box.inner
+  case [total] {
+    [head] as list -> head
+    _ -> increment(argument)
+  }
+}
+"#;
+    let compiled = analyse(
+        "app",
+        vec![source("app", "one", one), source("app", "two", two)],
+    );
+    assert_eq!(compiled.errors, vec![]);
+    let module = &compiled.modules["two"];
+    let labels = |needle: &str| {
+        let byte_index = two.find(needle).expect("needle") + needle.len();
+        completion::completion(&compiled, module, byte_index)
+            .into_iter()
+            .map(|item| item.label)
+            .collect::<Vec<_>>()
+    };
+
+    // Module members
+    assert_eq!(
+        labels("box = one."),
+        vec!["Box", "Box", "add_one", "answer"]
+    );
+    let items = completion::completion(&compiled, module, two.find("one.Box").unwrap() + 4);
+    let add_one = items.iter().find(|item| item.label == "add_one").unwrap();
+    assert_eq!(add_one.kind, Some(CompletionItemKind::Function));
+    assert_eq!(add_one.detail, Some("fn(Int) -> Int".to_string()));
+
+    // Record fields, of public types in other modules and private types in
+    // this one
+    assert_eq!(labels("box."), vec!["inner", "label"]);
+    assert_eq!(labels("pair.f"), vec!["first", "second"]);
+
+    // Variables in scope, and the values and modules available to the module
+    assert_eq!(
+        labels("[head] as list -> "),
+        vec![
+            "Pair",
+            "argument",
+            "box",
+            "head",
+            "increment",
+            "list",
+            "main",
+            "one",
+            "sum",
+            "total"
+        ]
+    );
+    assert_eq!(
+        labels("_ -> "),
+        vec![
+            "Pair",
+            "argument",
+            "box",
+            "increment",
+            "main",
+            "one",
+            "sum",
+            "total"
+        ]
+    );
+}
+
+#[test]
+fn references_test() {
+    let one = r#"pub fn add_one(x) { x + 1 }
+
+pub type Box {
+  Box(inner: Int)
+}
+
+pub const boxed = Box(1)
+"#;
+    let two = r#"import one.{Box, add_one as increment}
+import one as other
+
+fn unwrap(box: other.Box) -> Int {
+  let Box(value) = box
+  one.add_one(value)
+}
+
+pub fn main() {
+  let boxed = other.boxed
+  let wrapped = Box(..boxed, inner: 2)
+  increment(unwrap(wrapped))
+}
+"#;
+    let compiled = analyse(
+        "app",
+        vec![source("app", "one", one), source("app", "two", two)],
+    );
+    assert_eq!(compiled.errors, vec![]);
+    let index = references::Index::new(&compiled);
+    let references = |module: &str, code: &str, needle: &str| {
+        let byte_index = code.find(needle).expect("needle") + 1;
+        let definition = index.definition_at(module, byte_index).expect("definition");
+        let entry = index.get(definition).unwrap();
+        let mut references: Vec<_> = entry
+            .references
+            .iter()
+            .map(|reference| {
+                let code = &compiled.modules[&reference.module].code;
+                let text = &code[reference.location.start..reference.location.end];
+                (reference.module.as_str(), text, reference.named)
+            })
+            .collect();
+        references.sort_unstable();
+        (entry.name.as_str(), references)
+    };
+
+    // Functions referred to by aliases are not named at their location
+    assert_eq!(
+        references("one", one, "add_one"),
+        (
+            "add_one",
+            vec![
+                ("two", "add_one", true),
+                ("two", "add_one", true),
+                ("two", "increment", false)
+            ]
+        )
+    );
+
+    // Record constructors in constants, patterns, and record updates
+    assert_eq!(
+        references("two", two, "Box(value)"),
+        (
+            "Box",
+            vec![
+                ("one", "Box", true),
+                ("two", "Box", true),
+                ("two", "Box", true),
+                ("two", "Box", true)
+            ]
+        )
+    );
+
+    // Types, and references through module aliases
+    assert_eq!(
+        references("one", one, " Box {"),
+        ("Box", vec![("two", "Box", true), ("two", "Box", true)])
+    );
+    assert_eq!(
+        references("two", two, "boxed\n"),
+        ("boxed", vec![("two", "boxed", true)])
+    );
+
+    // Local variables are not in the index
+    assert_eq!(
+        index.definition_at("two", two.find("value)").unwrap()),
+        None
+    );
+
+    // The cursor may be just after the end of a name
+    let byte_index = two.find("unwrap(wrapped").unwrap() + "unwrap".len();
+    let definition = index.definition_at("two", byte_index).expect("definition");
+    assert_eq!(index.get(definition).unwrap().name, "unwrap");
+}
+
+#[test]
+fn rename_test() {
+    let one = r#"pub fn add_one(x) { x + 1 }
+
+pub fn two(plus_one) { add_one(plus_one) }
+"#;
+    let two = r#"import one.{add_one as increment}
+
+pub fn main() {
+  one.add_one(increment(1))
+}
+"#;
+    let compiled = analyse(
+        "app",
+        vec![
+            source("app", "one", one),
+            source("app", "two", two),
+            source("dep", "dep", "pub fn dep() { 1 }\n"),
+        ],
+    );
+    assert_eq!(compiled.errors, vec![]);
+    let index = references::Index::new(&compiled);
+    let rename = |module: &str, name: &str, new_name: &str| {
+        let definitions = index.find(module, name);
+        index
+            .rename(&compiled, &definitions, new_name)
+            .map(|edits| {
+                let mut renamed: Vec<_> = edits
+                    .into_iter()
+                    .map(|(module, spans)| {
+                        let code = &compiled.modules[&module].code;
+                        (module, references::apply(code, &spans, new_name))
+                    })
+                    .collect();
+                renamed.sort();
+                renamed
+            })
+            .map_err(|error| match error {
+                Error::Rename { problem, .. } => problem,
+                error => panic!("unexpected error {:?}", error),
+            })
+    };
+
+    // Aliases are kept, so code using them is unchanged
+    assert_eq!(
+        rename("one", "add_one", "inc"),
+        Ok(vec![
+            (
+                "one".to_string(),
+                "pub fn inc(x) { x + 1 }\n\npub fn two(plus_one) { inc(plus_one) }\n".to_string()
+            ),
+            (
+                "two".to_string(),
+                "import one.{inc as increment}\n\npub fn main() {\n  one.inc(increment(1))\n}\n"
+                    .to_string()
+            ),
+        ])
+    );
+
+    // The new name would clash with a function that already exists
+    assert_eq!(
+        rename("one", "add_one", "two"),
+        Err(RenameProblem::ChangesMeaning)
+    );
+
+    // The new name would be shadowed by a variable where it is used
+    assert_eq!(
+        rename("one", "add_one", "plus_one"),
+        Err(RenameProblem::ChangesMeaning)
+    );
+
+    assert_eq!(
+        rename("one", "add_one", "AddOne"),
+        Err(RenameProblem::InvalidName)
+    );
+    assert_eq!(
+        rename("dep", "dep", "other"),
+        Err(RenameProblem::Dependency)
+    );
+}
+
+#[test]
+fn document_symbols_test() {
+    let code = r#"import other
+
+pub fn main() {
+  1
+}
+
+pub type Box(a) {
+  Box(inner: a)
+  Empty
+}
+
+type Id =
+  Int
+
+const answer = 42
+
+external fn halt(Int) -> Nil = "erlang" "halt"
+
+external type Pid
+"#;
+    // Type errors do not stop the outline from being shown
+    let compiled = analyse("app", vec![source("app", "one", code)]);
+    assert_ne!(compiled.errors, vec![]);
+    let symbols = symbols::document_symbols(&compiled.modules["one"]);
+
+    let text = |range: &Range| {
+        let start = byte_index(code, range.start);
+        let end = byte_index(code, range.end);
+        &code[start..end]
+    };
+    let summary: Vec<_> = symbols
+        .iter()
+        .map(|symbol| {
+            (
+                symbol.name.as_str(),
+                symbol.kind,
+                text(&symbol.range),
+                text(&symbol.selection_range),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "main",
+                SymbolKind::Function,
+                "pub fn main() {\n  1\n}",
+                "main"
+            ),
+            (
+                "Box",
+                SymbolKind::Class,
+                "pub type Box(a) {\n  Box(inner: a)\n  Empty\n}",
+                "Box"
+            ),
+            ("Id", SymbolKind::Class, "type Id =\n  Int", "Id"),
+            (
+                "answer",
+                SymbolKind::Constant,
+                "const answer = 42",
+                "answer"
+            ),
+            (
+                "halt",
+                SymbolKind::Function,
+                "external fn halt(Int) -> Nil = \"erlang\" \"halt\"",
+                "halt"
+            ),
+            ("Pid", SymbolKind::Class, "external type Pid", "Pid"),
+        ]
+    );
+
+    let children: Vec<_> = symbols[1]
+        .children
+        .iter()
+        .flatten()
+        .map(|child| (child.name.as_str(), child.kind, text(&child.range)))
+        .collect();
+    assert_eq!(
+        children,
+        vec![
+            ("Box", SymbolKind::Constructor, "Box(inner: a)"),
+            ("Empty", SymbolKind::Constructor, "Empty"),
+        ]
+    );
+}
+
+#[test]
+fn workspace_symbols_test() {
+    let compiled = analyse(
+        "app",
+        vec![
+            source("app", "one", "pub fn make_box() { 1 }\n"),
+            source("app", "two", "pub type Box {\n  Box\n}\n"),
+            source("dep", "dep", "pub fn boxed() { 1 }\n"),
+        ],
+    );
+    let symbols = symbols::workspace_symbols(&compiled, "BOX");
+    let summary: Vec<_> = symbols
+        .iter()
+        .map(|symbol| (symbol.name.as_str(), symbol.container_name.as_deref()))
+        .collect();
+
+    // Symbols in dependencies are not included
+    assert_eq!(
+        summary,
+        vec![
+            ("Box", Some("two")),
+            ("Box", Some("two.Box")),
+            ("make_box", Some("one")),
+        ]
+    );
+}
+
+#[test]
+fn formatting_test() {
+    let code = "pub fn one(){1}\n";
+    let edits = formatting::format_document(code).unwrap();
+    assert_eq!(
+        edits,
+        vec![TextEdit {
+            range: range_of((0, 0), (1, 0)),
+            new_text: "pub fn one() {\n  1\n}\n".to_string(),
+        }]
+    );
+
+    // Formatted code needs no edits, and code that does not parse is left
+    assert_eq!(
+        formatting::format_document("pub fn one() {\n  1\n}\n"),
+        Some(vec![])
+    );
+    assert_eq!(formatting::format_document("pub fn one( {"), None);
+}
+
+#[test]
+fn range_formatting_test() {
+    let code = "import   one\n\n// About two\n/// Two\npub fn two(  ) { 1 }\n\n// About three\nfn three(){2}\n";
+    let selection = |needle: &str| {
+        let start = code.find(needle).unwrap();
+        SrcSpan {
+            start,
+            end: start + needle.len(),
+        }
+    };
+
+    // Only statements within the selection are formatted, along with the
+    // comments before them
+    let edits = formatting::format_range(code, &selection("{ 1 }")).unwrap();
+    assert_eq!(
+        edits,
+        vec![TextEdit {
+            range: range_of((2, 0), (4, 20)),
+            new_text: "// About two\n/// Two\npub fn two() {\n  1\n}".to_string(),
+        }]
+    );
+
+    let edits = formatting::format_range(code, &selection("one\n\n// About two")).unwrap();
+    assert_eq!(
+        edits,
+        vec![
+            TextEdit {
+                range: range_of((0, 0), (0, 12)),
+                new_text: "import one".to_string(),
+            },
+            TextEdit {
+                range: range_of((2, 0), (4, 20)),
+                new_text: "// About two\n/// Two\npub fn two() {\n  1\n}".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn missing_clauses_test() {
+    let two = "type Shape {
+  Circle
+  Square(Int)
+}
+
+fn area(shape) {
+  case shape {
+    Square(size) -> size
+  }
+}
+";
+    let compiled = analyse(
+        "app",
+        vec![
+            source(
+                "app",
+                "one",
+                "pub type Pet {\n  Cat(name: String, lives: Int)\n  Dog(name: String)\n  Fish\n}\n",
+            ),
+            source("app", "two", two),
+            source(
+                "app",
+                "three",
+                "import one.{Fish}
+
+pub fn greet(pet) {
+  case pet {
+    one.Dog(name: \"Rex\") -> 1
+    one.Cat(..) -> 2
+  }
+}
+",
+            ),
+        ],
+    );
+    let missing = |name: &str, needle: &str| {
+        let module = &compiled.modules[name];
+        let start = module.code.find(needle).unwrap();
+        let selection = SrcSpan { start, end: start };
+        code_action::missing_clauses(&compiled, module, &selection).map(|missing| {
+            let mut code = module.code.clone();
+            code.insert_str(missing.insert_at, &missing.code);
+            code
+        })
+    };
+
+    // Clauses are added for constructors of a private type in the module
+    assert_eq!(
+        missing("two", "case").unwrap(),
+        "type Shape {
+  Circle
+  Square(Int)
+}
+
+fn area(shape) {
+  case shape {
+    Square(size) -> size
+    Circle -> todo
+  }
+}
+"
+    );
+
+    // The labels of imported constructors are used, and constructors are
+    // referred to as they are imported
+    assert_eq!(
+        missing("three", "pet {").unwrap(),
+        "import one.{Fish}
+
+pub fn greet(pet) {
+  case pet {
+    one.Dog(name: \"Rex\") -> 1
+    one.Cat(..) -> 2
+    one.Dog(name: name) -> todo
+    Fish -> todo
+  }
+}
+"
+    );
+
+    // Nothing is offered away from an inexhaustive case
+    assert_eq!(missing("three", "greet"), None);
+
+    // Types from the prelude
+    let four = "pub fn get(result) {
+  case result {
+    Ok(value) -> value
+  }
+}
+
+pub fn check(flag) {
+  case flag {
+    True -> 1
+  }
+}
+";
+    let compiled = analyse("app", vec![source("app", "four", four)]);
+    let module = &compiled.modules["four"];
+    let missing = |needle: &str| {
+        let start = module.code.find(needle).unwrap();
+        let selection = SrcSpan { start, end: start };
+        code_action::missing_clauses(&compiled, module, &selection)
+            .map(|missing| (missing.insert_at, missing.code))
+    };
+    let after = |needle: &str| module.code.find(needle).unwrap() + needle.len();
+    assert_eq!(
+        missing("case result"),
+        Some((after("-> value"), "\n    Error(_) -> todo".to_string()))
+    );
+    assert_eq!(
+        missing("case flag"),
+        Some((after("True -> 1"), "\n    False -> todo".to_string()))
+    );
+
+    // Later cases of a function are only found to be inexhaustive once the
+    // earlier ones have a catch all clause
+    let five = "pub fn get(result, flag) {
+  case flag {
+    True -> 1
+  }
+  case result {
+    Ok(value) -> value
+  }
+}
+";
+    let compiled = analyse("app", vec![source("app", "five", five)]);
+    let module = &compiled.modules["five"];
+    let start = module.code.find("case flag").unwrap();
+    let selection = SrcSpan { start, end: start };
+    let missing = code_action::missing_clauses(&compiled, module, &selection).unwrap();
+    assert_eq!(
+        (missing.insert_at, missing.code.as_str()),
+        (five.find("True -> 1").unwrap() + 9, "\n    False -> todo")
+    );
+}
@@ -5,17 +5,21 @@ mod bit_string;
 mod build;
 mod cli;
 mod config;
+mod deps;
 mod diagnostic;
 mod docs;
 mod erl;
 mod error;
 mod eunit;
+mod explain;
 mod format;
 mod fs;
+mod language_server;
 mod new;
 mod parser;
 mod pretty;
 mod project;
+mod rename;
 mod shell;
 mod typ;
 mod warning;
@@ -41,7 +45,7 @@ extern crate lalrpop_util;
 #[macro_use]
 extern crate lazy_static;
 
-use crate::error::Error;
+use crate::{diagnostic::DiagnosticFormat, error::Error};
 use std::path::PathBuf;
 use structopt::clap::AppSettings;
 use structopt::StructOpt;
@@ -56,11 +60,41 @@ enum Command {
     Build {
         #[structopt(help = "location of the project root", default_value = ".")]
         project_root: String,
+
+        #[structopt(
+            long = "diagnostics-format",
+            help = "the format to print errors and warnings in",
+            possible_values = &DiagnosticFormat::VARIANTS,
+            case_insensitive = true,
+            default_value = "human"
+        )]
+        diagnostics_format: DiagnosticFormat,
+
+        #[structopt(
+            help = "fail the build if any warnings are emitted",
+            long = "warnings-as-errors"
+        )]
+        warnings_as_errors: bool,
     },
 
+    #[structopt(name = "deps", about = "Work with the dependencies of a project")]
+    Deps(Deps),
+
     #[structopt(name = "docs", about = "Render HTML documentation for a project")]
     Docs(Docs),
 
+    #[structopt(
+        name = "explain",
+        about = "Explain an error or warning code in more detail"
+    )]
+    Explain {
+        #[structopt(help = "the code to explain, as in E0013")]
+        code: String,
+    },
+
+    #[structopt(name = "lsp", about = "Run the language server")]
+    Lsp,
+
     #[structopt(name = "new", about = "Create a new project")]
     New {
         #[structopt(help = "name of the project")]
@@ -94,6 +128,34 @@ enum Command {
             long = "check"
         )]
         check: bool,
+
+        #[structopt(
+            long = "diagnostics-format",
+            help = "the format to print errors and warnings in",
+            possible_values = &DiagnosticFormat::VARIANTS,
+            case_insensitive = true,
+            default_value = "human"
+        )]
+        diagnostics_format: DiagnosticFormat,
+    },
+
+    #[structopt(
+        name = "rename",
+        about = "Rename a function, constant, record constructor, or type"
+    )]
+    Rename {
+        #[structopt(help = "the name to change, as in my_app/user.create")]
+        target: String,
+
+        #[structopt(help = "the new name")]
+        new_name: String,
+
+        #[structopt(
+            long = "project-root",
+            help = "location of the project root",
+            default_value = "."
+        )]
+        project_root: String,
     },
 
     #[structopt(
@@ -117,6 +179,31 @@ enum Command {
     },
 }
 
+#[derive(StructOpt, Debug)]
+enum Deps {
+    #[structopt(name = "download", about = "Download the dependencies from Hex")]
+    Download {
+        #[structopt(help = "location of the project root", default_value = ".")]
+        project_root: String,
+    },
+
+    #[structopt(
+        name = "update",
+        about = "Update the versions of dependencies locked by manifest.toml"
+    )]
+    Update {
+        #[structopt(help = "the packages to update, or all of them if none are given")]
+        packages: Vec<String>,
+
+        #[structopt(
+            long = "project-root",
+            help = "location of the project root",
+            default_value = "."
+        )]
+        project_root: String,
+    },
+}
+
 #[derive(StructOpt, Debug)]
 enum Docs {
     #[structopt(name = "build", about = "Render HTML docs locally")]
@@ -124,6 +211,15 @@ enum Docs {
         #[structopt(help = "location of the project root", default_value = ".")]
         project_root: String,
 
+        #[structopt(
+            long = "diagnostics-format",
+            help = "the format to print errors and warnings in",
+            possible_values = &DiagnosticFormat::VARIANTS,
+            case_insensitive = true,
+            default_value = "human"
+        )]
+        diagnostics_format: DiagnosticFormat,
+
         #[structopt(help = "the directory to write the docs to", long = "to")]
         to: Option<String>,
     },
@@ -147,13 +243,46 @@ enum Docs {
     },
 }
 
+impl Command {
+    fn diagnostics_format(&self) -> DiagnosticFormat {
+        match self {
+            Command::Build {
+                diagnostics_format, ..
+            }
+            | Command::Format {
+                diagnostics_format, ..
+            }
+            | Command::Docs(Docs::Build {
+                diagnostics_format, ..
+            }) => *diagnostics_format,
+            _ => DiagnosticFormat::Human,
+        }
+    }
+}
+
 fn main() {
     initialise_logger();
 
-    let result = match Command::from_args() {
-        Command::Build { project_root } => command_build(project_root),
+    let command = Command::from_args();
+    let diagnostics_format = command.diagnostics_format();
+
+    let result = match command {
+        Command::Build {
+            project_root,
+            warnings_as_errors,
+            ..
+        } => command_build(project_root, diagnostics_format, warnings_as_errors),
+
+        Command::Deps(Deps::Download { project_root }) => deps::download(project_root),
 
-        Command::Docs(Docs::Build { project_root, to }) => docs::command::build(project_root, to),
+        Command::Deps(Deps::Update {
+            packages,
+            project_root,
+        }) => deps::update(project_root, packages),
+
+        Command::Docs(Docs::Build {
+            project_root, to, ..
+        }) => docs::command::build(project_root, to),
 
         Command::Docs(Docs::Publish {
             project_root,
@@ -162,12 +291,17 @@ fn main() {
 
         Command::Docs(Docs::Remove { package, version }) => docs::command::remove(package, version),
 
+        Command::Explain { code } => explain::command(code),
+
         Command::Format {
             stdin,
             files,
             check,
+            ..
         } => format::command::run(stdin, check, files),
 
+        Command::Lsp => language_server::main(),
+
         Command::New {
             name,
             description,
@@ -175,6 +309,12 @@ fn main() {
             template,
         } => new::create(template, name, description, project_root, VERSION),
 
+        Command::Rename {
+            target,
+            new_name,
+            project_root,
+        } => rename::command(project_root, target, new_name),
+
         Command::Shell { project_root } => shell::command(project_root),
 
         Command::Eunit { project_root } => eunit::command(project_root),
@@ -186,33 +326,44 @@ fn main() {
         }
         Err(error) => {
             tracing::error!(error = ?error, "Failed");
-            error.pretty_print();
+            error.print(diagnostics_format);
             std::process::exit(1);
         }
     }
 }
 
-fn command_build(root: String) -> Result<(), Error> {
+fn command_build(
+    root: String,
+    diagnostics_format: DiagnosticFormat,
+    warnings_as_errors: bool,
+) -> Result<(), Error> {
     let root = PathBuf::from(&root);
-    let config = config::read_project_config(&root)?;
+    let mut config = config::read_project_config(&root)?;
+    config.warnings_as_errors |= warnings_as_errors;
 
     // Use new build tool
     if config.tool == config::BuildTool::Gleam {
-        return build::main(config, root).map(|_| ());
+        return build::main(config, root, build::Profile::Default, diagnostics_format).map(|_| ());
     }
 
     // Read and type check project
     let (_config, analysed) = project::read_and_analyse(&root)?;
 
+    // Print warnings
+    warning::print_all(analysed.as_slice(), diagnostics_format);
+    let warnings_count = analysed.iter().map(|a| a.warnings.len()).sum();
+    if config.warnings_as_errors && warnings_count > 0 {
+        return Err(Error::WarningsAsErrors {
+            count: warnings_count,
+        });
+    }
+
     // Generate Erlang code
     let output_files = erl::generate_erlang(analysed.as_slice());
 
     // Reset output directory
     fs::delete_dir(&root.join(project::OUTPUT_DIR_NAME))?;
 
-    // Print warnings
-    warning::print_all(analysed.as_slice());
-
     // Delete the gen directory before generating the newly compiled files
     fs::write_outputs(output_files.as_slice())?;
 
@@ -226,5 +377,7 @@ fn initialise_logger() {
         .with_env_filter(&std::env::var("GLEAM_LOG").unwrap_or_else(|_| "off".to_string()))
         .with_target(false)
         .without_time()
+        // Standard out is used by the language server to talk to the editor
+        .with_writer(std::io::stderr)
         .init();
 }
@@ -11,7 +11,7 @@ use crate::{
     warning::Warning,
 };
 use source_tree::SourceTree;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 pub const OUTPUT_DIR_NAME: &str = "gen";
@@ -103,6 +103,11 @@ pub fn analysed(inputs: Vec<Input>) -> Result<Vec<Analysed>, Error> {
     let mut modules_type_infos = HashMap::new();
     let mut compiled_modules = Vec::with_capacity(module_count);
     let mut uid = 0;
+    let mut errors = vec![];
+
+    // Modules that could not be analysed, either because they contain errors
+    // or because they import a module that could not be analysed
+    let mut failed = HashSet::new();
 
     struct Out {
         source_base_path: PathBuf,
@@ -124,6 +129,17 @@ pub fn analysed(inputs: Vec<Input>) -> Result<Vec<Analysed>, Error> {
         let name = module.name.clone();
         let name_string = module.name_string();
 
+        // The imports of a module that depends on a failed module cannot be
+        // resolved, so it is skipped rather than reporting further errors
+        if module
+            .dependencies()
+            .iter()
+            .any(|(dep, _)| failed.contains(dep))
+        {
+            let _ = failed.insert(name_string);
+            continue;
+        }
+
         println!("Compiling {}", name_string.as_str());
 
         let mut warnings = vec![];
@@ -137,11 +153,18 @@ pub fn analysed(inputs: Vec<Input>) -> Result<Vec<Analysed>, Error> {
             })
             .collect();
 
-        let ast = result.map_err(|error| Error::Type {
-            path: path.clone(),
-            src: src.clone(),
-            error,
-        })?;
+        let ast = match result {
+            Ok(ast) => ast,
+            Err(type_errors) => {
+                errors.push(Error::Type {
+                    path: path.clone(),
+                    src: src.clone(),
+                    errors: type_errors,
+                });
+                let _ = failed.insert(name_string);
+                continue;
+            }
+        };
 
         modules_type_infos.insert(
             name_string.clone(),
@@ -158,6 +181,10 @@ pub fn analysed(inputs: Vec<Input>) -> Result<Vec<Analysed>, Error> {
         });
     }
 
+    if let Some(error) = Error::from_errors(errors) {
+        return Err(error);
+    }
+
     Ok(compiled_modules
         .into_iter()
         .map(|out| {
@@ -398,8 +398,8 @@ call_thing() ->\n    thing:new().\n"
                     path: PathBuf::from("/src/two.gleam"),
                     source_base_path: PathBuf::from("/src"),
                     src: "import one
-                        fn make() { one.Point(1, 4) }
-                        fn x(p) { let one.Point(x, _) = p x }"
+                        pub fn make() { one.Point(1, 4) }
+                        pub fn x(p) { let one.Point(x, _) = p x }"
                         .to_string(),
                 },
             ],
@@ -414,7 +414,7 @@ call_thing() ->\n    thing:new().\n"
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
-                    text: "-module(two).\n-compile(no_auto_import).\n
+                    text: "-module(two).\n-compile(no_auto_import).\n\n-export([make/0, x/1]).\n
 make() ->\n    {point, 1, 4}.\n
 x(P) ->\n    {point, X, _} = P,\n    X.\n"
                         .to_string(),
@@ -435,7 +435,7 @@ x(P) ->\n    {point, X, _} = P,\n    X.\n"
                     path: PathBuf::from("/src/two.gleam"),
                     source_base_path: PathBuf::from("/src"),
                     src: "import one.{div}
-                    fn run() { 2 |> div(top: _, bottom: 4) |> div(2, bottom: _) }"
+                    pub fn run() { 2 |> div(top: _, bottom: 4) |> div(2, bottom: _) }"
                         .to_string(),
                 },
             ],
@@ -448,7 +448,7 @@ x(P) ->\n    {point, X, _} = P,\n    X.\n"
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
-                    text: "-module(two).\n-compile(no_auto_import).\n
+                    text: "-module(two).\n-compile(no_auto_import).\n\n-export([run/0]).\n
 run() ->\n    one:'div'(2, one:'div'(2, 4)).\n"
                         .to_string(),
                 },
@@ -468,7 +468,7 @@ run() ->\n    one:'div'(2, one:'div'(2, 4)).\n"
                     path: PathBuf::from("/src/two.gleam"),
                     source_base_path: PathBuf::from("/src"),
                     src: "import one
-                        fn make() { one.Empty }"
+                        pub fn make() { one.Empty }"
                         .to_string(),
                 },
             ],
@@ -479,7 +479,7 @@ run() ->\n    one:'div'(2, one:'div'(2, 4)).\n"
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
-                    text: "-module(two).\n-compile(no_auto_import).\n
+                    text: "-module(two).\n-compile(no_auto_import).\n\n-export([make/0]).\n
 make() ->\n    empty.\n"
                         .to_string(),
                 },
@@ -498,7 +498,7 @@ make() ->\n    empty.\n"
                     origin: ModuleOrigin::Src,
                     path: PathBuf::from("/src/two.gleam"),
                     source_base_path: PathBuf::from("/src"),
-                    src: "import one.{Empty, id} fn make() { id(Empty) }".to_string(),
+                    src: "import one.{Empty, id} pub fn make() { id(Empty) }".to_string(),
                 },
             ],
             expected: Ok(vec![
@@ -510,7 +510,7 @@ id(X) ->\n    X.\n"
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
-                    text: "-module(two).\n-compile(no_auto_import).\n
+                    text: "-module(two).\n-compile(no_auto_import).\n\n-export([make/0]).\n
 make() ->
     one:id(empty).\n"
                         .to_string(),
@@ -531,7 +531,7 @@ make() ->
                     origin: ModuleOrigin::Src,
                     path: PathBuf::from("/src/two.gleam"),
                     source_base_path: PathBuf::from("/src"),
-                    src: "import one.{Empty as e, id as i} fn make() { i(e) }".to_string(),
+                    src: "import one.{Empty as e, id as i} pub fn make() { i(e) }".to_string(),
                 },
             ],
             expected: Ok(vec![
@@ -544,7 +544,7 @@ id(X) ->\n    X.\n"
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
-                    text: "-module(two).\n-compile(no_auto_import).\n
+                    text: "-module(two).\n-compile(no_auto_import).\n\n-export([make/0]).\n
 make() ->\n    one:id(empty).\n"
                         .to_string(),
                 },
@@ -563,7 +563,7 @@ make() ->\n    one:id(empty).\n"
                     origin: ModuleOrigin::Src,
                     path: PathBuf::from("/src/two.gleam"),
                     source_base_path: PathBuf::from("/src"),
-                    src: "import one fn funky() { one.receive }".to_string(),
+                    src: "import one pub fn funky() { one.receive }".to_string(),
                 },
             ],
             expected: Ok(vec![
@@ -576,7 +576,7 @@ make() ->\n    one:id(empty).\n"
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
-                    text: "-module(two).\n-compile(no_auto_import).\n
+                    text: "-module(two).\n-compile(no_auto_import).\n\n-export([funky/0]).\n
 funky() ->
     fun one:'receive'/0.\n"
                         .to_string(),
@@ -596,7 +596,7 @@ funky() ->
                     origin: ModuleOrigin::Src,
                     path: PathBuf::from("/src/two.gleam"),
                     source_base_path: PathBuf::from("/src"),
-                    src: "import one.{receive} fn funky() { receive }".to_string(),
+                    src: "import one.{receive} pub fn funky() { receive }".to_string(),
                 },
             ],
             expected: Ok(vec![
@@ -609,7 +609,7 @@ funky() ->
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
-                    text: "-module(two).\n-compile(no_auto_import).\n\nfunky() ->
+                    text: "-module(two).\n-compile(no_auto_import).\n\n-export([funky/0]).\n\nfunky() ->
     fun one:'receive'/0.\n"
                         .to_string(),
                 },
@@ -629,7 +629,7 @@ funky() ->
                     origin: ModuleOrigin::Src,
                     path: PathBuf::from("/src/two.gleam"),
                     source_base_path: PathBuf::from("/src"),
-                    src: "import one fn funky() { one.receive(1) }".to_string(),
+                    src: "import one pub fn funky() { one.receive(1) }".to_string(),
                 },
             ],
             expected: Ok(vec![
@@ -642,7 +642,7 @@ funky() ->
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
-                    text: "-module(two).\n-compile(no_auto_import).\n\nfunky() ->
+                    text: "-module(two).\n-compile(no_auto_import).\n\n-export([funky/0]).\n\nfunky() ->
     one:'receive'(1).\n"
                         .to_string(),
                 },
@@ -774,7 +774,7 @@ type Two = Person"
                     path: PathBuf::from("/src/two.gleam"),
                     source_base_path: PathBuf::from("/src"),
                     src: "import one.{C}
-fn main() { C }"
+pub fn main() { C }"
                         .to_string(),
                 },
             ],
@@ -789,7 +789,7 @@ fn main() { C }"
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
-                    text: "-module(two).\n-compile(no_auto_import).\n\nmain() ->\n    fun(A, B) -> {c, A, B} end.\n".to_string(),
+                    text: "-module(two).\n-compile(no_auto_import).\n\n-export([main/0]).\n\nmain() ->\n    fun(A, B) -> {c, A, B} end.\n".to_string(),
                 },
             ]),
         },
@@ -807,7 +807,7 @@ fn main() { C }"
                     origin: ModuleOrigin::Src,
                     path: PathBuf::from("/src/two.gleam"),
                     source_base_path: PathBuf::from("/src"),
-                    src: "import one.{X as e, id as i} fn make() { i(e) }".to_string(),
+                    src: "import one.{X as e, id as i} pub fn make() { i(e) }".to_string(),
                 },
             ],
             expected: Ok(vec![
@@ -825,7 +825,7 @@ id(X) ->\n    X.\n"
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
-                    text: "-module(two).\n-compile(no_auto_import).\n
+                    text: "-module(two).\n-compile(no_auto_import).\n\n-export([make/0]).\n
 make() ->\n    one:id(fun(A) -> {x, A} end).\n"
                         .to_string(),
                 },
@@ -846,7 +846,7 @@ make() ->\n    one:id(fun(A) -> {x, A} end).\n"
                     path: PathBuf::from("/src/two.gleam"),
                     source_base_path: PathBuf::from("/src"),
                     src: "import one
-fn main() { one.C }"
+pub fn main() { one.C }"
                         .to_string(),
                 },
             ],
@@ -861,7 +861,7 @@ fn main() { one.C }"
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
-                    text: "-module(two).\n-compile(no_auto_import).\n\nmain() ->\n    fun(A, B) -> {c, A, B} end.\n".to_string(),
+                    text: "-module(two).\n-compile(no_auto_import).\n\n-export([main/0]).\n\nmain() ->\n    fun(A, B) -> {c, A, B} end.\n".to_string(),
                 },
             ]),
         },
@@ -888,7 +888,7 @@ fn main() { one.C }"
             expected: Err(Error::Type {
                 path: PathBuf::from("/src/two.gleam"),
                 src: "import one\nfn main() { one.C }".to_string(),
-                error: crate::typ::Error::UnknownModuleValue {
+                errors: vec![crate::typ::Error::UnknownModuleValue {
                     location: crate::ast::SrcSpan {
                         start: 26,
                         end: 28,
@@ -896,7 +896,7 @@ fn main() { one.C }"
                     name: "C".to_string(),
                     module_name: vec!["one".to_string(),],
                     value_constructors: vec![],
-                }
+                }]
             }),
         },
 
@@ -922,7 +922,7 @@ fn test(t: one.T) { t.a }"
             expected: Err(Error::Type {
                 path: PathBuf::from("/src/two.gleam"),
                 src: "import one\nfn test(t: one.T) { t.a }".to_string(),
-                error: crate::typ::Error::UnknownField {
+                errors: vec![crate::typ::Error::UnknownField {
                     location: crate::ast::SrcSpan {
                         start: 32,
                         end: 34,
@@ -935,7 +935,7 @@ fn test(t: one.T) { t.a }"
                     }),
                     label: "a".to_string(),
                     fields: vec![],
-                }
+                }]
             }),
         },
 
@@ -961,7 +961,7 @@ pub const cool_number2 = 3.14".to_string(),
                     source_base_path: PathBuf::from("/src"),
                     src: "import one
 import two.{cool_number, cool_number2 as pi}
-fn test() { one.const_string pi cool_number }"
+pub fn test() { one.const_string pi cool_number }"
                         .to_string(),
                 },
             ],
@@ -978,7 +978,7 @@ fn test() { one.const_string pi cool_number }"
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/three.erl"),
-                    text: "-module(three).\n-compile(no_auto_import).\n
+                    text: "-module(three).\n-compile(no_auto_import).\n\n-export([test/0]).\n
 test() ->\n    <<\"hello!\"/utf8>>,\n    3.14,\n    4.\n"
                         .to_string(),
                 },
@@ -1075,10 +1075,73 @@ main(Arg1, Arg2, Arg3) ->
                 },
             ]),
         },
+
+        // Errors in independent modules are all reported, and modules that
+        // import a module with errors are not type checked
+        Case {
+            input: vec![
+                Input {
+                    origin: ModuleOrigin::Src,
+                    path: PathBuf::from("/src/one.gleam"),
+                    source_base_path: PathBuf::from("/src"),
+                    src: "pub fn one() { 1 + 1.0 }".to_string(),
+                },
+                Input {
+                    origin: ModuleOrigin::Src,
+                    path: PathBuf::from("/src/two.gleam"),
+                    source_base_path: PathBuf::from("/src"),
+                    src: "import one pub fn two() { one.one() + x }".to_string(),
+                },
+                Input {
+                    origin: ModuleOrigin::Src,
+                    path: PathBuf::from("/src/three.gleam"),
+                    source_base_path: PathBuf::from("/src"),
+                    src: "pub fn three() { 1.0 + 2 }".to_string(),
+                },
+            ],
+            expected: Err(Error::Multiple {
+                errors: vec![
+                    Error::Type {
+                        path: PathBuf::from("/src/one.gleam"),
+                        src: "pub fn one() { 1 + 1.0 }".to_string(),
+                        errors: vec![crate::typ::Error::CouldNotUnify {
+                            location: crate::ast::SrcSpan { start: 19, end: 22 },
+                            expected: crate::typ::int(),
+                            given: crate::typ::float(),
+                        }],
+                    },
+                    Error::Type {
+                        path: PathBuf::from("/src/three.gleam"),
+                        src: "pub fn three() { 1.0 + 2 }".to_string(),
+                        errors: vec![crate::typ::Error::CouldNotUnify {
+                            location: crate::ast::SrcSpan { start: 17, end: 20 },
+                            expected: crate::typ::int(),
+                            given: crate::typ::float(),
+                        }],
+                    },
+                ],
+            }),
+        },
     ];
 
     for Case { input, expected } in cases.into_iter() {
-        let actual = analysed(input).map(|analysed| erl::generate_erlang(analysed.as_slice()));
+        let actual = analysed(input)
+            .map(|analysed| erl::generate_erlang(analysed.as_slice()))
+            .map_err(sort_errors);
         assert_eq!(expected, actual);
     }
 }
+
+// Independent modules may be compiled in any order
+fn sort_errors(error: Error) -> Error {
+    match error {
+        Error::Multiple { mut errors } => {
+            errors.sort_by_key(|error| match error {
+                Error::Type { path, .. } => path.clone(),
+                _ => PathBuf::new(),
+            });
+            Error::Multiple { errors }
+        }
+        error => error,
+    }
+}
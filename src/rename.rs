@@ -0,0 +1,74 @@
+use crate::{
+    build::project_root::ProjectRoot,
+    error::{Error, RenameProblem},
+    fs::OutputFile,
+    language_server::{
+        compiler,
+        references::{self, Index},
+    },
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Rename a function, constant, record constructor, or type, given as
+/// `module.name`, updating every reference to it in the project. A type and
+/// a record constructor with the same name are renamed together.
+///
+/// Each changed module is rewritten with the formatter.
+///
+pub fn command(root_string: String, target: String, new_name: String) -> Result<(), Error> {
+    let root = ProjectRoot::new(PathBuf::from(root_string));
+    let (root_package, sources) = compiler::read_sources(&root, &HashMap::new())?;
+    let compiled = compiler::analyse(&root_package, sources);
+    if !compiled.errors.is_empty() {
+        return Err(Error::Multiple {
+            errors: compiled.errors,
+        });
+    }
+
+    let index = Index::new(&compiled);
+    let definitions = match target.rfind('.') {
+        Some(i) => index.find(&target[..i], &target[i + 1..]),
+        None => vec![],
+    };
+    if definitions.is_empty() {
+        return Err(Error::Rename {
+            name: target,
+            new_name,
+            problem: RenameProblem::UnknownName,
+        });
+    }
+
+    let edits = index.rename(&compiled, &definitions, &new_name)?;
+    let mut outputs = Vec::with_capacity(edits.len());
+    for (name, spans) in edits {
+        let module = &compiled.modules[&name];
+        let code = references::apply(&module.code, &spans, &new_name);
+        let text = crate::format::pretty(&code).map_err(|error| Error::Parse {
+            path: module.path.clone(),
+            src: code.clone(),
+            error,
+        })?;
+        outputs.push(OutputFile {
+            text,
+            path: module.path.clone(),
+        });
+    }
+    crate::fs::write_outputs(&outputs)?;
+
+    crate::cli::print_green_prefix(
+        "  Renamed",
+        &format!(
+            "{} to {} in {} {}",
+            target,
+            new_name,
+            outputs.len(),
+            if outputs.len() == 1 {
+                "module"
+            } else {
+                "modules"
+            }
+        ),
+    );
+    Ok(())
+}
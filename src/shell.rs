@@ -1,5 +1,6 @@
 use crate::{
-    build::{self, project_root::ProjectRoot},
+    build::{self, project_root::ProjectRoot, Profile},
+    diagnostic::DiagnosticFormat,
     error::{Error, GleamExpect},
 };
 use std::path::PathBuf;
@@ -11,7 +12,7 @@ pub fn command(root_string: String) -> Result<(), Error> {
     let config = root.root_config()?;
 
     // Build project
-    build::main(config, root_path)?;
+    build::main(config, root_path, Profile::Default, DiagnosticFormat::Human)?;
 
     // Don't exit on ctrl+c as it is used by child erlang shell
     ctrlc::set_handler(move || {}).gleam_expect("Error setting Ctrl-C handler");
@@ -20,7 +21,7 @@ pub fn command(root_string: String) -> Result<(), Error> {
     let mut command = Command::new("erl");
 
     // Specify locations of .beam files
-    for entry in crate::fs::read_dir(root.default_build_lib_path())?.filter_map(Result::ok) {
+    for entry in crate::fs::read_dir(root.build_lib_path())?.filter_map(Result::ok) {
         command.arg("-pa");
         command.arg(entry.path().join("ebin"));
     }
@@ -1,5 +1,6 @@
 mod environment;
 mod error;
+mod exhaustiveness;
 mod expr;
 mod fields;
 mod hydrator;
@@ -27,9 +28,9 @@ use crate::{
     error::GleamExpect,
 };
 
-use std::cell::RefCell;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use error::*;
 use fields::FieldMap;
@@ -39,7 +40,7 @@ pub trait HasType {
     fn typ(&self) -> Arc<Type>;
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Type {
     App {
         public: bool,
@@ -54,7 +55,7 @@ pub enum Type {
     },
 
     Var {
-        typ: Arc<RefCell<TypeVar>>,
+        typ: Arc<TypeVarCell>,
     },
 
     Tuple {
@@ -170,21 +171,21 @@ pub fn collapse_links(t: Arc<Type>) -> Arc<Type> {
     t
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct AccessorsMap {
     pub public: bool,
     pub typ: Arc<Type>,
     pub accessors: HashMap<String, RecordAccessor>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct RecordAccessor {
     pub index: u64,
     pub label: String,
     pub typ: Arc<Type>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ValueConstructorVariant {
     /// A locally defined variable or function parameter
     LocalVariable,
@@ -236,12 +237,21 @@ pub enum ModuleValueConstructor {
     Constant { literal: TypedConstant },
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Module {
     pub name: Vec<String>,
     pub types: HashMap<String, TypeConstructor>,
     pub values: HashMap<String, ValueConstructor>,
     pub accessors: HashMap<String, AccessorsMap>,
+    pub types_constructors: HashMap<String, Vec<TypeValueConstructor>>,
+}
+
+/// A constructor of a custom type, as used when checking that every
+/// constructor of a type is matched by the clauses of a case expression.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeValueConstructor {
+    pub name: String,
+    pub arity: usize,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -261,7 +271,7 @@ pub trait Typer {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TypeVar {
     Unbound { id: usize, level: usize },
     Link { typ: Arc<Type> },
@@ -277,7 +287,50 @@ impl TypeVar {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// A type variable which can be linked to a type during unification. A lock
+/// is used rather than a `RefCell` so that types can be shared between the
+/// threads type checking modules in parallel.
+///
+#[derive(Debug)]
+pub struct TypeVarCell {
+    value: RwLock<TypeVar>,
+}
+
+impl TypeVarCell {
+    pub fn new(value: TypeVar) -> Self {
+        Self {
+            value: RwLock::new(value),
+        }
+    }
+
+    pub fn borrow(&self) -> RwLockReadGuard<'_, TypeVar> {
+        self.value.read().gleam_expect("Reading type variable")
+    }
+
+    pub fn borrow_mut(&self) -> RwLockWriteGuard<'_, TypeVar> {
+        self.value.write().gleam_expect("Writing type variable")
+    }
+}
+
+impl PartialEq for TypeVarCell {
+    fn eq(&self, other: &Self) -> bool {
+        *self.borrow() == *other.borrow()
+    }
+}
+
+impl Serialize for TypeVarCell {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.borrow().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TypeVarCell {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        TypeVar::deserialize(deserializer).map(Self::new)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TypeConstructor {
     pub public: bool,
     pub origin: SrcSpan,
@@ -286,10 +339,11 @@ pub struct TypeConstructor {
     pub typ: Arc<Type>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ValueConstructor {
     pub public: bool,
     pub origin: SrcSpan,
+    pub module: Vec<String>,
     pub variant: ValueConstructorVariant,
     pub typ: Arc<Type>,
 }
@@ -313,35 +367,79 @@ impl ValueConstructor {
 }
 
 /// Crawl the AST, annotating each node with the inferred type or
-/// returning an error.
+/// returning the errors found.
+///
+/// An error in one statement does not stop the rest of the module from being
+/// checked, so that as many errors as possible can be reported at once.
 ///
 pub fn infer_module(
     uid: &mut usize,
     module: UntypedModule,
     modules: &HashMap<String, (Origin, Module)>,
     warnings: &mut Vec<Warning>,
-) -> Result<TypedModule, Error> {
+) -> Result<TypedModule, Vec<Error>> {
     let mut environment = Environment::new(uid, module.name.as_slice(), modules, warnings);
     let module_name = &module.name;
+    let mut errors = vec![];
 
     // Register any modules, types, and values being imported
     // We process imports first so that anything imported can be referenced
     // anywhere in the module.
     for s in module.statements.iter() {
-        register_import(s, &mut environment)?;
+        if let Err(error) = register_import(s, &mut environment) {
+            errors.push(error);
+        }
     }
 
     // Register types so they can be used in constructors and functions
     // earlier in the module.
+    let mut registered = Vec::with_capacity(module.statements.len());
     for s in module.statements.iter() {
-        register_types(s, module_name, &mut environment)?;
+        match register_types(s, module_name, &mut environment) {
+            Ok(()) => registered.push(true),
+            Err(error) => {
+                errors.push(error);
+                registered.push(false);
+            }
+        }
     }
 
-    // Infer the types of each statement in the module
+    // Infer the types of each statement in the module, skipping any types that
+    // could not be registered
     let mut statements = Vec::with_capacity(module.statements.len());
-    for statement in module.statements {
-        let statement = infer_statement(statement, module_name, &mut environment)?;
-        statements.push(statement);
+    for (statement, registered) in module.statements.into_iter().zip(registered) {
+        if !registered {
+            continue;
+        }
+        let value_name = match &statement {
+            Statement::Fn { name, .. }
+            | Statement::ExternalFn { name, .. }
+            | Statement::ModuleConstant { name, .. } => Some(name.clone()),
+            _ => None,
+        };
+        match infer_statement(statement, module_name, &mut environment) {
+            Ok(statement) => statements.push(statement),
+
+            // Give any value that could not be inferred a generic type so that
+            // uses of it later in the module do not cause further errors
+            Err(error) => {
+                errors.push(error);
+                if let Some(name) = value_name {
+                    let typ = environment.new_generic_var();
+                    environment.insert_variable(
+                        name,
+                        ValueConstructorVariant::LocalVariable,
+                        typ,
+                        Default::default(),
+                        module_name.clone(),
+                    );
+                }
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
     }
 
     // Generate warnings for unused items
@@ -356,19 +454,35 @@ pub fn infer_module(
         .accessors
         .retain(|_, accessors| accessors.public);
 
+    // Only retain the constructors of public types, and not of opaque types as
+    // they cannot be pattern matched on outside of this module
+    let module_types = &environment.module_types;
+    environment
+        .module_types_constructors
+        .retain(|name, _| module_types.contains_key(name));
+    for statement in statements.iter() {
+        if let Statement::CustomType {
+            name, opaque: true, ..
+        } = statement
+        {
+            environment.module_types_constructors.remove(name);
+        }
+    }
+
     // Ensure no exported values have private types in their type signature
     for (_, value) in environment.module_values.iter() {
         if let Some(leaked) = value.typ.find_private_type() {
-            return Err(Error::PrivateTypeLeak {
+            return Err(vec![Error::PrivateTypeLeak {
                 location: value.origin.clone(),
                 leaked,
-            });
+            }]);
         }
     }
 
     let Environment {
         module_types: types,
         module_values: values,
+        module_types_constructors: types_constructors,
         accessors,
         ..
     } = environment;
@@ -382,6 +496,7 @@ pub fn infer_module(
             types,
             values,
             accessors,
+            types_constructors,
         },
     })
 }
@@ -429,6 +544,8 @@ fn infer_statement(
                     arity: args.len(),
                 },
                 rec.clone(),
+                location.clone(),
+                module_name.clone(),
             );
 
             // Infer the type
@@ -448,6 +565,7 @@ fn infer_statement(
                 ValueConstructor {
                     public,
                     origin: location.clone(),
+                    module: module_name.clone(),
                     typ: typ.clone(),
                     variant: ValueConstructorVariant::ModuleFn {
                         name: name.clone(),
@@ -458,6 +576,14 @@ fn infer_statement(
                 },
             )?;
 
+            // Register the function as unused until it is referenced. This is done
+            // after inferring the body so that recursive calls are not counted.
+            if !public {
+                environment
+                    .unused_private_functions
+                    .insert(name.clone(), location.clone());
+            }
+
             // Insert the function into the environment
             environment.insert_variable(
                 name.clone(),
@@ -468,6 +594,8 @@ fn infer_statement(
                     arity: args.len(),
                 },
                 typ,
+                location.clone(),
+                module_name.clone(),
             );
 
             let statement: TypedStatement = Statement::Fn {
@@ -527,6 +655,7 @@ fn infer_statement(
                     public,
                     typ: typ.clone(),
                     origin: location.clone(),
+                    module: module_name.clone(),
                     variant: ValueConstructorVariant::ModuleFn {
                         name: fun.clone(),
                         field_map: field_map.clone(),
@@ -546,6 +675,8 @@ fn infer_statement(
                     field_map,
                 },
                 typ,
+                location.clone(),
+                module_name.clone(),
             );
             Ok(Statement::ExternalFn {
                 return_type,
@@ -646,6 +777,7 @@ fn infer_statement(
                             public,
                             typ: typ.clone(),
                             origin: constructor.location.clone(),
+                            module: module_name.clone(),
                             variant: ValueConstructorVariant::Record {
                                 name: constructor.name.clone(),
                                 field_map: field_map.clone(),
@@ -661,6 +793,8 @@ fn infer_statement(
                         field_map,
                     },
                     typ,
+                    constructor.location.clone(),
+                    module_name.clone(),
                 );
             }
             Ok(Statement::CustomType {
@@ -728,6 +862,7 @@ fn infer_statement(
                 ValueConstructor {
                     public,
                     origin: location.clone(),
+                    module: module_name.clone(),
                     variant: ValueConstructorVariant::ModuleConstant {
                         literal: typed_expr.clone(),
                     },
@@ -735,6 +870,12 @@ fn infer_statement(
                 },
             )?;
 
+            if !public {
+                environment
+                    .unused_private_constants
+                    .insert(name.clone(), location.clone());
+            }
+
             Ok(Statement::ModuleConstant {
                 doc,
                 location,
@@ -961,7 +1102,7 @@ fn generalise(t: Arc<Type>, ctx_level: usize) -> Arc<Type> {
                     let id = *id;
                     if *level > ctx_level {
                         return Arc::new(Type::Var {
-                            typ: Arc::new(RefCell::new(TypeVar::Generic { id })),
+                            typ: Arc::new(TypeVarCell::new(TypeVar::Generic { id })),
                         });
                     } else {
                         Some(TypeVar::Unbound { id, level: *level })
@@ -1143,6 +1284,19 @@ pub fn register_types(
                 environment.insert_accessors(name.as_ref(), map)
             }
 
+            // Register the constructors of the type so that we can check that
+            // case expressions have a clause for each of them
+            environment.insert_type_to_constructors(
+                name.clone(),
+                constructors
+                    .iter()
+                    .map(|constructor| TypeValueConstructor {
+                        name: constructor.name.clone(),
+                        arity: constructor.args.len(),
+                    })
+                    .collect(),
+            );
+
             // Keep track of private types so we can tell if they are later unused
             if !public {
                 environment
@@ -1199,7 +1353,7 @@ pub fn register_import(s: &UntypedStatement, environment: &mut Environment) -> R
             module,
             as_name,
             unqualified,
-            ..
+            location,
         } => {
             // Find imported module
             let module_info = environment
@@ -1234,6 +1388,8 @@ pub fn register_import(s: &UntypedStatement, environment: &mut Environment) -> R
                         imported_name.clone(),
                         value.variant.clone(),
                         value.typ.clone(),
+                        value.origin.clone(),
+                        value.module.clone(),
                     );
                     value_imported = true;
                 }
@@ -1261,7 +1417,9 @@ pub fn register_import(s: &UntypedStatement, environment: &mut Environment) -> R
                         .unused_private_types
                         .insert(imported_name.clone(), location.clone());
                 } else if value_imported {
-                    ()
+                    environment
+                        .unused_imported_values
+                        .insert(imported_name.clone(), location.clone());
                 } else {
                     // Error if no type or value was found with that name
                     return Err(Error::UnknownModuleField {
@@ -1284,6 +1442,14 @@ pub fn register_import(s: &UntypedStatement, environment: &mut Environment) -> R
                 }
             }
 
+            // Register the module as unused until it is referenced. Modules
+            // imported for their unqualified values are considered used.
+            if unqualified.is_empty() {
+                environment
+                    .unused_imported_modules
+                    .insert(module_name.clone(), location.clone());
+            }
+
             // Insert imported module into scope
             // TODO: use a refernce to the module to avoid copying
             environment
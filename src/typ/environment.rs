@@ -1,5 +1,5 @@
 use super::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug)]
 pub struct Environment<'a, 'b> {
@@ -21,6 +21,9 @@ pub struct Environment<'a, 'b> {
     // Accessors defined in the current module
     pub accessors: HashMap<String, AccessorsMap>,
 
+    // The constructors of the custom types defined in the current module
+    pub module_types_constructors: HashMap<String, Vec<TypeValueConstructor>>,
+
     // Type constructors that have imported or privately defined
     // but have not yet been used.
     pub unused_private_types: HashMap<String, SrcSpan>,
@@ -32,6 +35,22 @@ pub struct Environment<'a, 'b> {
     // i.e. type X { X }
     pub unused_private_mixed_constructors: HashMap<String, SrcSpan>,
 
+    // Private functions and constants defined in the current module that
+    // have not yet been used.
+    pub unused_private_functions: HashMap<String, SrcSpan>,
+    pub unused_private_constants: HashMap<String, SrcSpan>,
+
+    // Modules that have been imported but have not yet been used.
+    pub unused_imported_modules: HashMap<String, SrcSpan>,
+
+    // Values that have been imported unqualified but have not yet been used.
+    pub unused_imported_values: HashMap<String, SrcSpan>,
+
+    // Local variables that have been defined but have not yet been used,
+    // identified by their name and the location where they were bound as
+    // names can be shadowed.
+    pub unused_local_variables: HashSet<(String, SrcSpan)>,
+
     // Warnings
     pub warnings: &'a mut Vec<Warning>,
 }
@@ -48,8 +67,14 @@ impl<'a, 'b> Environment<'a, 'b> {
             level: 1,
             unused_private_types: HashMap::new(),
             unused_private_mixed_constructors: HashMap::new(),
+            unused_private_functions: HashMap::new(),
+            unused_private_constants: HashMap::new(),
+            unused_imported_modules: HashMap::new(),
+            unused_imported_values: HashMap::new(),
+            unused_local_variables: HashSet::new(),
             module_types: HashMap::new(),
             module_values: HashMap::new(),
+            module_types_constructors: HashMap::new(),
             imported_modules: HashMap::new(),
             accessors: HashMap::new(),
             local_values: hashmap![],
@@ -105,7 +130,7 @@ impl<'a, 'b> Environment<'a, 'b> {
     ///
     pub fn new_unbound_var(&mut self, level: usize) -> Arc<Type> {
         Arc::new(Type::Var {
-            typ: Arc::new(RefCell::new(TypeVar::Unbound {
+            typ: Arc::new(TypeVarCell::new(TypeVar::Unbound {
                 id: self.next_uid(),
                 level,
             })),
@@ -116,31 +141,55 @@ impl<'a, 'b> Environment<'a, 'b> {
     ///
     pub fn new_generic_var(&mut self) -> Arc<Type> {
         Arc::new(Type::Var {
-            typ: Arc::new(RefCell::new(TypeVar::Generic {
+            typ: Arc::new(TypeVarCell::new(TypeVar::Generic {
                 id: self.next_uid(),
             })),
         })
     }
 
-    /// Insert a variable in the current scope.
+    /// Insert a variable in the current scope, recording the location and
+    /// module where it was defined.
     ///
     pub fn insert_variable(
         &mut self,
         name: String,
         variant: ValueConstructorVariant,
         typ: Arc<Type>,
+        origin: SrcSpan,
+        module: Vec<String>,
     ) {
         self.local_values.insert(
             name,
             ValueConstructor {
                 public: false,
-                origin: Default::default(), // TODO: use the real one
+                origin,
+                module,
                 variant,
                 typ,
             },
         );
     }
 
+    /// Insert a local variable in the current scope, tracking it so that a
+    /// warning can be emitted if it is never used.
+    ///
+    pub fn insert_local_variable(&mut self, name: String, location: SrcSpan, typ: Arc<Type>) {
+        if !name.starts_with('_') {
+            self.unused_local_variables
+                .insert((name.clone(), location.clone()));
+        }
+        self.local_values.insert(
+            name,
+            ValueConstructor {
+                public: false,
+                origin: location,
+                module: self.current_module.to_vec(),
+                variant: ValueConstructorVariant::LocalVariable,
+                typ,
+            },
+        );
+    }
+
     /// Insert a value into the current module.
     /// Errors if the module already has a value with that name.
     ///
@@ -284,6 +333,41 @@ impl<'a, 'b> Environment<'a, 'b> {
         }
     }
 
+    /// Record the constructors of a custom type defined in the current module.
+    ///
+    pub fn insert_type_to_constructors(
+        &mut self,
+        type_name: String,
+        constructors: Vec<TypeValueConstructor>,
+    ) {
+        let _ = self
+            .module_types_constructors
+            .insert(type_name, constructors);
+    }
+
+    /// Lookup the constructors of a custom type.
+    /// Returns None if the type has no known constructors, i.e. if it is an
+    /// external type or an opaque type from another module.
+    ///
+    pub fn get_constructors_for_type(
+        &self,
+        module: &[String],
+        name: &str,
+    ) -> Option<Vec<TypeValueConstructor>> {
+        // A module without a name has the same empty module path as the
+        // prelude, so the types of the current module are looked up first
+        if module == self.current_module && self.module_types_constructors.contains_key(name) {
+            self.module_types_constructors.get(name).cloned()
+        } else if module.is_empty() {
+            prelude::type_constructors(name)
+        } else {
+            self.importable_modules
+                .get(&module.join("/"))
+                .and_then(|module| module.1.types_constructors.get(name))
+                .cloned()
+        }
+    }
+
     pub fn insert_accessors(&mut self, type_name: &str, accessors: AccessorsMap) {
         self.accessors.insert(type_name.to_string(), accessors);
     }
@@ -481,13 +565,49 @@ impl<'a, 'b> Environment<'a, 'b> {
     }
 
     pub fn convert_unused_to_warnings(&mut self) {
+        let mut unused: Vec<(SrcSpan, Warning)> = vec![];
         for (name, location) in self.unused_private_types.drain() {
-            self.warnings.push(Warning::UnusedType { name, location })
+            unused.push((location.clone(), Warning::UnusedType { name, location }))
         }
         for (name, location) in self.unused_private_mixed_constructors.drain() {
-            self.warnings
-                .push(Warning::UnusedConstructor { name, location })
+            unused.push((
+                location.clone(),
+                Warning::UnusedConstructor { name, location },
+            ))
         }
+        for (name, location) in self.unused_private_functions.drain() {
+            unused.push((
+                location.clone(),
+                Warning::UnusedPrivateFunction { name, location },
+            ))
+        }
+        for (name, location) in self.unused_private_constants.drain() {
+            unused.push((
+                location.clone(),
+                Warning::UnusedPrivateModuleConstant { name, location },
+            ))
+        }
+        for (name, location) in self.unused_imported_modules.drain() {
+            unused.push((
+                location.clone(),
+                Warning::UnusedImportedModule { name, location },
+            ))
+        }
+        for (name, location) in self.unused_imported_values.drain() {
+            unused.push((
+                location.clone(),
+                Warning::UnusedImportedValue { name, location },
+            ))
+        }
+        for (name, location) in self.unused_local_variables.drain() {
+            unused.push((location.clone(), Warning::UnusedVariable { name, location }))
+        }
+
+        // The unused items are held in hash maps so they are sorted to keep
+        // the order of the warnings the same from one compilation to the next
+        unused.sort_by_key(|(location, _)| location.start);
+        self.warnings
+            .extend(unused.into_iter().map(|(_, warning)| warning));
     }
 
     pub fn type_used(&mut self, name: &str) {
@@ -497,5 +617,17 @@ impl<'a, 'b> Environment<'a, 'b> {
 
     pub fn value_used(&mut self, name: &str) {
         self.unused_private_mixed_constructors.remove(name);
+        self.unused_private_functions.remove(name);
+        self.unused_private_constants.remove(name);
+        self.unused_imported_values.remove(name);
+    }
+
+    pub fn module_used(&mut self, alias: &str) {
+        self.unused_imported_modules.remove(alias);
+    }
+
+    pub fn local_variable_used(&mut self, name: &str, location: &SrcSpan) {
+        self.unused_local_variables
+            .remove(&(name.to_string(), location.clone()));
     }
 }
@@ -216,6 +216,11 @@ pub enum Error {
     UnexpectedTypeHole {
         location: SrcSpan,
     },
+
+    InexhaustiveCaseExpression {
+        location: SrcSpan,
+        missing: Vec<String>,
+    },
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -231,6 +236,18 @@ pub enum Warning {
     UnusedType { location: SrcSpan, name: String },
 
     UnusedConstructor { location: SrcSpan, name: String },
+
+    UnreachableCaseClause { location: SrcSpan },
+
+    UnusedVariable { location: SrcSpan, name: String },
+
+    UnusedPrivateFunction { location: SrcSpan, name: String },
+
+    UnusedPrivateModuleConstant { location: SrcSpan, name: String },
+
+    UnusedImportedModule { location: SrcSpan, name: String },
+
+    UnusedImportedValue { location: SrcSpan, name: String },
 }
 
 #[derive(Debug, PartialEq)]
@@ -0,0 +1,441 @@
+//! Exhaustiveness checking of case expressions.
+//!
+//! Patterns are simplified into a matrix with one row per (alternative)
+//! multi-pattern and one column per case subject, which is then analysed using
+//! the algorithms described in Luc Maranget's paper "Warnings for pattern
+//! matching" to find both missing patterns and unreachable clauses.
+//!
+use super::*;
+use crate::ast::TypedClause;
+use itertools::Itertools;
+
+/// The maximum number of example missing patterns to collect. Some types have a
+/// very large number of possible inexhaustive combinations and there is no use
+/// in showing all of them.
+const MAX_MISSING_PATTERNS: usize = 10;
+
+type Row = Vec<Pat>;
+
+/// A pattern reduced to the information needed to determine which values it
+/// matches. Variables, discards and assignments all become wildcards.
+#[derive(Debug, Clone)]
+enum Pat {
+    Wildcard,
+    Constructor(Constructor, Vec<Pat>),
+}
+
+#[derive(Debug, Clone)]
+enum Constructor {
+    Tuple(usize),
+    ListNil,
+    ListCons,
+    Record {
+        name: String,
+        arity: usize,
+        // None if the constructors of the type could not be determined
+        siblings: Option<Vec<TypeValueConstructor>>,
+    },
+    Int(String),
+    Float(String),
+    String(String),
+    // Bit string patterns are not analysed so each one is unique
+    BitString(usize),
+}
+
+impl Constructor {
+    fn arity(&self) -> usize {
+        match self {
+            Constructor::Tuple(arity) | Constructor::Record { arity, .. } => *arity,
+            Constructor::ListCons => 2,
+            Constructor::ListNil
+            | Constructor::Int(_)
+            | Constructor::Float(_)
+            | Constructor::String(_)
+            | Constructor::BitString(_) => 0,
+        }
+    }
+
+    fn is_same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Constructor::Tuple(a), Constructor::Tuple(b)) => a == b,
+            (Constructor::ListNil, Constructor::ListNil) => true,
+            (Constructor::ListCons, Constructor::ListCons) => true,
+            (Constructor::Record { name: a, .. }, Constructor::Record { name: b, .. }) => a == b,
+            (Constructor::Int(a), Constructor::Int(b)) => a == b,
+            (Constructor::Float(a), Constructor::Float(b)) => a == b,
+            (Constructor::String(a), Constructor::String(b)) => a == b,
+            (Constructor::BitString(a), Constructor::BitString(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// All the constructors of the type this constructor belongs to, or None if
+    /// the type has an unbounded number of values (such as Int or String).
+    fn all_constructors(&self) -> Option<Vec<Constructor>> {
+        match self {
+            Constructor::Tuple(arity) => Some(vec![Constructor::Tuple(*arity)]),
+
+            Constructor::ListNil | Constructor::ListCons => {
+                Some(vec![Constructor::ListNil, Constructor::ListCons])
+            }
+
+            Constructor::Record {
+                siblings: Some(siblings),
+                ..
+            } => Some(
+                siblings
+                    .iter()
+                    .map(|sibling| Constructor::Record {
+                        name: sibling.name.clone(),
+                        arity: sibling.arity,
+                        siblings: Some(siblings.clone()),
+                    })
+                    .collect(),
+            ),
+
+            Constructor::Record { siblings: None, .. }
+            | Constructor::Int(_)
+            | Constructor::Float(_)
+            | Constructor::String(_)
+            | Constructor::BitString(_) => None,
+        }
+    }
+}
+
+fn wildcards(count: usize) -> Row {
+    (0..count).map(|_| Pat::Wildcard).collect()
+}
+
+/// Reduces typed patterns to `Pat`s, looking up the constructors of any custom
+/// types matched upon in the environment.
+struct Simplifier<'a, 'b, 'c> {
+    environment: &'a Environment<'b, 'c>,
+    bit_string_count: usize,
+}
+
+impl<'a, 'b, 'c> Simplifier<'a, 'b, 'c> {
+    fn simplify(&mut self, pattern: &TypedPattern) -> Pat {
+        match pattern {
+            Pattern::Var { .. } | Pattern::VarCall { .. } | Pattern::Discard { .. } => {
+                Pat::Wildcard
+            }
+
+            Pattern::Let { pattern, .. } => self.simplify(pattern),
+
+            Pattern::Int { value, .. } => literal(Constructor::Int(value.replace("_", ""))),
+
+            Pattern::Float { value, .. } => literal(Constructor::Float(value.replace("_", ""))),
+
+            Pattern::String { value, .. } => literal(Constructor::String(value.clone())),
+
+            Pattern::BitString { .. } => {
+                self.bit_string_count += 1;
+                literal(Constructor::BitString(self.bit_string_count))
+            }
+
+            Pattern::Nil { .. } => literal(Constructor::ListNil),
+
+            Pattern::Cons { head, tail, .. } => Pat::Constructor(
+                Constructor::ListCons,
+                vec![self.simplify(head), self.simplify(tail)],
+            ),
+
+            Pattern::Tuple { elems, .. } => Pat::Constructor(
+                Constructor::Tuple(elems.len()),
+                elems.iter().map(|e| self.simplify(e)).collect(),
+            ),
+
+            Pattern::Constructor {
+                module,
+                name,
+                args,
+                constructor: PatternConstructor::Record { name: record_name },
+                ..
+            } => {
+                let siblings = self.record_siblings(module.as_ref(), name);
+                Pat::Constructor(
+                    Constructor::Record {
+                        name: record_name.clone(),
+                        arity: args.len(),
+                        siblings,
+                    },
+                    args.iter().map(|arg| self.simplify(&arg.value)).collect(),
+                )
+            }
+        }
+    }
+
+    /// Find all the constructors of the custom type that the named record
+    /// constructor belongs to.
+    fn record_siblings(
+        &self,
+        module: Option<&String>,
+        name: &str,
+    ) -> Option<Vec<TypeValueConstructor>> {
+        let constructor = self.environment.get_value_constructor(module, name).ok()?;
+        let typ = match constructor.typ.as_ref() {
+            Type::Fn { retrn, .. } => collapse_links(retrn.clone()),
+            _ => collapse_links(constructor.typ.clone()),
+        };
+        match typ.as_ref() {
+            Type::App { module, name, .. } => {
+                self.environment.get_constructors_for_type(module, name)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn literal(constructor: Constructor) -> Pat {
+    Pat::Constructor(constructor, vec![])
+}
+
+/// The distinct constructors found at the head of each row of the matrix.
+fn head_constructors(rows: &[Row]) -> Vec<Constructor> {
+    let mut constructors: Vec<Constructor> = vec![];
+    for row in rows {
+        if let Some(Pat::Constructor(constructor, _)) = row.first() {
+            if !constructors.iter().any(|c| c.is_same(constructor)) {
+                constructors.push(constructor.clone());
+            }
+        }
+    }
+    constructors
+}
+
+/// If the given constructors are all of the constructors of their type then
+/// return them, otherwise return None.
+fn complete_signature(constructors: &[Constructor]) -> Option<Vec<Constructor>> {
+    let all = constructors.first()?.all_constructors()?;
+    if all
+        .iter()
+        .all(|c| constructors.iter().any(|other| other.is_same(c)))
+    {
+        Some(all)
+    } else {
+        None
+    }
+}
+
+/// Keep only the rows that could match a value built with the given
+/// constructor, replacing their first column with the constructor's arguments.
+fn specialise(rows: &[Row], constructor: &Constructor) -> Vec<Row> {
+    rows.iter()
+        .filter_map(|row| {
+            let (head, tail) = row.split_first()?;
+            let mut new_row = match head {
+                Pat::Wildcard => wildcards(constructor.arity()),
+                Pat::Constructor(c, args) if c.is_same(constructor) => args.clone(),
+                Pat::Constructor(..) => return None,
+            };
+            new_row.extend(tail.iter().cloned());
+            Some(new_row)
+        })
+        .collect()
+}
+
+/// Keep only the rows that have a wildcard in their first column, removing
+/// that column.
+fn default_matrix(rows: &[Row]) -> Vec<Row> {
+    rows.iter()
+        .filter_map(|row| match row.split_first()? {
+            (Pat::Wildcard, tail) => Some(tail.to_vec()),
+            (Pat::Constructor(..), _) => None,
+        })
+        .collect()
+}
+
+/// Find example rows of the given width that are not matched by any row of the
+/// matrix.
+fn missing_rows(rows: &[Row], width: usize) -> Vec<Row> {
+    if width == 0 {
+        return if rows.is_empty() {
+            vec![vec![]]
+        } else {
+            vec![]
+        };
+    }
+
+    let heads = head_constructors(rows);
+
+    // If every constructor of the type appears in the first column then the
+    // missing values must be found within the arguments of those constructors.
+    if let Some(all) = complete_signature(&heads) {
+        let mut missing = vec![];
+        for constructor in all {
+            let arity = constructor.arity();
+            let specialised = specialise(rows, &constructor);
+            for mut row in missing_rows(&specialised, arity + width - 1) {
+                let tail = row.split_off(arity);
+                let mut new_row = vec![Pat::Constructor(constructor.clone(), row)];
+                new_row.extend(tail);
+                missing.push(new_row);
+                if missing.len() >= MAX_MISSING_PATTERNS {
+                    return missing;
+                }
+            }
+        }
+        return missing;
+    }
+
+    // Otherwise there are values not matched by any constructor in the first
+    // column, so only the rows with a wildcard can match them.
+    let missing_tails = missing_rows(&default_matrix(rows), width - 1);
+    if missing_tails.is_empty() {
+        return vec![];
+    }
+
+    let examples = match heads.first().and_then(|c| c.all_constructors()) {
+        Some(all) => all
+            .into_iter()
+            .filter(|c| !heads.iter().any(|head| head.is_same(c)))
+            .map(|c| {
+                let args = wildcards(c.arity());
+                Pat::Constructor(c, args)
+            })
+            .collect(),
+        None => vec![Pat::Wildcard],
+    };
+
+    examples
+        .iter()
+        .cartesian_product(missing_tails.iter())
+        .take(MAX_MISSING_PATTERNS)
+        .map(|(example, tail)| {
+            let mut row = vec![example.clone()];
+            row.extend(tail.iter().cloned());
+            row
+        })
+        .collect()
+}
+
+fn pretty_row(row: &[Pat]) -> String {
+    row.iter().map(pretty_pattern).join(", ")
+}
+
+fn pretty_pattern(pattern: &Pat) -> String {
+    match pattern {
+        Pat::Wildcard => "_".to_string(),
+
+        Pat::Constructor(Constructor::Tuple(_), elems) => format!("tuple({})", pretty_row(elems)),
+
+        Pat::Constructor(Constructor::ListNil, _) => "[]".to_string(),
+
+        Pat::Constructor(Constructor::ListCons, args) => {
+            let mut elems = vec![];
+            let mut tail = None;
+            let mut args = args;
+            loop {
+                elems.push(pretty_pattern(&args[0]));
+                match &args[1] {
+                    Pat::Constructor(Constructor::ListCons, next) => args = next,
+                    Pat::Constructor(Constructor::ListNil, _) => break,
+                    other => {
+                        tail = Some(pretty_pattern(other));
+                        break;
+                    }
+                }
+            }
+            match tail {
+                None => format!("[{}]", elems.join(", ")),
+                Some(tail) => format!("[{}, ..{}]", elems.join(", "), tail),
+            }
+        }
+
+        Pat::Constructor(Constructor::Record { name, .. }, args) if args.is_empty() => name.clone(),
+
+        Pat::Constructor(Constructor::Record { name, .. }, args) => {
+            format!("{}({})", name, pretty_row(args))
+        }
+
+        Pat::Constructor(Constructor::Int(value), _)
+        | Pat::Constructor(Constructor::Float(value), _) => value.clone(),
+
+        Pat::Constructor(Constructor::String(value), _) => format!("\"{}\"", value),
+
+        Pat::Constructor(Constructor::BitString(_), _) => "<<_>>".to_string(),
+    }
+}
+
+/// Determine whether there is any value matched by the given row that is not
+/// matched by any row of the matrix.
+fn is_useful(rows: &[Row], row: &[Pat]) -> bool {
+    let (head, tail) = match row.split_first() {
+        Some(split) => split,
+        None => return rows.is_empty(),
+    };
+
+    match head {
+        Pat::Constructor(constructor, args) => {
+            let mut specialised_row = args.clone();
+            specialised_row.extend(tail.iter().cloned());
+            is_useful(&specialise(rows, constructor), &specialised_row)
+        }
+
+        Pat::Wildcard => match complete_signature(&head_constructors(rows)) {
+            Some(all) => all.iter().any(|constructor| {
+                let mut specialised_row = wildcards(constructor.arity());
+                specialised_row.extend(tail.iter().cloned());
+                is_useful(&specialise(rows, constructor), &specialised_row)
+            }),
+            None => is_useful(&default_matrix(rows), tail),
+        },
+    }
+}
+
+/// Check the clauses of a case expression, emitting a warning for each clause
+/// that can never be reached because earlier clauses match all of its values,
+/// and returning an error listing example unmatched patterns if the clauses do
+/// not match every possible value of the subjects.
+///
+pub fn check_case_clauses(
+    environment: &mut Environment,
+    subjects_count: usize,
+    clauses: &[TypedClause],
+    location: &SrcSpan,
+) -> Result<(), Error> {
+    let mut simplifier = Simplifier {
+        environment: &*environment,
+        bit_string_count: 0,
+    };
+
+    let clauses_rows: Vec<Vec<Row>> = clauses
+        .iter()
+        .map(|clause| {
+            std::iter::once(&clause.pattern)
+                .chain(&clause.alternative_patterns)
+                .map(|multi_pattern| {
+                    multi_pattern
+                        .iter()
+                        .map(|p| simplifier.simplify(p))
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut rows: Vec<Row> = vec![];
+    for (clause, clause_rows) in clauses.iter().zip(clauses_rows) {
+        if !clause_rows.iter().any(|row| is_useful(&rows, row)) {
+            environment.warnings.push(Warning::UnreachableCaseClause {
+                location: clause.location.clone(),
+            });
+        }
+
+        // Clauses with guards may not match, so they cannot count towards the
+        // values covered by the case expression.
+        if clause.guard.is_none() {
+            rows.extend(clause_rows);
+        }
+    }
+
+    let missing = missing_rows(&rows, subjects_count);
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::InexhaustiveCaseExpression {
+            location: location.clone(),
+            missing: missing.iter().map(|row| pretty_row(row)).collect(),
+        })
+    }
+}
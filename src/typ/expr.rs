@@ -729,6 +729,14 @@ impl<'a, 'b, 'c> ExprTyper<'a, 'b, 'c> {
                 .map_err(|e| convert_unify_error(e, typed_clause.then.location()))?;
             typed_clauses.push(typed_clause);
         }
+
+        exhaustiveness::check_case_clauses(
+            self.environment,
+            subjects_count,
+            &typed_clauses,
+            &location,
+        )?;
+
         Ok(TypedExpr::Case {
             location,
             typ: return_type,
@@ -1077,6 +1085,7 @@ impl<'a, 'b, 'c> ExprTyper<'a, 'b, 'c> {
         module_location: &SrcSpan,
         select_location: SrcSpan,
     ) -> Result<TypedExpr, Error> {
+        self.environment.module_used(module_alias);
         let (module_name, constructor) = {
             let module_info = self
                 .environment
@@ -1215,7 +1224,8 @@ impl<'a, 'b, 'c> ExprTyper<'a, 'b, 'c> {
         args: Vec<UntypedRecordUpdateArg>,
         location: SrcSpan,
     ) -> Result<TypedExpr, Error> {
-        let (module, name) = match self.infer(constructor.clone())? {
+        let constructor = self.infer(constructor)?;
+        let (module, name) = match &constructor {
             TypedExpr::ModuleSelect {
                 module_alias,
                 label,
@@ -1233,7 +1243,7 @@ impl<'a, 'b, 'c> ExprTyper<'a, 'b, 'c> {
 
         let value_constructor = self
             .environment
-            .get_value_constructor(module.as_ref(), &name)
+            .get_value_constructor(module, name)
             .map_err(|e| convert_get_value_constructor_error(e, &location))?
             .clone();
 
@@ -1312,6 +1322,7 @@ impl<'a, 'b, 'c> ExprTyper<'a, 'b, 'c> {
                 return Ok(TypedExpr::RecordUpdate {
                     location,
                     typ: spread.typ(),
+                    constructor: Box::new(constructor),
                     spread: Box::new(spread),
                     args,
                 });
@@ -1333,6 +1344,7 @@ impl<'a, 'b, 'c> ExprTyper<'a, 'b, 'c> {
             public,
             variant,
             origin,
+            module,
             typ,
         } = self
             .environment
@@ -1351,7 +1363,14 @@ impl<'a, 'b, 'c> ExprTyper<'a, 'b, 'c> {
             })?;
 
         // Register the value as seen for detection of unused values
-        self.environment.value_used(name);
+        match variant {
+            ValueConstructorVariant::LocalVariable => {
+                self.environment.local_variable_used(name, &origin)
+            }
+            ValueConstructorVariant::ModuleConstant { .. }
+            | ValueConstructorVariant::ModuleFn { .. }
+            | ValueConstructorVariant::Record { .. } => self.environment.value_used(name),
+        }
 
         // Instantiate generic variables into unbound variables for this usage
         let typ = self.instantiate(typ, self.environment.level, &mut hashmap![]);
@@ -1359,6 +1378,7 @@ impl<'a, 'b, 'c> ExprTyper<'a, 'b, 'c> {
             public,
             variant,
             origin,
+            module,
             typ,
         })
     }
@@ -1402,6 +1422,9 @@ impl<'a, 'b, 'c> ExprTyper<'a, 'b, 'c> {
                 mut args,
                 ..
             } => {
+                if let Some(module) = &module {
+                    self.environment.module_used(module);
+                }
                 let constructor = self.infer_value_constructor(&name, &location)?;
 
                 let tag = match &constructor.variant {
@@ -1616,13 +1639,9 @@ impl<'a, 'b, 'c> ExprTyper<'a, 'b, 'c> {
         let body = self.in_new_scope(|body_typer| {
             for (arg, t) in args.iter().zip(args.iter().map(|arg| arg.typ.clone())) {
                 match &arg.names {
-                    ArgNames::Named { name } | ArgNames::NamedLabelled { name, .. } => {
-                        body_typer.environment.insert_variable(
-                            name.to_string(),
-                            ValueConstructorVariant::LocalVariable,
-                            t,
-                        )
-                    }
+                    ArgNames::Named { name } | ArgNames::NamedLabelled { name, .. } => body_typer
+                        .environment
+                        .insert_local_variable(name.to_string(), arg.location.clone(), t),
                     ArgNames::Discard { .. } | ArgNames::LabelledDiscard { .. } => (),
                 };
             }
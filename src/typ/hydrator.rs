@@ -104,8 +104,9 @@ impl Hydrator {
                 // Register the type constructor as being used if it is unqualifed.
                 // We do not track use of qualified type constructors as they may be
                 // used in another module.
-                if module.is_none() {
-                    environment.type_used(name.as_str());
+                match module {
+                    None => environment.type_used(name.as_str()),
+                    Some(module) => environment.module_used(module.as_str()),
                 }
 
                 // Ensure that the correct number of arguments have been given to the constructor
@@ -33,7 +33,12 @@ impl<'a, 'b, 'c> PatternTyper<'a, 'b, 'c> {
         }
     }
 
-    fn insert_variable(&mut self, name: &str, typ: Arc<Type>) -> Result<(), UnifyError> {
+    fn insert_variable(
+        &mut self,
+        name: &str,
+        location: &SrcSpan,
+        typ: Arc<Type>,
+    ) -> Result<(), UnifyError> {
         match self.mode {
             PatternMode::Initial => {
                 if self.initial_pattern_vars.contains(name) {
@@ -42,11 +47,8 @@ impl<'a, 'b, 'c> PatternTyper<'a, 'b, 'c> {
                     });
                 }
                 self.initial_pattern_vars.insert(name.to_string());
-                self.environment.insert_variable(
-                    name.to_string(),
-                    ValueConstructorVariant::LocalVariable,
-                    typ,
-                );
+                self.environment
+                    .insert_local_variable(name.to_string(), location.clone(), typ);
                 Ok(())
             }
 
@@ -174,14 +176,18 @@ impl<'a, 'b, 'c> PatternTyper<'a, 'b, 'c> {
         match pattern {
             Pattern::Discard { name, location } => Ok(Pattern::Discard { name, location }),
 
-            Pattern::Var { name, location } => {
-                self.insert_variable(name.as_ref(), typ)
+            Pattern::Var { name, location, .. } => {
+                self.insert_variable(name.as_ref(), &location, typ.clone())
                     .map_err(|e| convert_unify_error(e, &location))?;
-                Ok(Pattern::Var { name, location })
+                Ok(Pattern::Var {
+                    name,
+                    location,
+                    typ,
+                })
             }
 
             Pattern::VarCall { name, location, .. } => {
-                let ValueConstructor { typ, .. } = self
+                let ValueConstructor { typ, origin, .. } = self
                     .environment
                     .get_variable(&name)
                     .cloned()
@@ -195,6 +201,7 @@ impl<'a, 'b, 'c> PatternTyper<'a, 'b, 'c> {
                             .map(|t| t.to_string())
                             .collect(),
                     })?;
+                self.environment.local_variable_used(&name, &origin);
                 let typ = self.environment.instantiate(
                     typ,
                     self.environment.level,
@@ -212,10 +219,19 @@ impl<'a, 'b, 'c> PatternTyper<'a, 'b, 'c> {
                 })
             }
 
-            Pattern::Let { name, pattern, .. } => {
-                self.insert_variable(name.as_ref(), typ.clone())
+            Pattern::Let {
+                name,
+                location,
+                pattern,
+            } => {
+                self.insert_variable(name.as_ref(), &location, typ.clone())
                     .map_err(|e| convert_unify_error(e, pattern.location()))?;
-                self.unify(*pattern, typ)
+                let pattern = self.unify(*pattern, typ)?;
+                Ok(Pattern::Let {
+                    name,
+                    location,
+                    pattern: Box::new(pattern),
+                })
             }
 
             Pattern::Int { location, value } => {
@@ -324,6 +340,10 @@ impl<'a, 'b, 'c> PatternTyper<'a, 'b, 'c> {
                 with_spread,
                 ..
             } => {
+                match &module {
+                    None => self.environment.value_used(&name),
+                    Some(module) => self.environment.module_used(module),
+                }
                 let cons = self
                     .environment
                     .get_value_constructor(module.as_ref(), &name)
@@ -1,4 +1,4 @@
-use super::{Environment, Type, TypeConstructor, ValueConstructorVariant};
+use super::{Environment, Type, TypeConstructor, TypeValueConstructor, ValueConstructorVariant};
 use crate::error::GleamExpect;
 use std::sync::Arc;
 
@@ -91,6 +91,23 @@ pub fn utf_codepoint() -> Arc<Type> {
     })
 }
 
+/// The constructors of a custom type defined in the prelude, which are kept
+/// apart from those of the module being checked so that a module can define
+/// a type of the same name.
+///
+pub fn type_constructors(name: &str) -> Option<Vec<TypeValueConstructor>> {
+    let constructor = |name: &str, arity| TypeValueConstructor {
+        name: name.to_string(),
+        arity,
+    };
+    match name {
+        "Bool" => Some(vec![constructor("True", 0), constructor("False", 0)]),
+        "Result" => Some(vec![constructor("Ok", 1), constructor("Error", 1)]),
+        "Nil" => Some(vec![constructor("Nil", 0)]),
+        _ => None,
+    }
+}
+
 pub fn register_prelude<'a, 'b>(mut typer: Environment<'a, 'b>) -> Environment<'a, 'b> {
     typer
         .insert_type_constructor(
@@ -112,6 +129,8 @@ pub fn register_prelude<'a, 'b>(mut typer: Environment<'a, 'b>) -> Environment<'
             field_map: None,
         },
         bool(),
+        Default::default(),
+        vec![],
     );
     typer.insert_variable(
         "False".to_string(),
@@ -120,6 +139,8 @@ pub fn register_prelude<'a, 'b>(mut typer: Environment<'a, 'b>) -> Environment<'
             field_map: None,
         },
         bool(),
+        Default::default(),
+        vec![],
     );
     typer
         .insert_type_constructor(
@@ -196,6 +217,8 @@ pub fn register_prelude<'a, 'b>(mut typer: Environment<'a, 'b>) -> Environment<'
             field_map: None,
         },
         nil(),
+        Default::default(),
+        vec![],
     );
     typer
         .insert_type_constructor(
@@ -245,6 +268,8 @@ pub fn register_prelude<'a, 'b>(mut typer: Environment<'a, 'b>) -> Environment<'
             field_map: None,
         },
         fn_(vec![ok.clone()], result(ok, error)),
+        Default::default(),
+        vec![],
     );
 
     let ok = typer.new_generic_var();
@@ -256,6 +281,8 @@ pub fn register_prelude<'a, 'b>(mut typer: Environment<'a, 'b>) -> Environment<'
             field_map: None,
         },
         fn_(vec![error.clone()], result(ok, error)),
+        Default::default(),
+        vec![],
     );
 
     typer
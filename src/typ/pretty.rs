@@ -199,7 +199,7 @@ fn next_letter_test() {
 
 #[test]
 fn pretty_print_test() {
-    use std::cell::RefCell;
+    use super::TypeVarCell;
     use std::sync::Arc;
 
     macro_rules! assert_string {
@@ -267,7 +267,7 @@ fn pretty_print_test() {
     );
     assert_string!(
         Type::Var {
-            typ: Arc::new(RefCell::new(TypeVar::Link {
+            typ: Arc::new(TypeVarCell::new(TypeVar::Link {
                 typ: Arc::new(Type::App {
                     args: vec![],
                     module: vec!["whatever".to_string()],
@@ -280,17 +280,17 @@ fn pretty_print_test() {
     );
     assert_string!(
         Type::Var {
-            typ: Arc::new(RefCell::new(TypeVar::Unbound { level: 1, id: 2231 })),
+            typ: Arc::new(TypeVarCell::new(TypeVar::Unbound { level: 1, id: 2231 })),
         },
         "a",
     );
     assert_string!(
         crate::typ::fn_(
             vec![Arc::new(Type::Var {
-                typ: Arc::new(RefCell::new(TypeVar::Unbound { level: 1, id: 78 })),
+                typ: Arc::new(TypeVarCell::new(TypeVar::Unbound { level: 1, id: 78 })),
             })],
             Arc::new(Type::Var {
-                typ: Arc::new(RefCell::new(TypeVar::Unbound { level: 1, id: 2 })),
+                typ: Arc::new(TypeVarCell::new(TypeVar::Unbound { level: 1, id: 2 })),
             }),
         ),
         "fn(a) -> b",
@@ -298,10 +298,10 @@ fn pretty_print_test() {
     assert_string!(
         crate::typ::fn_(
             vec![Arc::new(Type::Var {
-                typ: Arc::new(RefCell::new(TypeVar::Generic { id: 78 })),
+                typ: Arc::new(TypeVarCell::new(TypeVar::Generic { id: 78 })),
             })],
             Arc::new(Type::Var {
-                typ: Arc::new(RefCell::new(TypeVar::Generic { id: 2 })),
+                typ: Arc::new(TypeVarCell::new(TypeVar::Generic { id: 2 })),
             }),
         ),
         "fn(a) -> b",
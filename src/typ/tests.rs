@@ -139,6 +139,7 @@ fn infer_module_type_retention_test() {
             types: HashMap::new(), // Core type constructors like String and Int are not included
             values: HashMap::new(),
             accessors: HashMap::new(),
+            types_constructors: HashMap::new(),
         }
     );
 }
@@ -787,7 +788,7 @@ fn infer_error_test() {
             }),
             given: Arc::new(Type::Fn {
                 args: vec![Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link { typ: int() })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: int() })),
                 })],
                 retrn: int(),
             }),
@@ -937,10 +938,10 @@ fn infer_error_test() {
             expected: int(),
             given: tuple(vec![
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Unbound { id: 9, level: 1 })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Unbound { id: 9, level: 1 })),
                 }),
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Unbound { id: 10, level: 1 })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Unbound { id: 10, level: 1 })),
                 })
             ]),
         },
@@ -951,10 +952,10 @@ fn infer_error_test() {
         Error::CouldNotUnify {
             location: SrcSpan { start: 9, end: 12 },
             expected: list(Arc::new(Type::Var {
-                typ: Arc::new(RefCell::new(TypeVar::Link { typ: float() }))
+                typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: float() }))
             })),
             given: list(Arc::new(Type::Var {
-                typ: Arc::new(RefCell::new(TypeVar::Link { typ: int() }))
+                typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: int() }))
             }))
         },
     );
@@ -1277,7 +1278,7 @@ fn infer_error_test() {
             label: "field".to_string(),
             fields: vec![],
             typ: Arc::new(Type::Var {
-                typ: Arc::new(RefCell::new(TypeVar::Generic { id: 7 })),
+                typ: Arc::new(TypeVarCell::new(TypeVar::Generic { id: 7 })),
             }),
         },
     );
@@ -1297,26 +1298,26 @@ fn infer_error_test() {
             location: SrcSpan { start: 17, end: 34 },
             expected: result(
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link {
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link {
                         typ: Arc::new(Type::Var {
-                            typ: Arc::new(RefCell::new(TypeVar::Unbound { id: 8, level: 1 }))
+                            typ: Arc::new(TypeVarCell::new(TypeVar::Unbound { id: 8, level: 1 }))
                         })
                     })),
                 }),
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link { typ: int() })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: int() })),
                 }),
             ),
             given: result(
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link {
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link {
                         typ: Arc::new(Type::Var {
-                            typ: Arc::new(RefCell::new(TypeVar::Unbound { id: 8, level: 1 }))
+                            typ: Arc::new(TypeVarCell::new(TypeVar::Unbound { id: 8, level: 1 }))
                         })
                     })),
                 }),
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link { typ: float() })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: float() })),
                 }),
             ),
         },
@@ -1328,22 +1329,22 @@ fn infer_error_test() {
             location: SrcSpan { start: 17, end: 26 },
             expected: result(
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link {
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link {
                         typ: Arc::new(Type::Var {
-                            typ: Arc::new(RefCell::new(TypeVar::Unbound { id: 12, level: 1 }))
+                            typ: Arc::new(TypeVarCell::new(TypeVar::Unbound { id: 12, level: 1 }))
                         })
                     })),
                 }),
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link { typ: int() })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: int() })),
                 }),
             ),
             given: result(
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Unbound { id: 12, level: 1 }))
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Unbound { id: 12, level: 1 }))
                 }),
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link { typ: float() })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: float() })),
                 }),
             ),
         },
@@ -1355,10 +1356,10 @@ fn infer_error_test() {
             location: SrcSpan { start: 17, end: 18 },
             expected: result(
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Unbound { id: 11, level: 1 }))
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Unbound { id: 11, level: 1 }))
                 }),
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link { typ: int() })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: int() })),
                 }),
             ),
             given: int(),
@@ -1371,18 +1372,18 @@ fn infer_error_test() {
             location: SrcSpan { start: 17, end: 34 },
             expected: result(
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link { typ: int() }))
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: int() }))
                 }),
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link { typ: int() })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: int() })),
                 }),
             ),
             given: result(
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link { typ: int() }))
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: int() }))
                 }),
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link { typ: float() })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: float() })),
                 }),
             ),
         },
@@ -1394,22 +1395,22 @@ fn infer_error_test() {
             location: SrcSpan { start: 39, end: 56 },
             expected: result(
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link {
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link {
                         typ: Arc::new(Type::Var {
-                            typ: Arc::new(RefCell::new(TypeVar::Unbound { id: 14, level: 1 }))
+                            typ: Arc::new(TypeVarCell::new(TypeVar::Unbound { id: 14, level: 1 }))
                         })
                     }))
                 }),
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link { typ: int() })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: int() })),
                 }),
             ),
             given: result(
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Unbound { id: 14, level: 1 }))
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Unbound { id: 14, level: 1 }))
                 }),
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Link { typ: string() })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: string() })),
                 }),
             ),
         },
@@ -2000,9 +2001,12 @@ fn infer_module_error_test() {
                 .parse(&src)
                 .expect("syntax error");
             ast.name = vec!["my_module".to_string()];
-            let ast = infer_module(&mut 0, ast, &HashMap::new(), &mut vec![])
-                .expect_err("should infer an error");
-            assert_eq!(($src, sort_options($error)), ($src, sort_options(ast)));
+            let errors: Vec<_> = infer_module(&mut 0, ast, &HashMap::new(), &mut vec![])
+                .expect_err("should infer an error")
+                .into_iter()
+                .map(sort_options)
+                .collect();
+            assert_eq!(($src, vec![sort_options($error)]), ($src, errors));
         };
 
         ($src:expr) => {
@@ -2123,10 +2127,10 @@ fn demo() {
             location: SrcSpan { start: 33, end: 46 },
             expected: tuple(vec![
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Generic { id: 10 }))
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Generic { id: 10 }))
                 }),
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Generic { id: 10 }))
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Generic { id: 10 }))
                 })
             ]),
             given: tuple(vec![int(), float()]),
@@ -2151,10 +2155,10 @@ fn demo() {
             location: SrcSpan { start: 65, end: 86 },
             expected: tuple(vec![
                 Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Generic { id: 11 }))
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Generic { id: 11 }))
                 }),
                 list(Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Generic { id: 11 }))
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Generic { id: 11 }))
                 }))
             ]),
             given: tuple(vec![string(), list(int())]),
@@ -2490,7 +2494,7 @@ pub fn main(box: Box(Box(Int))) { box.inner.unknown }
             label: "unknown".to_string(),
             fields: vec!["inner".to_string()],
             typ: Arc::new(Type::Var {
-                typ: Arc::new(RefCell::new(TypeVar::Link {
+                typ: Arc::new(TypeVarCell::new(TypeVar::Link {
                     typ: Arc::new(Type::App {
                         args: vec![int()],
                         public: true,
@@ -2603,7 +2607,7 @@ fn main() {
                 module: vec!["my_module".to_string()],
                 name: "Box".to_string(),
                 args: vec![Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Generic { id: 11 })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Generic { id: 11 })),
                 })]
             }),
             given: Arc::new(Type::App {
@@ -2611,7 +2615,7 @@ fn main() {
                 module: vec!["my_module".to_string()],
                 name: "Box".to_string(),
                 args: vec![Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Generic { id: 9 })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Generic { id: 9 })),
                 })]
             }),
         },
@@ -2671,10 +2675,10 @@ fn main() {
         Error::CouldNotUnify {
             location: SrcSpan { start: 31, end: 46 },
             expected: list(Arc::new(Type::Var {
-                typ: Arc::new(RefCell::new(TypeVar::Link { typ: int() }))
+                typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: int() }))
             })),
             given: list(Arc::new(Type::Var {
-                typ: Arc::new(RefCell::new(TypeVar::Link { typ: float() }))
+                typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: float() }))
             }))
         }
     );
@@ -2750,7 +2754,7 @@ fn x() {
                 module: vec!["my_module".to_string()],
                 name: "Box".to_string(),
                 args: vec![Arc::new(Type::Var {
-                    typ: Arc::new(RefCell::new(TypeVar::Generic { id: 9 })),
+                    typ: Arc::new(TypeVarCell::new(TypeVar::Generic { id: 9 })),
                 })]
             }),
         },
@@ -2899,10 +2903,10 @@ fn x() {
                 end: 158,
             },
             expected: Arc::new(Type::Var {
-                typ: Arc::new(RefCell::new(TypeVar::Generic { id: 9 })),
+                typ: Arc::new(TypeVarCell::new(TypeVar::Generic { id: 9 })),
             }),
             given: Arc::new(Type::Var {
-                typ: Arc::new(RefCell::new(TypeVar::Generic { id: 11 })),
+                typ: Arc::new(TypeVarCell::new(TypeVar::Generic { id: 11 })),
             }),
         },
     );
@@ -2950,6 +2954,184 @@ fn x() {
             location: SrcSpan { start: 14, end: 15 },
         },
     );
+
+    assert_error!(
+        "type Thing { One Two(Int) Three(Bool, Int) }
+fn go(x) { case x { One -> 1 Two(_) -> 2 } }",
+        Error::InexhaustiveCaseExpression {
+            location: SrcSpan { start: 56, end: 87 },
+            missing: vec!["Three(_, _)".to_string()],
+        },
+    );
+
+    assert_error!(
+        "type Thing { One Two(Int) Three(Bool, Int) }
+fn go(x) { case x { One | Two(_) -> 1 Three(True, _) -> 2 } }",
+        Error::InexhaustiveCaseExpression {
+            location: SrcSpan {
+                start: 56,
+                end: 104
+            },
+            missing: vec!["Three(False, _)".to_string()],
+        },
+    );
+}
+
+#[test]
+fn infer_module_multiple_errors_test() {
+    macro_rules! assert_errors {
+        ($src:expr, $errors:expr $(,)?) => {
+            let (src, _) = crate::parser::strip_extra($src);
+            let mut ast = crate::grammar::ModuleParser::new()
+                .parse(&src)
+                .expect("syntax error");
+            ast.name = vec!["my_module".to_string()];
+            let errors: Vec<_> = infer_module(&mut 0, ast, &HashMap::new(), &mut vec![])
+                .expect_err("should infer an error")
+                .into_iter()
+                .map(sort_options)
+                .collect();
+            let expected: Vec<_> = $errors.into_iter().map(sort_options).collect();
+            assert_eq!(($src, expected), ($src, errors));
+        };
+    }
+
+    // Each statement with an error is reported
+    assert_errors!(
+        "fn a() { 1 + 1.0 } fn b() { x }",
+        vec![
+            Error::CouldNotUnify {
+                location: SrcSpan { start: 13, end: 16 },
+                expected: int(),
+                given: float(),
+            },
+            Error::UnknownVariable {
+                location: SrcSpan { start: 28, end: 29 },
+                name: "x".to_string(),
+                variables: env_vars_with(&["a", "b"]),
+            },
+        ],
+    );
+
+    // A value that could not be inferred does not cause errors where it is used
+    assert_errors!(
+        "fn a() { 1 + 1.0 } fn b() { a() + 1 } fn c() { a() +. 1.0 }",
+        vec![Error::CouldNotUnify {
+            location: SrcSpan { start: 13, end: 16 },
+            expected: int(),
+            given: float(),
+        }],
+    );
+
+    // Types that cannot be registered are reported alongside other errors
+    assert_errors!(
+        "type A = B fn a() { 1 + 1.0 }",
+        vec![
+            Error::UnknownType {
+                location: SrcSpan { start: 9, end: 10 },
+                name: "B".to_string(),
+                types: env_types(),
+            },
+            Error::CouldNotUnify {
+                location: SrcSpan { start: 24, end: 27 },
+                expected: int(),
+                given: float(),
+            },
+        ],
+    );
+}
+
+#[test]
+fn infer_case_exhaustiveness_error_test() {
+    assert_error!(
+        "case True { True -> 1 }",
+        Error::InexhaustiveCaseExpression {
+            location: SrcSpan { start: 0, end: 23 },
+            missing: vec!["False".to_string()],
+        },
+    );
+
+    assert_error!(
+        "case Ok(1) { Ok(x) -> x }",
+        Error::InexhaustiveCaseExpression {
+            location: SrcSpan { start: 0, end: 25 },
+            missing: vec!["Error(_)".to_string()],
+        },
+    );
+
+    assert_error!(
+        "case [1] { [] -> 1 [x] -> x }",
+        Error::InexhaustiveCaseExpression {
+            location: SrcSpan { start: 0, end: 29 },
+            missing: vec!["[_, _, .._]".to_string()],
+        },
+    );
+
+    assert_error!(
+        "case 1 { 1 -> 1 2 -> 2 }",
+        Error::InexhaustiveCaseExpression {
+            location: SrcSpan { start: 0, end: 24 },
+            missing: vec!["_".to_string()],
+        },
+    );
+
+    assert_error!(
+        "case True, False { True, _ -> 1 _, True -> 2 }",
+        Error::InexhaustiveCaseExpression {
+            location: SrcSpan { start: 0, end: 46 },
+            missing: vec!["False, False".to_string()],
+        },
+    );
+
+    assert_error!(
+        "case tuple(True, 1) { tuple(False, _) -> 1 }",
+        Error::InexhaustiveCaseExpression {
+            location: SrcSpan { start: 0, end: 44 },
+            missing: vec!["tuple(True, _)".to_string()],
+        },
+    );
+
+    // Clauses with guards are not considered to cover any values
+    assert_error!(
+        "case 1 { x if x > 0 -> 1 }",
+        Error::InexhaustiveCaseExpression {
+            location: SrcSpan { start: 0, end: 26 },
+            missing: vec!["_".to_string()],
+        },
+    );
+}
+
+#[test]
+fn infer_case_exhaustiveness_shadowed_prelude_type_test() {
+    let infer = |src: &str| {
+        let mut ast = crate::grammar::ModuleParser::new()
+            .parse(src)
+            .expect("syntax error");
+        ast.name = vec!["my_module".to_string()];
+        infer_module(&mut 0, ast, &HashMap::new(), &mut vec![]).map(|_| ())
+    };
+
+    // A type with the same name as one in the prelude does not replace the
+    // constructors of the prelude type
+    assert_eq!(
+        infer(
+            "pub type Result(a, b) { Yes No }
+pub fn main(x) { case Ok(x) { Ok(y) -> y Error(_) -> 0 } }
+pub fn is(x) { case x { Yes -> 1 No -> 2 } }"
+        ),
+        Ok(())
+    );
+
+    assert_eq!(
+        infer(
+            "pub type Result(a, b) { Yes No }
+pub fn is(x) { case x { Yes -> 1 } }"
+        ),
+        Err(vec![Error::InexhaustiveCaseExpression {
+            location: SrcSpan { start: 48, end: 67 },
+            missing: vec!["No".to_string()],
+        }])
+    );
 }
 
 macro_rules! assert_warning {
@@ -2989,7 +3171,7 @@ fn todo_warning_test() {
         Warning::Todo {
             location: SrcSpan { start: 17, end: 21 },
             typ: Arc::new(Type::Var {
-                typ: Arc::new(RefCell::new(TypeVar::Link { typ: int() })),
+                typ: Arc::new(TypeVarCell::new(TypeVar::Link { typ: int() })),
             }),
         },
     );
@@ -3011,8 +3193,104 @@ fn main() { foo(); 5 }",
     assert_no_warnings!(
         "
 fn foo() { Ok(5) }
-fn main() { let _ = foo(); 5 }",
+pub fn main() { let _ = foo(); 5 }",
+    );
+}
+
+#[test]
+fn unreachable_case_clause_warnings_test() {
+    assert_warning!(
+        "pub fn main(x) { case x { _ -> 1 Ok(y) -> y } }",
+        Warning::UnreachableCaseClause {
+            location: SrcSpan { start: 33, end: 43 },
+        },
+    );
+
+    assert_warning!(
+        "pub fn main(x) { case x { True -> 1 False -> 2 True -> 3 } }",
+        Warning::UnreachableCaseClause {
+            location: SrcSpan { start: 47, end: 56 },
+        },
+    );
+
+    assert_warning!(
+        "pub fn main(x) { case x { [] -> 1 [_, ..] -> 2 [_] | [] -> 3 } }",
+        Warning::UnreachableCaseClause {
+            location: SrcSpan { start: 47, end: 60 },
+        },
+    );
+
+    assert_warning!(
+        "pub fn main(x, y) { case x, y { 1, _ -> 1 _, 2 -> 2 1, 2 -> 3 _, _ -> 4 } }",
+        Warning::UnreachableCaseClause {
+            location: SrcSpan { start: 52, end: 61 },
+        },
+    );
+
+    // A clause is reachable if any of its alternative patterns is reachable
+    assert_no_warnings!("pub fn main(x) { case x { 1 -> 1 1 | 2 -> 2 _ -> 3 } }");
+
+    // Clauses with guards do not make later clauses unreachable
+    assert_no_warnings!("pub fn main(x) { case x { y if y > 1 -> 1 _ -> 2 } }");
+}
+
+#[test]
+fn unused_variable_warnings_test() {
+    assert_warning!(
+        "pub fn main() { let x = 1 2 }",
+        Warning::UnusedVariable {
+            location: SrcSpan { start: 20, end: 21 },
+            name: "x".to_string(),
+        },
+    );
+
+    assert_warning!(
+        "pub fn main(x) { 1 }",
+        Warning::UnusedVariable {
+            location: SrcSpan { start: 12, end: 13 },
+            name: "x".to_string(),
+        },
     );
+
+    assert_warning!(
+        "pub fn main(x) { case x { Ok(y) -> 1 Error(_) -> 2 } }",
+        Warning::UnusedVariable {
+            location: SrcSpan { start: 29, end: 30 },
+            name: "y".to_string(),
+        },
+    );
+
+    assert_warning!(
+        "pub fn main() { fn(x) { 1 } }",
+        Warning::UnusedVariable {
+            location: SrcSpan { start: 19, end: 20 },
+            name: "x".to_string(),
+        },
+    );
+
+    // The location of an `as` name is that of the `as name` part
+    assert_warning!(
+        "pub fn main(x) { case x { [_] as y -> 1 _ -> 2 } }",
+        Warning::UnusedVariable {
+            location: SrcSpan { start: 30, end: 34 },
+            name: "y".to_string(),
+        },
+    );
+
+    // A shadowed variable is unused if it is not used before being shadowed
+    assert_warning!(
+        "pub fn main() { let x = 1 let x = 2 x }",
+        Warning::UnusedVariable {
+            location: SrcSpan { start: 20, end: 21 },
+            name: "x".to_string(),
+        },
+    );
+
+    assert_no_warnings!("pub fn main(x) { let y = x y }");
+    assert_no_warnings!("pub fn main() { let x = 1 let x = x + 1 x }");
+    assert_no_warnings!("pub fn main(x) { case x { y if y > 1 -> 1 _ -> 2 } }");
+    assert_no_warnings!("pub fn main(x, n) { let <<a:size(n)>> = x a }");
+    assert_no_warnings!("pub fn main(_x) { let _y = 1 2 }");
 }
 
 #[test]
@@ -3101,9 +3379,143 @@ fn unused_type_warnings_test() {
     assert_no_warnings!("pub type Y = Int");
 
     // Used typed are not warned for
-    assert_no_warnings!("type Y { Y } fn run(x: Y) { x }");
-    assert_no_warnings!("external type Y fn run(x: Y) { x }");
-    assert_no_warnings!("type Y = Int fn run(x: Y) { x }");
+    assert_no_warnings!("type Y { Y } fn run(x: Y) { x } pub fn main() { let _ = run 1 }");
+    assert_no_warnings!("external type Y fn run(x: Y) { x } pub fn main() { let _ = run 1 }");
+    assert_no_warnings!("type Y = Int fn run(x: Y) { x } pub fn main() { let _ = run 1 }");
+}
+
+#[test]
+fn unused_private_value_warnings_test() {
+    assert_warning!(
+        "fn unused() { 1 }",
+        Warning::UnusedPrivateFunction {
+            name: "unused".to_string(),
+            location: SrcSpan { start: 0, end: 11 },
+        }
+    );
+
+    assert_warning!(
+        "const unused = 1",
+        Warning::UnusedPrivateModuleConstant {
+            name: "unused".to_string(),
+            location: SrcSpan { start: 0, end: 16 },
+        }
+    );
+
+    // Recursive calls do not count as a use
+    assert_warning!(
+        "fn unused(x) { unused(x) }",
+        Warning::UnusedPrivateFunction {
+            name: "unused".to_string(),
+            location: SrcSpan { start: 0, end: 12 },
+        }
+    );
+
+    // Pub values are not warned for
+    assert_no_warnings!("pub fn main() { 1 }");
+    assert_no_warnings!("pub const one = 1");
+
+    // Used values are not warned for
+    assert_no_warnings!("fn one() { 1 } pub fn main() { one() }");
+    assert_no_warnings!("const one = 1 pub fn main() { one }");
+    assert_no_warnings!("fn one() { 1 } pub fn main() { one }");
+}
+
+#[test]
+fn unused_import_warnings_test() {
+    macro_rules! assert_warnings_with_import {
+        ($src:expr, $warnings:expr $(,)?) => {
+            let dep = "pub type Box { Box(Int) } pub fn box(x) { Box(x) } pub const one = 1";
+            let mut dep_ast = crate::grammar::ModuleParser::new()
+                .parse(dep)
+                .expect("syntax error");
+            dep_ast.name = vec!["other".to_string()];
+            let dep_module = infer_module(&mut 0, dep_ast, &HashMap::new(), &mut vec![])
+                .expect("should successfully infer");
+            let mut modules = HashMap::new();
+            let _ = modules.insert("other".to_string(), (Origin::Src, dep_module.type_info));
+
+            let (src, _) = crate::parser::strip_extra($src);
+            let mut ast = crate::grammar::ModuleParser::new()
+                .parse(&src)
+                .expect("syntax error");
+            ast.name = vec!["my_module".to_string()];
+            let mut warnings = vec![];
+            let _ = infer_module(&mut 0, ast, &modules, &mut warnings)
+                .expect("should successfully infer");
+            let expected: Vec<Warning> = $warnings;
+            assert_eq!(($src, expected), ($src, warnings));
+        };
+    }
+
+    assert_warnings_with_import!(
+        "import other",
+        vec![Warning::UnusedImportedModule {
+            name: "other".to_string(),
+            location: SrcSpan { start: 7, end: 12 },
+        }],
+    );
+
+    assert_warnings_with_import!(
+        "import other as thing",
+        vec![Warning::UnusedImportedModule {
+            name: "thing".to_string(),
+            location: SrcSpan { start: 7, end: 13 },
+        }],
+    );
+
+    assert_warnings_with_import!(
+        "import other.{box}",
+        vec![Warning::UnusedImportedValue {
+            name: "box".to_string(),
+            location: SrcSpan { start: 14, end: 17 },
+        }],
+    );
+
+    assert_warnings_with_import!(
+        "import other.{box as make} pub fn main() { other.one }",
+        vec![Warning::UnusedImportedValue {
+            name: "make".to_string(),
+            location: SrcSpan { start: 14, end: 25 },
+        }],
+    );
+
+    // Unused items are warned for in the order they appear in the module
+    assert_warnings_with_import!(
+        "import other.{box, one} fn a() { 1 } const b = 1",
+        vec![
+            Warning::UnusedImportedValue {
+                name: "box".to_string(),
+                location: SrcSpan { start: 14, end: 17 },
+            },
+            Warning::UnusedImportedValue {
+                name: "one".to_string(),
+                location: SrcSpan { start: 19, end: 22 },
+            },
+            Warning::UnusedPrivateFunction {
+                name: "a".to_string(),
+                location: SrcSpan { start: 24, end: 30 },
+            },
+            Warning::UnusedPrivateModuleConstant {
+                name: "b".to_string(),
+                location: SrcSpan { start: 37, end: 48 },
+            },
+        ],
+    );
+
+    // Used imports are not warned for
+    assert_warnings_with_import!("import other pub fn main() { other.box(1) }", vec![]);
+    assert_warnings_with_import!("import other pub fn main(x: other.Box) { x }", vec![]);
+    assert_warnings_with_import!(
+        "import other pub fn main(x) { case x { other.Box(y) -> y } }",
+        vec![]
+    );
+    assert_warnings_with_import!("import other.{box} pub fn main() { box(1) }", vec![]);
+    assert_warnings_with_import!("import other.{one} pub fn main() { one }", vec![]);
+    assert_warnings_with_import!(
+        "import other.{Box} pub fn main(x) { case x { Box(y) -> y } }",
+        vec![]
+    );
 }
 
 fn env_types_with(things: &[&str]) -> Vec<String> {
@@ -1,10 +1,9 @@
 use crate::{
     cli,
-    diagnostic::{write, Diagnostic, Severity},
+    diagnostic::{write, Code, Diagnostic, DiagnosticBuffer, DiagnosticFormat, Severity},
     typ::pretty::Printer,
 };
 use std::path::PathBuf;
-use termcolor::Buffer;
 
 pub type Src = String;
 
@@ -18,7 +17,7 @@ pub enum Warning {
 }
 
 impl Warning {
-    pub fn pretty(&self, buffer: &mut Buffer) {
+    pub fn pretty(&self, buffer: &mut DiagnosticBuffer) {
         use crate::typ::Warning::*;
         use std::io::Write;
 
@@ -30,6 +29,7 @@ impl Warning {
             Warning::Type { path, src, warning } => match warning {
                 Todo { location, typ } => {
                     let diagnostic = Diagnostic {
+                        code: Code::W0001,
                         title: "Todo found".to_string(),
                         label: "".to_string(),
                         file: path.to_str().unwrap().to_string(),
@@ -52,6 +52,7 @@ your program.",
 
                 ImplicitlyDiscardedResult { location } => {
                     let diagnostic = Diagnostic {
+                        code: Code::W0002,
                         title: "Unused result value".to_string(),
                         label: "".to_string(),
                         file: path.to_str().unwrap().to_string(),
@@ -68,6 +69,7 @@ variable _ if you are sure the error does not matter.")
 
                 NoFieldsRecordUpdate { location } => {
                     let diagnostic = Diagnostic {
+                        code: Code::W0003,
                         title: "Fieldless record update".to_string(),
                         label: "".to_string(),
                         file: path.to_str().unwrap().to_string(),
@@ -83,6 +85,7 @@ record without modification. Add some fields or remove this update.")
 
                 AllFieldsRecordUpdate { location } => {
                     let diagnostic = Diagnostic {
+                        code: Code::W0004,
                         title: "Redundant record update".to_string(),
                         label: "".to_string(),
                         file: path.to_str().unwrap().to_string(),
@@ -98,6 +101,7 @@ be an update. Remove the update or remove fields that need to be copied.")
 
                 UnusedType { location, name } => {
                     let diagnostic = Diagnostic {
+                        code: Code::W0005,
                         title: "Unused type".to_string(),
                         label: "".to_string(),
                         file: path.to_str().unwrap().to_string(),
@@ -115,6 +119,7 @@ be an update. Remove the update or remove fields that need to be copied.")
 
                 UnusedConstructor { location, name } => {
                     let diagnostic = Diagnostic {
+                        code: Code::W0006,
                         title: "Unused constructor".to_string(),
                         label: "".to_string(),
                         file: path.to_str().unwrap().to_string(),
@@ -129,22 +134,130 @@ be an update. Remove the update or remove fields that need to be copied.")
                     )
                     .unwrap();
                 }
+
+                UnreachableCaseClause { location } => {
+                    let diagnostic = Diagnostic {
+                        code: Code::W0007,
+                        title: "Unreachable case clause".to_string(),
+                        label: "".to_string(),
+                        file: path.to_str().unwrap().to_string(),
+                        src: src.to_string(),
+                        location: location.clone(),
+                    };
+                    write(buffer, diagnostic, Severity::Warning);
+                    writeln!(buffer,
+"This case clause cannot be reached as all the values it matches are matched
+by earlier clauses. Remove this clause or move it before the clauses that
+match its values.")
+                    .unwrap();
+                }
+
+                UnusedVariable { location, name } => {
+                    let diagnostic = Diagnostic {
+                        code: Code::W0008,
+                        title: "Unused variable".to_string(),
+                        label: "".to_string(),
+                        file: path.to_str().unwrap().to_string(),
+                        src: src.to_string(),
+                        location: location.clone(),
+                    };
+                    write(buffer, diagnostic, Severity::Warning);
+                    writeln!(
+                        buffer,
+                        "The variable {} is never used. If this is intentional it can be
+prefixed with an underscore, as in _{}, to silence this warning.",
+                        name, name
+                    )
+                    .unwrap();
+                }
+
+                UnusedPrivateFunction { location, name } => {
+                    let diagnostic = Diagnostic {
+                        code: Code::W0009,
+                        title: "Unused private function".to_string(),
+                        label: "".to_string(),
+                        file: path.to_str().unwrap().to_string(),
+                        src: src.to_string(),
+                        location: location.clone(),
+                    };
+                    write(buffer, diagnostic, Severity::Warning);
+                    writeln!(
+                        buffer,
+                        "The private function {} is never used, it can be safely removed.",
+                        name
+                    )
+                    .unwrap();
+                }
+
+                UnusedPrivateModuleConstant { location, name } => {
+                    let diagnostic = Diagnostic {
+                        code: Code::W0010,
+                        title: "Unused private constant".to_string(),
+                        label: "".to_string(),
+                        file: path.to_str().unwrap().to_string(),
+                        src: src.to_string(),
+                        location: location.clone(),
+                    };
+                    write(buffer, diagnostic, Severity::Warning);
+                    writeln!(
+                        buffer,
+                        "The private constant {} is never used, it can be safely removed.",
+                        name
+                    )
+                    .unwrap();
+                }
+
+                UnusedImportedModule { location, name } => {
+                    let diagnostic = Diagnostic {
+                        code: Code::W0011,
+                        title: "Unused imported module".to_string(),
+                        label: "".to_string(),
+                        file: path.to_str().unwrap().to_string(),
+                        src: src.to_string(),
+                        location: location.clone(),
+                    };
+                    write(buffer, diagnostic, Severity::Warning);
+                    writeln!(
+                        buffer,
+                        "The module {} is imported but never used, it can be safely removed.",
+                        name
+                    )
+                    .unwrap();
+                }
+
+                UnusedImportedValue { location, name } => {
+                    let diagnostic = Diagnostic {
+                        code: Code::W0012,
+                        title: "Unused imported value".to_string(),
+                        label: "".to_string(),
+                        file: path.to_str().unwrap().to_string(),
+                        src: src.to_string(),
+                        location: location.clone(),
+                    };
+                    write(buffer, diagnostic, Severity::Warning);
+                    writeln!(
+                        buffer,
+                        "The value {} is imported but never used, it can be safely removed.",
+                        name
+                    )
+                    .unwrap();
+                }
             },
         }
     }
 
-    pub fn pretty_print(&self) {
+    pub fn print(&self, format: DiagnosticFormat) {
         let buffer_writer = cli::stderr_buffer_writer();
-        let mut buffer = buffer_writer.buffer();
+        let mut buffer = DiagnosticBuffer::new(format, &buffer_writer);
         self.pretty(&mut buffer);
-        buffer_writer.print(&buffer).unwrap();
+        buffer.print(&buffer_writer);
     }
 }
 
-pub fn print_all(analysed: &[crate::project::Analysed]) {
+pub fn print_all(analysed: &[crate::project::Analysed], format: DiagnosticFormat) {
     for a in analysed.iter() {
         for w in a.warnings.iter() {
-            w.pretty_print()
+            w.print(format)
         }
     }
 }